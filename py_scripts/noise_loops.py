@@ -0,0 +1,172 @@
+"""Synthesizes the café and street ambience loops played under the recordings.
+
+The café is a babble of overlapping voices with the clinks of cups, the street
+is the rumble of the traffic with cars passing by. Every event wraps around the
+end of the loop, so the loops are seamless and free of licensing concerns.
+"""
+import math
+import random
+import struct
+import wave
+
+SAMPLE_RATE = 16000
+LOOP_DURATION = 10.0
+NB_SAMPLES = int(SAMPLE_RATE * LOOP_DURATION)
+PEAK = 0.8
+
+# First two formants of a few vowels, in Hz
+VOWEL_FORMANTS = [(800, 1200), (400, 2000), (300, 2300), (500, 900), (350, 800), (600, 1700)]
+NB_VOICES = 7
+SYLLABLE_DURATION = (0.12, 0.28)
+PAUSE_DURATION = (0.3, 1.2)
+NB_CLINKS = 9
+NB_CARS = 6
+
+
+def add_wrapped(samples, start: int, event):
+    for i, value in enumerate(event):
+        samples[(start + i) % NB_SAMPLES] += value
+
+
+def resonator(frequency: float, bandwidth: float):
+    """Coefficients of a two-pole band-pass filter."""
+    r = math.exp(-math.pi * bandwidth / SAMPLE_RATE)
+    return 2 * r * math.cos(2 * math.pi * frequency / SAMPLE_RATE), -r * r, 1 - r
+
+
+def filter_looped(samples, coefficients):
+    """Runs the filter over the loop twice and keeps the second pass, whose state at the start
+    is the one at the end, so that the filtered loop stays seamless."""
+    a1, a2, gain = coefficients
+    y1 = y2 = 0.0
+    out = [0.0] * NB_SAMPLES
+    for _ in range(2):
+        for i, x in enumerate(samples):
+            y = gain * x + a1 * y1 + a2 * y2
+            y2, y1 = y1, y
+            out[i] = y
+    return out
+
+
+def low_pass_looped(samples, cutoff: float):
+    alpha = 1 - math.exp(-2 * math.pi * cutoff / SAMPLE_RATE)
+    y = 0.0
+    out = [0.0] * NB_SAMPLES
+    for _ in range(2):
+        for i, x in enumerate(samples):
+            y += alpha * (x - y)
+            out[i] = y
+    return out
+
+
+def syllable(rng: random.Random, f0: float):
+    duration = rng.uniform(*SYLLABLE_DURATION)
+    nb_samples = int(duration * SAMPLE_RATE)
+    first, second = rng.choice(VOWEL_FORMANTS)
+    filters = [resonator(first, 90), resonator(second, 120)]
+    glide = rng.uniform(-0.25, 0.25)
+    phase = 0.0
+    source = []
+    for i in range(nb_samples):
+        progress = i / nb_samples
+        phase += 2 * math.pi * f0 * (1 + glide * progress) / SAMPLE_RATE
+        # Glottal pulses approximated by a band-limited sawtooth
+        value = sum(math.sin(k * phase) / k for k in range(1, 12))
+        source.append(value * math.sin(math.pi * progress) ** 2)
+    out = [0.0] * nb_samples
+    for a1, a2, gain in filters:
+        y1 = y2 = 0.0
+        for i, x in enumerate(source):
+            y = gain * x + a1 * y1 + a2 * y2
+            y2, y1 = y1, y
+            out[i] += y
+    return out
+
+
+def voice(rng: random.Random, samples, loudness: float):
+    f0 = rng.uniform(95, 240)
+    position = rng.randrange(NB_SAMPLES)
+    end = position + NB_SAMPLES
+    while position < end:
+        # Phrases of a few syllables, then a pause
+        for _ in range(rng.randint(3, 9)):
+            event = syllable(rng, f0 * rng.uniform(0.9, 1.15))
+            add_wrapped(samples, position, [loudness * value for value in event])
+            position += len(event) + int(rng.uniform(0.01, 0.06) * SAMPLE_RATE)
+        position += int(rng.uniform(*PAUSE_DURATION) * SAMPLE_RATE)
+
+
+def clink(rng: random.Random):
+    nb_samples = int(0.25 * SAMPLE_RATE)
+    partials = [(rng.uniform(2200, 4800), rng.uniform(0.3, 1.0)) for _ in range(3)]
+    loudness = rng.uniform(0.05, 0.15)
+    return [
+        loudness
+        * math.exp(-i / (0.04 * SAMPLE_RATE))
+        * sum(
+            amplitude * math.sin(2 * math.pi * frequency * i / SAMPLE_RATE)
+            for frequency, amplitude in partials
+        )
+        for i in range(nb_samples)
+    ]
+
+
+def cafe(seed: int):
+    rng = random.Random(seed)
+    samples = [0.0] * NB_SAMPLES
+    for idx in range(NB_VOICES):
+        # The nearest voices are the loudest
+        voice(rng, samples, 1.0 / (1 + idx * 0.4))
+    for _ in range(NB_CLINKS):
+        add_wrapped(samples, rng.randrange(NB_SAMPLES), clink(rng))
+    room = low_pass_looped([rng.gauss(0, 1) for _ in range(NB_SAMPLES)], 400)
+    return [value + 0.3 * noise for value, noise in zip(samples, room)]
+
+
+def car(rng: random.Random, rumble):
+    """Envelope of a car passing by, and its engine hum."""
+    duration = rng.uniform(3.0, 6.0)
+    nb_samples = int(duration * SAMPLE_RATE)
+    engine = rng.uniform(35, 70)
+    loudness = rng.uniform(0.5, 1.0)
+    offset = rng.randrange(NB_SAMPLES)
+    phase = 0.0
+    event = []
+    for i in range(nb_samples):
+        progress = i / nb_samples
+        envelope = math.exp(-(((progress - 0.5) / 0.18) ** 2))
+        # The pitch drops as the car goes by
+        phase += 2 * math.pi * engine * (1.06 - 0.12 * progress) / SAMPLE_RATE
+        hum = sum(math.sin(k * phase) / k for k in range(1, 6))
+        tyres = rumble[(offset + i) % NB_SAMPLES]
+        event.append(loudness * envelope * (0.25 * hum + 3.0 * tyres))
+    return event
+
+
+def street(seed: int):
+    rng = random.Random(seed)
+    white = [rng.gauss(0, 1) for _ in range(NB_SAMPLES)]
+    # Distant traffic, a low rumble
+    samples = low_pass_looped(low_pass_looped(white, 150), 150)
+    samples = [2.0 * value for value in samples]
+    tyres = filter_looped(white, resonator(700, 900))
+    for _ in range(NB_CARS):
+        add_wrapped(samples, rng.randrange(NB_SAMPLES), car(rng, tyres))
+    return samples
+
+
+def write_wav(path: str, samples):
+    peak = max(abs(s) for s in samples)
+    with wave.open(path, "wb") as out_f:
+        out_f.setnchannels(1)
+        out_f.setsampwidth(2)
+        out_f.setframerate(SAMPLE_RATE)
+        out_f.writeframes(
+            b"".join(struct.pack("<h", int(s / peak * PEAK * 32767)) for s in samples)
+        )
+
+
+if __name__ == "__main__":
+    write_wav("../public/noise/cafe.wav", cafe(0))
+    write_wav("../public/noise/street.wav", street(1))
+    print("Done!")
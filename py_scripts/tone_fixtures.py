@@ -0,0 +1,105 @@
+"""Synthesizes the two-syllable WAV fixtures used by the tone classifier tests.
+
+Each syllable is a harmonic tone following an idealized Chao contour, so the
+fixtures are deterministic and free of licensing concerns.
+"""
+import math
+import random
+import struct
+import wave
+
+SAMPLE_RATE = 16000
+# Speaker range: Chao level 1 is 100 Hz, each level is 2.5 semitones higher
+BASE_FREQUENCY = 100.0
+SEMITONES_PER_LEVEL = 2.5
+SYLLABLE_DURATION = 0.3
+NEUTRAL_DURATION = 0.14
+GAP_DURATION = 0.07
+LEADING_SILENCE = 0.1
+
+TONE_LEVELS = {
+    "1": [5.0, 5.0],
+    "2": [3.0, 3.2, 5.0],
+    "3": [2.0, 1.0, 1.2, 3.5],
+    "4": [5.0, 4.8, 1.0],
+    "5": [2.5, 2.0],
+    # Half third tone, as pronounced before a non-third tone
+    "h": [2.0, 1.2, 1.0],
+}
+
+
+def level_to_frequency(level: float) -> float:
+    return BASE_FREQUENCY * 2 ** ((level - 1.0) * SEMITONES_PER_LEVEL / 12.0)
+
+
+def interpolate(levels, progress: float) -> float:
+    position = progress * (len(levels) - 1)
+    idx = min(int(position), len(levels) - 2)
+    frac = position - idx
+    return levels[idx] * (1 - frac) + levels[idx + 1] * frac
+
+
+def syllable(tone: str, duration: float, phase: float):
+    levels = TONE_LEVELS[tone]
+    nb_samples = int(duration * SAMPLE_RATE)
+    samples = []
+    for i in range(nb_samples):
+        progress = i / nb_samples
+        frequency = level_to_frequency(interpolate(levels, progress))
+        phase += 2 * math.pi * frequency / SAMPLE_RATE
+        value = sum(math.sin(k * phase) / k for k in range(1, 9))
+        attack = min(1.0, i / (0.02 * SAMPLE_RATE))
+        release = min(1.0, (nb_samples - i) / (0.04 * SAMPLE_RATE))
+        samples.append(0.3 * value * attack * release)
+    return samples, phase
+
+
+def silence(duration: float, rng: random.Random):
+    return [rng.uniform(-0.003, 0.003) for _ in range(int(duration * SAMPLE_RATE))]
+
+
+def utterance(tones: str, with_gap: bool, seed: int):
+    rng = random.Random(seed)
+    samples = silence(LEADING_SILENCE, rng)
+    phase = 0.0
+    for i, tone in enumerate(tones):
+        duration = NEUTRAL_DURATION if tone == "5" else SYLLABLE_DURATION
+        if i > 0:
+            if with_gap:
+                samples += silence(GAP_DURATION, rng)
+            else:
+                # Voiced transition: a short energy dip instead of a pause
+                dip = int(0.03 * SAMPLE_RATE)
+                for j in range(1, dip + 1):
+                    samples[-j] *= 0.15 + 0.85 * (j / dip)
+        syllable_samples, phase = syllable(tone, duration, phase)
+        samples += syllable_samples
+    samples += silence(LEADING_SILENCE, rng)
+    return [s + rng.uniform(-0.002, 0.002) for s in samples]
+
+
+def write_wav(path: str, samples):
+    with wave.open(path, "wb") as out_f:
+        out_f.setnchannels(1)
+        out_f.setsampwidth(2)
+        out_f.setframerate(SAMPLE_RATE)
+        out_f.writeframes(
+            b"".join(struct.pack("<h", int(max(-1.0, min(1.0, s)) * 32767)) for s in samples)
+        )
+
+
+FIXTURES = [
+    # (file name, tones, with gap)
+    ("fei1ji1", "11", True),
+    ("xue2xiao4", "24", True),
+    ("wen4ti2", "42", True),
+    ("lao3shi1", "h1", True),
+    ("ni2hao3", "23", False),
+    ("ma1ma", "15", True),
+    ("da4jia1", "41", False),
+]
+
+if __name__ == "__main__":
+    for seed, (name, tones, with_gap) in enumerate(FIXTURES):
+        write_wav(f"../tests/fixtures/{name}.wav", utterance(tones, with_gap, seed))
+    print("Done!")
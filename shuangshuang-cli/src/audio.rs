@@ -0,0 +1,77 @@
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use shuangshuang_core::exercise::HanziPair;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AudioError {
+    #[error("The audio player command is empty.")]
+    EmptyPlayerCommand,
+    #[error("Failed to run the audio player: {0}")]
+    PlayerFailed(#[from] std::io::Error),
+}
+
+/// How the terminal client makes the learner hear a hanzi pair
+pub trait AudioBackend {
+    /// Plays the recording at `url` of `hanzi_pair`
+    fn play(&mut self, url: &str, hanzi_pair: &HanziPair) -> Result<(), AudioError>;
+}
+
+/// Plays nothing and shows the hanzi instead, for drills where the learner reads the word and
+/// types its pinyin
+pub struct NoAudio<W: std::io::Write> {
+    pub output: W,
+}
+
+impl<W: std::io::Write> AudioBackend for NoAudio<W> {
+    fn play(&mut self, _url: &str, hanzi_pair: &HanziPair) -> Result<(), AudioError> {
+        writeln!(self.output, "{}", hanzi_pair.characters)?;
+        Ok(())
+    }
+}
+
+/// Plays the recording with an external command, the url is appended to its arguments, e.g.
+/// `mpv --really-quiet` or `ffplay -nodisp -autoexit -loglevel quiet`
+pub struct CommandPlayer {
+    program: String,
+    args: Vec<String>,
+}
+
+impl FromStr for CommandPlayer {
+    type Err = AudioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut splits = s.split_whitespace().map(str::to_string);
+        let program = splits.next().ok_or(AudioError::EmptyPlayerCommand)?;
+        Ok(Self {
+            program,
+            args: splits.collect(),
+        })
+    }
+}
+
+impl AudioBackend for CommandPlayer {
+    fn play(&mut self, url: &str, _hanzi_pair: &HanziPair) -> Result<(), AudioError> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .arg(url)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_player_parses_arguments() {
+        let player = CommandPlayer::from_str("ffplay -nodisp -autoexit").unwrap();
+        assert_eq!(player.program, "ffplay");
+        assert_eq!(player.args, vec!["-nodisp", "-autoexit"]);
+        assert!(CommandPlayer::from_str("  ").is_err());
+    }
+}
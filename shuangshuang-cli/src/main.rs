@@ -0,0 +1,238 @@
+use std::io::{stderr, stdin, stdout};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use shuangshuang_core::corpus::{
+    apply_pronunciation_overrides, parse_corpus, parse_pronunciation_overrides,
+};
+use shuangshuang_core::exercise::{
+    AudioQuality, ExerciseDifficulty, ExerciseParams, ExerciseSummary, ExerciseType, HanziPair,
+    ShuangElement, ShuffleMode,
+};
+use shuangshuang_core::session::{SessionEngine, SessionState};
+use thiserror::Error;
+
+mod audio;
+mod report;
+mod session;
+
+use crate::audio::{AudioBackend, AudioError, CommandPlayer, NoAudio};
+use crate::report::SummaryReport;
+use crate::session::run_session;
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Audio(#[from] AudioError),
+    #[error("Failed to write the JSON summary: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The corpus {0} has no valid hanzi pair.")]
+    EmptyCorpus(PathBuf),
+    #[error("The {0} exercise type is only available in the web app.")]
+    UnsupportedExerciseType(ExerciseType),
+    #[error("The {0} exercise type needs the recordings, --no-audio would show its answer.")]
+    NoAudioExerciseType(ExerciseType),
+}
+
+/// Practice Mandarin tone pairs in the terminal. Answers are graded like in the web app, the
+/// answer timer of the harder difficulties is not used.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// Path of the hanzi pairs corpus, hanzi_pairs.txt is read too
+    #[arg(long, default_value = "data/hanzi_pairs.jsonl")]
+    corpus: PathBuf,
+    /// Path of the pronunciations of the recordings that differ from the dictionary
+    #[arg(long, default_value = "data/pronunciation_overrides.txt")]
+    pronunciation_overrides: PathBuf,
+    /// Preset the other options start from: first_time, easy, normal, hard or native
+    #[arg(long, default_value = "easy")]
+    difficulty: ExerciseDifficulty,
+    /// Number of hanzi pairs to answer
+    #[arg(long)]
+    size: Option<u32>,
+    /// What to answer: tone_only, no_tone_pinyin or pinyin
+    #[arg(long)]
+    exercise_type: Option<ExerciseType>,
+    /// How pairs are picked: random or even
+    #[arg(long)]
+    shuffle: Option<ShuffleMode>,
+    /// Quality of the recordings: 18k-abr, 24k-abr, 64k or 96k
+    #[arg(long)]
+    audio_quality: Option<AudioQuality>,
+    /// Number of replays allowed per pair
+    #[arg(long)]
+    retries: Option<u32>,
+    /// Command playing a recording, its url is appended to the arguments
+    #[arg(long, default_value = "ffplay -nodisp -autoexit -loglevel quiet")]
+    player: String,
+    /// Also accept the other dictionary readings of a pair, not only the recorded one
+    #[arg(long)]
+    accept_alternative_readings: bool,
+    /// Only accept the characters of the recording in the hanzi exercise, not its homophones
+    #[arg(long)]
+    exact_hanzi_match: bool,
+    /// How many times a missed pair is asked, it comes back after the other pairs
+    #[arg(long)]
+    max_attempts: Option<u32>,
+    /// Once the session is over, ask again the pairs still missed
+    #[arg(long)]
+    review_mistakes: bool,
+    /// Show the hanzi instead of playing the recordings, not with the hanzi exercise type
+    #[arg(long)]
+    no_audio: bool,
+    /// Print the summary as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+impl Args {
+    fn get_exercise_params(&self) -> ExerciseParams {
+        let mut exercise_params = ExerciseParams::from(self.difficulty);
+        exercise_params.exercise_size = self.size.unwrap_or(exercise_params.exercise_size);
+        exercise_params.exercise_type = self.exercise_type.unwrap_or(exercise_params.exercise_type);
+        exercise_params.shuffle_mode = self.shuffle.unwrap_or(exercise_params.shuffle_mode);
+        exercise_params.audio_quality = self.audio_quality.unwrap_or(exercise_params.audio_quality);
+        exercise_params.audio_retries = self.retries.unwrap_or(exercise_params.audio_retries);
+        exercise_params.accept_alternative_readings = self.accept_alternative_readings;
+        exercise_params.exact_hanzi_match = self.exact_hanzi_match;
+        exercise_params.max_attempts = self.max_attempts.unwrap_or(exercise_params.max_attempts);
+        exercise_params
+    }
+}
+
+// Invalid lines are skipped like in the web app, which also works without overrides
+fn load_corpus(path: &PathBuf, overrides_path: &PathBuf) -> Result<Vec<HanziPair>, CliError> {
+    let text = std::fs::read_to_string(path)?;
+    let mut hanzi_pairs: Vec<HanziPair> = parse_corpus(&text)
+        .into_iter()
+        .filter_map(|(line_number, hanzi_pair)| match hanzi_pair {
+            Ok(hanzi_pair) => Some(hanzi_pair),
+            Err(e) => {
+                eprintln!("Skipping {} line {line_number}: {e}", path.display());
+                None
+            }
+        })
+        .collect();
+    if hanzi_pairs.is_empty() {
+        return Err(CliError::EmptyCorpus(path.clone()));
+    }
+    let overrides_text = match std::fs::read_to_string(overrides_path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let overrides: Vec<_> = parse_pronunciation_overrides(&overrides_text)
+        .into_iter()
+        .filter_map(
+            |(line_number, pronunciation_override)| match pronunciation_override {
+                Ok(pronunciation_override) => Some(pronunciation_override),
+                Err(e) => {
+                    eprintln!(
+                        "Skipping {} line {line_number}: {e}",
+                        overrides_path.display()
+                    );
+                    None
+                }
+            },
+        )
+        .collect();
+    apply_pronunciation_overrides(&mut hanzi_pairs, &overrides);
+    Ok(hanzi_pairs)
+}
+
+// The review only asks the mistakes, in order, so its answers replace them one by one. Pairs
+// left when the review is quit keep their answer from the session
+fn practice(
+    engine: &mut SessionEngine,
+    corpus: &[HanziPair],
+    exercise_params: &ExerciseParams,
+    review_mistakes: bool,
+    audio: &mut impl AudioBackend,
+) -> Result<Vec<ShuangElement>, CliError> {
+    let mut input = stdin().lock();
+    // The session talks on stderr so that stdout only holds the summary
+    let mut output = stderr();
+    run_session(engine, &mut input, &mut output, audio)?;
+    let mut shuang_elements = engine.get_summary().test_elements;
+    let mistakes = shuang_elements
+        .iter()
+        .filter(|elem| !elem.is_correct)
+        .count();
+    if review_mistakes && engine.state() == SessionState::Finished && mistakes > 0 {
+        eprintln!("Reviewing {mistakes} mistake(s).");
+        let mut review =
+            SessionEngine::from_mistakes(&shuang_elements, exercise_params).with_homophones(corpus);
+        run_session(&mut review, &mut input, &mut output, audio)?;
+        let mut reviewed_elements = review.get_summary().test_elements.into_iter();
+        for elem in shuang_elements.iter_mut().filter(|elem| !elem.is_correct) {
+            match reviewed_elements.next() {
+                Some(reviewed_elem) => *elem = reviewed_elem,
+                None => break,
+            }
+        }
+    }
+    Ok(shuang_elements)
+}
+
+fn run(args: Args) -> Result<(), CliError> {
+    let exercise_params = args.get_exercise_params();
+    if let ExerciseType::Production
+    | ExerciseType::MinimalPair
+    | ExerciseType::ReverseAudio
+    | ExerciseType::PinyinDrill
+    | ExerciseType::Mixed = exercise_params.exercise_type
+    {
+        return Err(CliError::UnsupportedExerciseType(
+            exercise_params.exercise_type,
+        ));
+    }
+    if args.no_audio && exercise_params.exercise_type == ExerciseType::Hanzi {
+        return Err(CliError::NoAudioExerciseType(exercise_params.exercise_type));
+    }
+    let corpus = load_corpus(&args.corpus, &args.pronunciation_overrides)?;
+    let mut engine = SessionEngine::from_corpus(&corpus, &exercise_params);
+    let shuang_elements = if args.no_audio {
+        let mut audio = NoAudio { output: stderr() };
+        practice(
+            &mut engine,
+            &corpus,
+            &exercise_params,
+            args.review_mistakes,
+            &mut audio,
+        )?
+    } else {
+        let mut audio: CommandPlayer = args.player.parse()?;
+        practice(
+            &mut engine,
+            &corpus,
+            &exercise_params,
+            args.review_mistakes,
+            &mut audio,
+        )?
+    };
+    let report = SummaryReport::new(
+        &ExerciseSummary::from(shuang_elements),
+        exercise_params.exercise_type,
+    );
+    if args.json {
+        serde_json::to_writer_pretty(stdout(), &report)?;
+        println!();
+    } else {
+        report.write_table(&mut stdout())?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
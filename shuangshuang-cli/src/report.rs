@@ -0,0 +1,269 @@
+use std::io::Write;
+
+use serde::Serialize;
+use shuangshuang_core::exercise::{ExerciseSummary, ExerciseType, FALLBACK_GLOSS_LANGUAGE};
+use shuangshuang_core::session::get_expected_answer;
+
+/// Columns taken by `text` in a terminal, the CJK characters being twice as wide as the others
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c {
+            '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{20000}'..='\u{3FFFD}' => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// `text` followed by the spaces filling the columns up to `width`
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{text}{}",
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ElementReport {
+    pub characters: String,
+    pub pronounced_pinyin: String,
+    pub alternative_readings: Vec<String>,
+    /// English meaning of the word, when the corpus has one
+    pub gloss: Option<String>,
+    pub expected_answer: String,
+    pub user_answer: String,
+    pub is_correct: bool,
+    /// The first answer, when the pair was asked again
+    pub first_answer: Option<String>,
+    pub attempts: u32,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ToneMistakesReport {
+    pub tone_pair: String,
+    pub mistakes: u32,
+}
+
+/// The `ExerciseSummary` of a session, as printed by `--json`
+#[derive(Serialize, PartialEq, Debug)]
+pub struct SummaryReport {
+    pub correct_answers: u32,
+    pub first_attempt_correct_answers: u32,
+    pub total_answers: usize,
+    pub correct_percentage: f32,
+    pub elements: Vec<ElementReport>,
+    pub tone_pair_mistakes: Vec<ToneMistakesReport>,
+}
+
+impl SummaryReport {
+    pub fn new(summary: &ExerciseSummary, exercise_type: ExerciseType) -> Self {
+        let mut tone_pair_mistakes: Vec<ToneMistakesReport> = summary
+            .tone_pair_mistakes
+            .iter()
+            .map(|(tone_pair, mistakes)| ToneMistakesReport {
+                tone_pair: format!("{}{}", tone_pair.0, tone_pair.1),
+                mistakes: *mistakes,
+            })
+            .collect();
+        tone_pair_mistakes.sort_by(|a, b| {
+            b.mistakes
+                .cmp(&a.mistakes)
+                .then(a.tone_pair.cmp(&b.tone_pair))
+        });
+        Self {
+            correct_answers: summary.correct_answers,
+            first_attempt_correct_answers: summary.first_attempt_correct_answers,
+            total_answers: summary.test_elements.len(),
+            correct_percentage: if summary.test_elements.is_empty() {
+                0.0
+            } else {
+                summary.get_correct_percentage()
+            },
+            elements: summary
+                .test_elements
+                .iter()
+                .map(|elem| ElementReport {
+                    characters: elem.hanzi_pair.characters.clone(),
+                    pronounced_pinyin: elem.hanzi_pair.pronounced_pinyin.clone(),
+                    alternative_readings: elem
+                        .hanzi_pair
+                        .alternative_readings
+                        .iter()
+                        .map(|reading| reading.pronounced_pinyin.clone())
+                        .collect(),
+                    gloss: elem
+                        .hanzi_pair
+                        .metadata
+                        .gloss(FALLBACK_GLOSS_LANGUAGE)
+                        .map(str::to_string),
+                    expected_answer: get_expected_answer(&elem.hanzi_pair, exercise_type),
+                    user_answer: elem.user_answer.clone(),
+                    is_correct: elem.is_correct,
+                    first_answer: elem.first_answer.clone(),
+                    attempts: elem.attempts,
+                })
+                .collect(),
+            tone_pair_mistakes,
+        }
+    }
+
+    /// Prints the report as a table, the hanzi come last as their width varies between
+    /// terminals
+    pub fn write_table(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let pinyin_width = self
+            .elements
+            .iter()
+            .map(|elem| display_width(&elem.pronounced_pinyin))
+            .chain([display_width("Pinyin")])
+            .max()
+            .unwrap_or_default();
+        let answer_width = self
+            .elements
+            .iter()
+            .map(|elem| display_width(&elem.expected_answer).max(display_width(&elem.user_answer)))
+            .chain([display_width("Expected")])
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            output,
+            "    | {} | {} | {} | Hanzi",
+            pad("Pinyin", pinyin_width),
+            pad("Expected", answer_width),
+            pad("Answer", answer_width)
+        )?;
+        for elem in self.elements.iter() {
+            writeln!(
+                output,
+                " {} | {} | {} | {} | {}{}{}",
+                if elem.is_correct { "✓ " } else { "✗ " },
+                pad(&elem.pronounced_pinyin, pinyin_width),
+                pad(&elem.expected_answer, answer_width),
+                pad(&elem.user_answer, answer_width),
+                elem.characters,
+                elem.gloss
+                    .as_ref()
+                    .map(|gloss| format!("  {gloss}"))
+                    .unwrap_or_default(),
+                elem.first_answer
+                    .as_ref()
+                    .map(|first_answer| format!(
+                        "  (first answer {first_answer}, {} attempts)",
+                        elem.attempts
+                    ))
+                    .unwrap_or_default(),
+            )?;
+        }
+        writeln!(
+            output,
+            "\nCorrect answers: {}/{} ({:.2}%)",
+            self.correct_answers, self.total_answers, self.correct_percentage
+        )?;
+        if self.first_attempt_correct_answers != self.correct_answers {
+            writeln!(
+                output,
+                "Correct at the first attempt: {}/{}",
+                self.first_attempt_correct_answers, self.total_answers
+            )?;
+        }
+        if !self.tone_pair_mistakes.is_empty() {
+            writeln!(output, "Incorrect tone pairs:")?;
+            for tone_mistakes in self.tone_pair_mistakes.iter() {
+                writeln!(
+                    output,
+                    "  {} => {}",
+                    tone_mistakes.tone_pair, tone_mistakes.mistakes
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shuangshuang_core::corpus::parse_hanzi_pair;
+    use shuangshuang_core::exercise::ShuangElement;
+
+    fn get_report() -> SummaryReport {
+        let summary = ExerciseSummary::from(vec![
+            ShuangElement {
+                hanzi_pair: parse_hanzi_pair("学校 xue2xiao4 24").unwrap(),
+                user_answer: "24".to_string(),
+                is_correct: true,
+                first_answer: Some("22".to_string()),
+                attempts: 2,
+                exercise_type: ExerciseType::ToneOnly,
+            },
+            ShuangElement {
+                hanzi_pair: parse_hanzi_pair("你好 ni3hao3 33 ni3hao4").unwrap(),
+                user_answer: "33".to_string(),
+                is_correct: false,
+                first_answer: None,
+                attempts: 1,
+                exercise_type: ExerciseType::ToneOnly,
+            },
+        ]);
+        SummaryReport::new(&summary, ExerciseType::ToneOnly)
+    }
+
+    #[test]
+    fn report_uses_pronounced_tones() {
+        let report = get_report();
+        assert_eq!(report.correct_answers, 1);
+        assert_eq!(report.correct_percentage, 50.0);
+        assert_eq!(report.elements[1].expected_answer, "23");
+        assert_eq!(
+            report.tone_pair_mistakes,
+            vec![ToneMistakesReport {
+                tone_pair: "23".to_string(),
+                mistakes: 1
+            }]
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["elements"][1]["user_answer"], "33");
+        assert_eq!(json["total_answers"], 2);
+        assert_eq!(json["elements"][1]["alternative_readings"][0], "ni3hao4");
+        assert_eq!(json["elements"][0]["first_answer"], "22");
+        assert_eq!(json["first_attempt_correct_answers"], 0);
+    }
+
+    #[test]
+    fn report_table_lists_every_answer() {
+        let mut output = vec![];
+        get_report().write_table(&mut output).unwrap();
+        let table = String::from_utf8(output).unwrap();
+        assert!(table.contains(
+            " ✓  | xue2xiao4 | 24       | 24       | 学校  (first answer 22, 2 attempts)"
+        ));
+        assert!(table.contains(" ✗  | ni2hao3   | 23       | 33       | 你好"));
+        assert!(table.contains("Correct answers: 1/2 (50.00%)"));
+        assert!(table.contains("Correct at the first attempt: 0/2"));
+        assert!(table.contains("  23 => 1"));
+    }
+
+    #[test]
+    fn report_table_pads_hanzi_answers_by_width() {
+        let summary = ExerciseSummary::from(vec![ShuangElement {
+            hanzi_pair: parse_hanzi_pair("学校 xue2xiao4 24").unwrap(),
+            user_answer: "学".to_string(),
+            is_correct: false,
+            first_answer: None,
+            attempts: 1,
+            exercise_type: ExerciseType::Hanzi,
+        }]);
+        let mut output = vec![];
+        SummaryReport::new(&summary, ExerciseType::Hanzi)
+            .write_table(&mut output)
+            .unwrap();
+        let table = String::from_utf8(output).unwrap();
+        assert!(table.contains("    | Pinyin    | Expected | Answer   | Hanzi"));
+        assert!(table.contains(" ✗  | xue2xiao4 | 学校     | 学       | 学校"));
+    }
+}
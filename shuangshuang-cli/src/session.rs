@@ -0,0 +1,167 @@
+use std::io::{BufRead, Write};
+
+use shuangshuang_core::exercise::ExerciseType;
+use shuangshuang_core::session::{SessionEffect, SessionEngine, SessionEvent, SessionState};
+
+use crate::audio::AudioBackend;
+use crate::CliError;
+
+const REPLAY_COMMAND: &str = ":r";
+const QUIT_COMMAND: &str = ":q";
+
+fn apply_effects(
+    engine: &SessionEngine,
+    effects: Vec<SessionEffect>,
+    audio: &mut impl AudioBackend,
+) -> Result<(), CliError> {
+    for effect in effects {
+        match effect {
+            SessionEffect::PlayAudio { url, .. } => {
+                if let Some(hanzi_pair) = engine.current_hanzi_pair() {
+                    audio.play(&url, hanzi_pair)?;
+                }
+            }
+            // There is no answer timer in the terminal
+            SessionEffect::ResetTimer => (),
+        }
+    }
+    Ok(())
+}
+
+/// Runs a session until every pair is answered, the learner quits or `input` ends. Answers
+/// are graded like in the web app, `:r` replays the audio and `:q` ends the session early.
+pub fn run_session(
+    engine: &mut SessionEngine,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    audio: &mut impl AudioBackend,
+) -> Result<(), CliError> {
+    writeln!(
+        output,
+        "Type your answer then Enter, {REPLAY_COMMAND} to replay the audio, {QUIT_COMMAND} to quit."
+    )?;
+    let effects = engine.handle(SessionEvent::Start);
+    apply_effects(engine, effects, audio)?;
+    while engine.state() == SessionState::Playing {
+        // The sandhi quiz asks the pronounced tones of the dictionary pinyin
+        if engine.exercise_type() == ExerciseType::Sandhi {
+            if let Some(hanzi_pair) = engine.current_hanzi_pair() {
+                writeln!(output, "{} {}", hanzi_pair.characters, hanzi_pair.pinyin)?;
+            }
+        }
+        write!(
+            output,
+            "[{} left, {} replays] > ",
+            engine.remaining_pairs(),
+            engine.remaining_listenings()
+        )?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        let answer = line.trim().to_lowercase();
+        match answer.as_str() {
+            QUIT_COMMAND => break,
+            REPLAY_COMMAND => {
+                let effects = engine.handle(SessionEvent::ReplayAudio);
+                if effects.is_empty() {
+                    writeln!(output, "No replays left for this pair.")?;
+                }
+                apply_effects(engine, effects, audio)?;
+            }
+            _ => {
+                let effects = engine.handle(SessionEvent::SubmitAnswer(answer));
+                apply_effects(engine, effects, audio)?;
+                let effects = engine.handle(SessionEvent::Next);
+                apply_effects(engine, effects, audio)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::NoAudio;
+    use shuangshuang_core::corpus::parse_hanzi_pair;
+    use shuangshuang_core::exercise::ExerciseParams;
+    use std::io::Cursor;
+
+    fn get_engine(audio_retries: u32) -> SessionEngine {
+        SessionEngine::new(
+            vec![
+                parse_hanzi_pair("学校 xue2xiao4 24").unwrap(),
+                parse_hanzi_pair("你好 ni3hao3 33").unwrap(),
+            ],
+            &ExerciseParams {
+                exercise_type: ExerciseType::ToneOnly,
+                audio_retries,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn run(engine: &mut SessionEngine, input: &str) -> String {
+        let mut audio = NoAudio { output: vec![] };
+        let mut output = vec![];
+        run_session(engine, &mut Cursor::new(input), &mut output, &mut audio).unwrap();
+        String::from_utf8(audio.output).unwrap()
+    }
+
+    #[test]
+    fn session_grades_answers_like_the_web_app() {
+        let mut engine = get_engine(1);
+        let shown_hanzi = run(&mut engine, "24\n:r\n:r\n 33 \n");
+        assert_eq!(engine.state(), SessionState::Finished);
+        let summary = engine.get_summary();
+        assert!(summary.test_elements[0].is_correct);
+        // The third tone sandhi makes 你好 pronounced 23
+        assert!(!summary.test_elements[1].is_correct);
+        assert_eq!(summary.test_elements[1].user_answer, "33");
+        // The second replay is refused
+        assert_eq!(shown_hanzi, "学校\n你好\n你好\n");
+    }
+
+    #[test]
+    fn session_can_be_quit_early() {
+        let mut engine = get_engine(3);
+        run(&mut engine, "24\n:q\n23\n");
+        assert_eq!(engine.state(), SessionState::Playing);
+        assert_eq!(engine.get_summary().test_elements.len(), 1);
+        let mut engine = get_engine(3);
+        run(&mut engine, "");
+        assert!(engine.get_summary().test_elements.is_empty());
+    }
+
+    #[test]
+    fn sandhi_quiz_shows_the_dictionary_pinyin_first() {
+        let mut engine = SessionEngine::new(
+            vec![parse_hanzi_pair("你好 ni3hao3 33").unwrap()],
+            &ExerciseParams {
+                exercise_type: ExerciseType::Sandhi,
+                ..Default::default()
+            },
+        );
+        let mut audio = NoAudio { output: vec![] };
+        let mut output = vec![];
+        run_session(
+            &mut engine,
+            &mut Cursor::new(
+                ":r
+23
+",
+            ),
+            &mut output,
+            &mut audio,
+        )
+        .unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("你好 ni3hao3\n"));
+        assert!(engine.get_summary().test_elements[0].is_correct);
+        // Nothing is played before the answer
+        assert_eq!(String::from_utf8(audio.output).unwrap(), "你好\n");
+    }
+}
@@ -0,0 +1,37 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use shuangshuang_core::binary_corpus::BinaryCorpus;
+use shuangshuang_core::corpus::parse_corpus;
+use shuangshuang_core::exercise::HanziPair;
+
+// Loading what the app fetches at startup, before the first session can begin
+fn corpus_loading(c: &mut Criterion) {
+    let jsonl = include_str!("../../data/hanzi_pairs.jsonl");
+    let text = include_str!("../../data/hanzi_pairs.txt");
+    let binary = include_bytes!("../../data/hanzi_pairs.bin");
+    let mut group = c.benchmark_group("corpus_loading");
+    group.bench_function("jsonl", |b| {
+        b.iter(|| {
+            parse_corpus(black_box(jsonl))
+                .into_iter()
+                .filter_map(|(_, hanzi_pair)| hanzi_pair.ok())
+                .collect::<Vec<HanziPair>>()
+        })
+    });
+    group.bench_function("text", |b| {
+        b.iter(|| {
+            parse_corpus(black_box(text))
+                .into_iter()
+                .filter_map(|(_, hanzi_pair)| hanzi_pair.ok())
+                .collect::<Vec<HanziPair>>()
+        })
+    });
+    group.bench_function("binary", |b| {
+        b.iter(|| BinaryCorpus::decode(black_box(binary)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, corpus_loading);
+criterion_main!(benches);
@@ -0,0 +1,595 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use thiserror::Error;
+
+use crate::corpus::HanziPairSource;
+use crate::exercise::{AudioQuality, HanziMetadata, HanziPair, HanziReading, Tone};
+use crate::utils::get_tones_from_pinyin;
+
+/// First bytes of `hanzi_pairs.bin`
+pub const BINARY_CORPUS_MAGIC: &[u8; 4] = b"SSBC";
+/// Latest version of the binary corpus format
+pub const BINARY_CORPUS_VERSION: u16 = 1;
+
+// Magic, version, number of records, number of extras and length of the string table
+const HEADER_SIZE: usize = 4 + 2 + 4 + 4 + 4;
+// Offset then length of a string of the table
+const STR_REF_SIZE: usize = 4 + 2;
+const NO_STR: u32 = u32::MAX;
+
+// Every pair has a record. Strings are interned, so a pinyin left unchanged by the tone sandhi
+// is only stored once.
+const CHARACTERS: usize = 0;
+const PINYIN: usize = STR_REF_SIZE;
+const PRONOUNCED_PINYIN: usize = 2 * STR_REF_SIZE;
+// Both tones of a pair are packed in one byte
+const TONE_PAIR: usize = 3 * STR_REF_SIZE;
+const PRONOUNCED_TONE_PAIR: usize = TONE_PAIR + 1;
+const HSK_LEVEL: usize = PRONOUNCED_TONE_PAIR + 1;
+const AUDIO_QUALITIES: usize = HSK_LEVEL + 1;
+const FLAGS: usize = AUDIO_QUALITIES + 1;
+const RECORD_SIZE: usize = FLAGS + 1;
+
+// Fields that few pairs have are stored in extras, sorted by the index of their record
+const EXTRA_RECORD_IDX: usize = 0;
+const PRONUNCIATION_OVERRIDE: usize = 4;
+const ALTERNATIVES: usize = PRONUNCIATION_OVERRIDE + STR_REF_SIZE;
+const TRADITIONAL: usize = ALTERNATIVES + STR_REF_SIZE;
+const GLOSSES: usize = TRADITIONAL + STR_REF_SIZE;
+const FREQUENCY_RANK: usize = GLOSSES + STR_REF_SIZE;
+const EXTRA_SIZE: usize = FREQUENCY_RANK + 4;
+
+const HAS_EXTRA: u8 = 0b0000_0001;
+
+// Separators of the lists packed in a single string
+const LIST_SEPARATOR: char = '\n';
+const PAIR_SEPARATOR: char = '\t';
+
+// Set in the audio qualities byte when the list is known, the qualities are the lower bits
+const AUDIO_QUALITIES_KNOWN: u8 = 0b1000_0000;
+const AUDIO_QUALITY_BITS: [AudioQuality; 4] = [
+    AudioQuality::Q18k,
+    AudioQuality::Q24k,
+    AudioQuality::Q64k,
+    AudioQuality::Q96k,
+];
+
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum BinaryCorpusError {
+    #[error("This is not a binary corpus.")]
+    InvalidMagic,
+    #[error("Version {0} of the binary corpus format is not supported, the latest is {BINARY_CORPUS_VERSION}.")]
+    UnsupportedVersion(u16),
+    #[error("The binary corpus is truncated.")]
+    Truncated,
+    #[error("The string table of the binary corpus is not valid UTF-8.")]
+    InvalidStrings,
+    #[error("Record {0} of the binary corpus is invalid.")]
+    InvalidRecord(usize),
+    #[error("Extra {0} of the binary corpus is invalid.")]
+    InvalidExtra(usize),
+}
+
+fn tone_to_bits(tone: Tone) -> u8 {
+    match tone {
+        Tone::Tone1 => 1,
+        Tone::Tone2 => 2,
+        Tone::Tone3 => 3,
+        Tone::Tone4 => 4,
+        Tone::NeutralTone => 5,
+    }
+}
+
+fn bits_to_tone(bits: u8) -> Option<Tone> {
+    match bits {
+        1 => Some(Tone::Tone1),
+        2 => Some(Tone::Tone2),
+        3 => Some(Tone::Tone3),
+        4 => Some(Tone::Tone4),
+        5 => Some(Tone::NeutralTone),
+        _ => None,
+    }
+}
+
+fn tone_pair_to_byte(tone_pair: (Tone, Tone)) -> u8 {
+    (tone_to_bits(tone_pair.0) << 4) | tone_to_bits(tone_pair.1)
+}
+
+fn byte_to_tone_pair(byte: u8) -> Option<(Tone, Tone)> {
+    Some((bits_to_tone(byte >> 4)?, bits_to_tone(byte & 0b1111)?))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn read_str<'a>(strings: &'a str, bytes: &[u8], field: usize) -> Option<&'a str> {
+    let offset = read_u32(bytes, field);
+    if offset == NO_STR {
+        return None;
+    }
+    let offset = offset as usize;
+    Some(&strings[offset..offset + read_u16(bytes, field + 4) as usize])
+}
+
+fn is_valid_str_ref(strings: &str, bytes: &[u8], field: usize, is_optional: bool) -> bool {
+    let offset = read_u32(bytes, field);
+    if offset == NO_STR {
+        return is_optional;
+    }
+    let end = offset as usize + read_u16(bytes, field + 4) as usize;
+    end <= strings.len()
+        && strings.is_char_boundary(offset as usize)
+        && strings.is_char_boundary(end)
+}
+
+// Writes every string once in the table
+#[derive(Default)]
+struct StringInterner {
+    strings: String,
+    offsets: HashMap<String, u32>,
+}
+
+impl StringInterner {
+    fn write_ref(&mut self, bytes: &mut Vec<u8>, string: Option<&str>) {
+        let (offset, len) = match string {
+            None => (NO_STR, 0),
+            Some(string) => {
+                let len = u16::try_from(string.len()).expect("Corpus strings should be short");
+                let offset = match self.offsets.get(string) {
+                    Some(offset) => *offset,
+                    None => {
+                        let offset = self.strings.len() as u32;
+                        self.strings.push_str(string);
+                        self.offsets.insert(string.to_string(), offset);
+                        offset
+                    }
+                };
+                (offset, len)
+            }
+        };
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend(len.to_le_bytes());
+    }
+}
+
+fn join_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> Option<String> {
+    let pairs: Vec<String> = pairs
+        .map(|(key, value)| format!("{key}{PAIR_SEPARATOR}{value}"))
+        .collect();
+    (!pairs.is_empty()).then(|| pairs.join(&LIST_SEPARATOR.to_string()))
+}
+
+/// A corpus stored as fixed size records pointing into a table of interned strings, with the
+/// tone sandhi and the pronunciation overrides already applied. Decoding only validates the
+/// records, pairs are built when they are needed.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct BinaryCorpus {
+    strings: String,
+    records: Vec<u8>,
+    extras: Vec<u8>,
+}
+
+impl BinaryCorpus {
+    /// Encodes hanzi pairs as `hanzi_pairs.bin`
+    pub fn encode(hanzi_pairs: &[HanziPair]) -> Vec<u8> {
+        let mut interner = StringInterner::default();
+        let mut records: Vec<u8> = Vec::with_capacity(hanzi_pairs.len() * RECORD_SIZE);
+        let mut extras: Vec<u8> = vec![];
+        for (idx, hanzi_pair) in hanzi_pairs.iter().enumerate() {
+            let metadata = &hanzi_pair.metadata;
+            interner.write_ref(&mut records, Some(&hanzi_pair.characters));
+            interner.write_ref(&mut records, Some(&hanzi_pair.pinyin));
+            interner.write_ref(&mut records, Some(&hanzi_pair.pronounced_pinyin));
+            records.push(tone_pair_to_byte(hanzi_pair.tone_pair));
+            records.push(tone_pair_to_byte(hanzi_pair.pronounced_tone_pair));
+            records.push(metadata.hsk_level.unwrap_or_default());
+            records.push(metadata.audio_qualities.as_ref().map_or(0, |qualities| {
+                AUDIO_QUALITY_BITS
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, quality)| qualities.contains(quality))
+                    .fold(AUDIO_QUALITIES_KNOWN, |byte, (bit, _)| byte | (1 << bit))
+            }));
+            let alternatives = join_pairs(
+                hanzi_pair
+                    .alternative_readings
+                    .iter()
+                    .map(|reading| (reading.pinyin.as_str(), reading.pronounced_pinyin.as_str())),
+            );
+            let glosses = join_pairs(
+                metadata
+                    .glosses
+                    .iter()
+                    .map(|(language, gloss)| (language.as_str(), gloss.as_str())),
+            );
+            let has_extra = hanzi_pair.pronunciation_override.is_some()
+                || alternatives.is_some()
+                || metadata.traditional.is_some()
+                || glosses.is_some()
+                || metadata.frequency_rank.is_some();
+            if !has_extra {
+                records.push(0);
+                continue;
+            }
+            records.push(HAS_EXTRA);
+            extras.extend((idx as u32).to_le_bytes());
+            interner.write_ref(&mut extras, hanzi_pair.pronunciation_override.as_deref());
+            interner.write_ref(&mut extras, alternatives.as_deref());
+            interner.write_ref(&mut extras, metadata.traditional.as_deref());
+            interner.write_ref(&mut extras, glosses.as_deref());
+            extras.extend(metadata.frequency_rank.unwrap_or_default().to_le_bytes());
+        }
+        let mut bytes: Vec<u8> =
+            Vec::with_capacity(HEADER_SIZE + interner.strings.len() + records.len() + extras.len());
+        bytes.extend(BINARY_CORPUS_MAGIC);
+        bytes.extend(BINARY_CORPUS_VERSION.to_le_bytes());
+        bytes.extend((hanzi_pairs.len() as u32).to_le_bytes());
+        bytes.extend(((extras.len() / EXTRA_SIZE) as u32).to_le_bytes());
+        bytes.extend((interner.strings.len() as u32).to_le_bytes());
+        bytes.extend(interner.strings.as_bytes());
+        bytes.extend(records);
+        bytes.extend(extras);
+        bytes
+    }
+
+    /// Decodes `hanzi_pairs.bin`, checking every record once so that reading them cannot fail
+    pub fn decode(bytes: &[u8]) -> Result<Self, BinaryCorpusError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(BinaryCorpusError::Truncated);
+        }
+        if &bytes[..4] != BINARY_CORPUS_MAGIC {
+            return Err(BinaryCorpusError::InvalidMagic);
+        }
+        let version = read_u16(bytes, 4);
+        if version > BINARY_CORPUS_VERSION {
+            return Err(BinaryCorpusError::UnsupportedVersion(version));
+        }
+        let len = read_u32(bytes, 6) as usize;
+        let extras_len = read_u32(bytes, 10) as usize;
+        let records_start = HEADER_SIZE + read_u32(bytes, 14) as usize;
+        let extras_start = records_start + len * RECORD_SIZE;
+        if bytes.len() != extras_start + extras_len * EXTRA_SIZE {
+            return Err(BinaryCorpusError::Truncated);
+        }
+        let strings = std::str::from_utf8(&bytes[HEADER_SIZE..records_start])
+            .map_err(|_| BinaryCorpusError::InvalidStrings)?;
+        let records = &bytes[records_start..extras_start];
+        let extras = &bytes[extras_start..];
+        let mut records_with_extra = 0;
+        for (idx, record) in records.chunks_exact(RECORD_SIZE).enumerate() {
+            let is_valid = [CHARACTERS, PINYIN, PRONOUNCED_PINYIN]
+                .into_iter()
+                .all(|field| is_valid_str_ref(strings, record, field, false))
+                && byte_to_tone_pair(record[TONE_PAIR]).is_some()
+                && byte_to_tone_pair(record[PRONOUNCED_TONE_PAIR]).is_some();
+            if !is_valid {
+                return Err(BinaryCorpusError::InvalidRecord(idx));
+            }
+            if record[FLAGS] & HAS_EXTRA != 0 {
+                records_with_extra += 1;
+            }
+        }
+        let mut previous_record_idx: Option<usize> = None;
+        for (idx, extra) in extras.chunks_exact(EXTRA_SIZE).enumerate() {
+            let record_idx = read_u32(extra, EXTRA_RECORD_IDX) as usize;
+            let is_valid = record_idx < len
+                && previous_record_idx.is_none_or(|previous_idx| previous_idx < record_idx)
+                && records[record_idx * RECORD_SIZE + FLAGS] & HAS_EXTRA != 0
+                && [PRONUNCIATION_OVERRIDE, ALTERNATIVES, TRADITIONAL, GLOSSES]
+                    .into_iter()
+                    .all(|field| is_valid_str_ref(strings, extra, field, true));
+            if !is_valid {
+                return Err(BinaryCorpusError::InvalidExtra(idx));
+            }
+            previous_record_idx = Some(record_idx);
+        }
+        // A flagged record without an extra
+        if records_with_extra != extras_len {
+            return Err(BinaryCorpusError::InvalidExtra(extras_len));
+        }
+        Ok(Self {
+            strings: strings.to_string(),
+            records: records.to_vec(),
+            extras: extras.to_vec(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len() / RECORD_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<HanziPairRef<'_>> {
+        let record = self
+            .records
+            .get(idx * RECORD_SIZE..(idx + 1) * RECORD_SIZE)?;
+        Some(HanziPairRef {
+            corpus: self,
+            idx,
+            record,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = HanziPairRef<'_>> {
+        (0..self.len()).filter_map(|idx| self.get(idx))
+    }
+
+    // Binary search, extras are sorted by record index
+    fn get_extra(&self, record_idx: usize) -> Option<&[u8]> {
+        let (mut low, mut high) = (0, self.extras.len() / EXTRA_SIZE);
+        while low < high {
+            let middle = (low + high) / 2;
+            let extra = &self.extras[middle * EXTRA_SIZE..(middle + 1) * EXTRA_SIZE];
+            match (read_u32(extra, EXTRA_RECORD_IDX) as usize).cmp(&record_idx) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(extra),
+            }
+        }
+        None
+    }
+}
+
+impl From<&[HanziPair]> for BinaryCorpus {
+    fn from(hanzi_pairs: &[HanziPair]) -> Self {
+        Self::decode(&Self::encode(hanzi_pairs)).expect("Encoded corpus should decode")
+    }
+}
+
+impl HanziPairSource for BinaryCorpus {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pronounced_tone_pair(&self, idx: usize) -> (Tone, Tone) {
+        self.get(idx)
+            .expect("Hanzi pair index should be in the corpus")
+            .pronounced_tone_pair()
+    }
+
+    fn pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(
+            self.get(idx)
+                .expect("Hanzi pair index should be in the corpus")
+                .pinyin(),
+        )
+    }
+
+    fn pronounced_pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(
+            self.get(idx)
+                .expect("Hanzi pair index should be in the corpus")
+                .pronounced_pinyin(),
+        )
+    }
+
+    fn hanzi_pair(&self, idx: usize) -> HanziPair {
+        self.get(idx)
+            .expect("Hanzi pair index should be in the corpus")
+            .to_hanzi_pair()
+    }
+}
+
+/// A hanzi pair of a `BinaryCorpus`, borrowing its strings
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HanziPairRef<'a> {
+    corpus: &'a BinaryCorpus,
+    idx: usize,
+    record: &'a [u8],
+}
+
+impl<'a> HanziPairRef<'a> {
+    fn read_str(&self, field: usize) -> &'a str {
+        read_str(&self.corpus.strings, self.record, field).unwrap_or_default()
+    }
+
+    pub fn characters(&self) -> &'a str {
+        self.read_str(CHARACTERS)
+    }
+
+    pub fn pinyin(&self) -> &'a str {
+        self.read_str(PINYIN)
+    }
+
+    pub fn pronounced_pinyin(&self) -> &'a str {
+        self.read_str(PRONOUNCED_PINYIN)
+    }
+
+    pub fn tone_pair(&self) -> (Tone, Tone) {
+        byte_to_tone_pair(self.record[TONE_PAIR]).expect("Tones are checked when decoding")
+    }
+
+    pub fn pronounced_tone_pair(&self) -> (Tone, Tone) {
+        byte_to_tone_pair(self.record[PRONOUNCED_TONE_PAIR])
+            .expect("Tones are checked when decoding")
+    }
+
+    /// Builds an owned hanzi pair, with its metadata
+    pub fn to_hanzi_pair(&self) -> HanziPair {
+        let audio_qualities = self.record[AUDIO_QUALITIES];
+        let mut hanzi_pair = HanziPair {
+            characters: self.characters().to_string(),
+            pinyin: self.pinyin().to_string(),
+            pronounced_pinyin: self.pronounced_pinyin().to_string(),
+            tone_pair: self.tone_pair(),
+            pronounced_tone_pair: self.pronounced_tone_pair(),
+            metadata: HanziMetadata {
+                hsk_level: Some(self.record[HSK_LEVEL]).filter(|level| *level > 0),
+                audio_qualities: (audio_qualities & AUDIO_QUALITIES_KNOWN != 0).then(|| {
+                    AUDIO_QUALITY_BITS
+                        .iter()
+                        .enumerate()
+                        .filter(|(bit, _)| audio_qualities & (1 << bit) != 0)
+                        .map(|(_, quality)| *quality)
+                        .collect()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        if self.record[FLAGS] & HAS_EXTRA == 0 {
+            return hanzi_pair;
+        }
+        let Some(extra) = self.corpus.get_extra(self.idx) else {
+            return hanzi_pair;
+        };
+        let strings = &self.corpus.strings;
+        let read_pairs = |field: usize| {
+            read_str(strings, extra, field)
+                .unwrap_or_default()
+                .split(LIST_SEPARATOR)
+                .filter_map(|pair| pair.split_once(PAIR_SEPARATOR))
+        };
+        hanzi_pair.pronunciation_override =
+            read_str(strings, extra, PRONUNCIATION_OVERRIDE).map(str::to_string);
+        hanzi_pair.alternative_readings = read_pairs(ALTERNATIVES)
+            .map(|(pinyin, pronounced_pinyin)| {
+                let tones = get_tones_from_pinyin(pronounced_pinyin);
+                HanziReading {
+                    pinyin: pinyin.to_string(),
+                    pronounced_pinyin: pronounced_pinyin.to_string(),
+                    pronounced_tone_pair: (tones[0], tones[1]),
+                }
+            })
+            .collect();
+        hanzi_pair.metadata.traditional = read_str(strings, extra, TRADITIONAL).map(str::to_string);
+        hanzi_pair.metadata.glosses = read_pairs(GLOSSES)
+            .map(|(language, gloss)| (language.to_string(), gloss.to_string()))
+            .collect::<BTreeMap<String, String>>();
+        hanzi_pair.metadata.frequency_rank =
+            Some(read_u32(extra, FREQUENCY_RANK)).filter(|rank| *rank > 0);
+        hanzi_pair
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{
+        apply_pronunciation_overrides, parse_corpus, parse_hanzi_pair,
+        parse_pronunciation_overrides, PronunciationOverride,
+    };
+
+    fn get_hanzi_pairs() -> Vec<HanziPair> {
+        let mut hanzi_pairs = vec![
+            parse_hanzi_pair("你好 ni3hao3 33 ni3hao4").unwrap(),
+            parse_hanzi_pair("我们 wo3men 35").unwrap(),
+            parse_hanzi_pair("学校 xue2xiao4 24").unwrap(),
+        ];
+        hanzi_pairs[0].pronunciation_override = Some("A reason".to_string());
+        hanzi_pairs[1].metadata.audio_qualities = Some(vec![AudioQuality::Q24k]);
+        hanzi_pairs[2].metadata = HanziMetadata {
+            traditional: Some("學校".to_string()),
+            hsk_level: Some(1),
+            frequency_rank: Some(42),
+            glosses: BTreeMap::from([
+                ("en".to_string(), "school".to_string()),
+                ("fr".to_string(), "école".to_string()),
+            ]),
+            audio_qualities: Some(vec![AudioQuality::Q24k, AudioQuality::Q64k]),
+        };
+        hanzi_pairs
+    }
+
+    #[test]
+    fn binary_corpus_round_trips() {
+        let hanzi_pairs = get_hanzi_pairs();
+        let corpus = BinaryCorpus::from(&hanzi_pairs[..]);
+        assert_eq!(corpus.len(), 3);
+        assert_eq!(corpus.get(0).unwrap().pronounced_pinyin(), "ni2hao3");
+        assert_eq!(
+            corpus.get(2).unwrap().pronounced_tone_pair(),
+            (Tone::Tone2, Tone::Tone4)
+        );
+        let decoded_pairs: Vec<HanziPair> =
+            corpus.iter().map(|pair| pair.to_hanzi_pair()).collect();
+        assert_eq!(decoded_pairs, hanzi_pairs);
+        assert!(corpus.get(3).is_none());
+    }
+
+    #[test]
+    fn binary_corpus_interns_strings() {
+        let hanzi_pair = parse_hanzi_pair("学校 xue2xiao4 24").unwrap();
+        let one_pair_len = BinaryCorpus::encode(std::slice::from_ref(&hanzi_pair)).len();
+        assert_eq!(
+            BinaryCorpus::encode(&[hanzi_pair.clone(), hanzi_pair]).len(),
+            one_pair_len + RECORD_SIZE
+        );
+    }
+
+    #[test]
+    fn binary_corpus_rejects_invalid_bytes() {
+        let bytes = BinaryCorpus::encode(&get_hanzi_pairs());
+        let records_start = bytes.len() - 3 * RECORD_SIZE - 2 * EXTRA_SIZE;
+        assert_eq!(
+            BinaryCorpus::decode(b"SSBC"),
+            Err(BinaryCorpusError::Truncated)
+        );
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[0] = b'X';
+        assert_eq!(
+            BinaryCorpus::decode(&invalid_bytes),
+            Err(BinaryCorpusError::InvalidMagic)
+        );
+        assert_eq!(
+            BinaryCorpus::decode(&bytes[..bytes.len() - 1]),
+            Err(BinaryCorpusError::Truncated)
+        );
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[records_start + RECORD_SIZE + TONE_PAIR] = 0x36;
+        assert_eq!(
+            BinaryCorpus::decode(&invalid_bytes),
+            Err(BinaryCorpusError::InvalidRecord(1))
+        );
+        let mut invalid_bytes = bytes.clone();
+        // The characters of the first record now end in the middle of 好
+        invalid_bytes[records_start + CHARACTERS + 4] = 4;
+        assert_eq!(
+            BinaryCorpus::decode(&invalid_bytes),
+            Err(BinaryCorpusError::InvalidRecord(0))
+        );
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[records_start + RECORD_SIZE + FLAGS] = HAS_EXTRA;
+        assert_eq!(
+            BinaryCorpus::decode(&invalid_bytes),
+            Err(BinaryCorpusError::InvalidExtra(2))
+        );
+    }
+
+    #[test]
+    fn bundled_binary_corpus_matches_jsonl() {
+        let corpus = BinaryCorpus::decode(include_bytes!("../../data/hanzi_pairs.bin"))
+            .expect("Bundled binary corpus should decode");
+        let mut jsonl_pairs: Vec<HanziPair> =
+            parse_corpus(include_str!("../../data/hanzi_pairs.jsonl"))
+                .into_iter()
+                .map(|(_, hanzi_pair)| hanzi_pair.unwrap())
+                .collect();
+        // The binary corpus is built with the pronunciations of the recordings applied
+        let overrides: Vec<PronunciationOverride> =
+            parse_pronunciation_overrides(include_str!("../../data/pronunciation_overrides.txt"))
+                .into_iter()
+                .map(|(_, pronunciation_override)| pronunciation_override.unwrap())
+                .collect();
+        assert!(apply_pronunciation_overrides(&mut jsonl_pairs, &overrides).is_empty());
+        assert_eq!(corpus.len(), jsonl_pairs.len());
+        for (pair_ref, hanzi_pair) in corpus.iter().zip(jsonl_pairs) {
+            assert_eq!(pair_ref.to_hanzi_pair(), hanzi_pair);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeSet;
+
+use crate::corpus::HanziPairSource;
+use crate::exercise::{ExerciseType, HanziPair, Tone};
+use crate::minimal_pairs::MinimalPairIndex;
+use crate::session::get_expected_answer;
+use crate::utils::{get_syllables_from_pinyin, get_tones_from_pinyin};
+
+/// Other tones a learner may hear for each tone, most often confused first. The 2 and 3 tones
+/// are the most confused pair, then the 1 and 4 tones
+fn get_confusable_tones(tone: Tone) -> [Tone; 4] {
+    match tone {
+        Tone::Tone1 => [Tone::Tone4, Tone::Tone2, Tone::NeutralTone, Tone::Tone3],
+        Tone::Tone2 => [Tone::Tone3, Tone::Tone1, Tone::Tone4, Tone::NeutralTone],
+        Tone::Tone3 => [Tone::Tone2, Tone::NeutralTone, Tone::Tone4, Tone::Tone1],
+        Tone::Tone4 => [Tone::Tone1, Tone::NeutralTone, Tone::Tone2, Tone::Tone3],
+        Tone::NeutralTone => [Tone::Tone3, Tone::Tone1, Tone::Tone4, Tone::Tone2],
+    }
+}
+
+/// Number of answers offered for an exercise type, pinyin answers are easier to tell apart
+pub fn get_nb_choices(exercise_type: ExerciseType) -> usize {
+    match exercise_type {
+        ExerciseType::ToneOnly
+        | ExerciseType::Production
+        | ExerciseType::MinimalPair
+        | ExerciseType::ReverseAudio
+        | ExerciseType::Sandhi => 4,
+        ExerciseType::NoTonePinyin
+        | ExerciseType::Pinyin
+        | ExerciseType::Hanzi
+        | ExerciseType::PinyinDrill
+        | ExerciseType::Mixed => 6,
+    }
+}
+
+fn format_pinyin(syllables: &[String], tones: (Tone, Tone)) -> String {
+    syllables
+        .iter()
+        .zip([tones.0, tones.1])
+        .map(|(syllable, tone)| match tone {
+            Tone::NeutralTone => syllable.clone(),
+            _ => format!("{syllable}{tone}"),
+        })
+        .collect()
+}
+
+// Tone pairs confusable with `tone_pair`, one changed tone first, then both. A pair never
+// starts with a neutral tone
+fn get_confusable_tone_pairs(tone_pair: (Tone, Tone)) -> Vec<(Tone, Tone)> {
+    let first_tones = get_confusable_tones(tone_pair.0);
+    let second_tones = get_confusable_tones(tone_pair.1);
+    let mut tone_pairs: Vec<(Tone, Tone)> = vec![];
+    for rank in 0..4 {
+        tone_pairs.push((first_tones[rank], tone_pair.1));
+        tone_pairs.push((tone_pair.0, second_tones[rank]));
+    }
+    tone_pairs.push((first_tones[0], second_tones[0]));
+    tone_pairs.push((tone_pair.1, tone_pair.0));
+    tone_pairs.retain(|tone_pair| tone_pair.0 != Tone::NeutralTone);
+    tone_pairs
+}
+
+/// Returns `nb_choices` answers for `hanzi_pair`, in alphabetical order, the expected one
+/// included. The distractors are the answers of corpus pairs with the same syllables and other
+/// tones, then the confusable tones of the expected answer. With no tones to answer, they are
+/// corpus pairs sharing a syllable. The other readings of the pair are never offered, and the
+/// same pair and corpus always give the same answers.
+pub fn get_answer_choices(
+    hanzi_pair: &HanziPair,
+    exercise_type: ExerciseType,
+    corpus: &(impl HanziPairSource + ?Sized),
+    nb_choices: usize,
+) -> Vec<String> {
+    let expected_answer = get_expected_answer(hanzi_pair, exercise_type);
+    let syllables = get_syllables_from_pinyin(&hanzi_pair.pronounced_pinyin);
+    let excluded_answers: Vec<String> = hanzi_pair
+        .alternative_readings
+        .iter()
+        .map(|reading| {
+            let reading_pair = HanziPair {
+                pronounced_pinyin: reading.pronounced_pinyin.clone(),
+                ..Default::default()
+            };
+            get_expected_answer(&reading_pair, exercise_type)
+        })
+        .chain([expected_answer.clone()])
+        .collect();
+
+    let mut candidates: Vec<String> = vec![];
+    for idx in 0..corpus.len() {
+        let pronounced_pinyin = corpus.pronounced_pinyin(idx);
+        let corpus_syllables = get_syllables_from_pinyin(&pronounced_pinyin);
+        let is_confusable = match exercise_type {
+            ExerciseType::NoTonePinyin => {
+                corpus_syllables != syllables
+                    && corpus_syllables
+                        .iter()
+                        .zip(syllables.iter())
+                        .any(|(corpus_syllable, syllable)| corpus_syllable == syllable)
+            }
+            _ => corpus_syllables == syllables,
+        };
+        if is_confusable {
+            let corpus_pair = HanziPair {
+                pronounced_pinyin: pronounced_pinyin.into_owned(),
+                ..Default::default()
+            };
+            candidates.push(get_expected_answer(&corpus_pair, exercise_type));
+        }
+    }
+    if exercise_type != ExerciseType::NoTonePinyin {
+        let tones = get_tones_from_pinyin(&hanzi_pair.pronounced_pinyin);
+        for tone_pair in get_confusable_tone_pairs((tones[0], tones[1])) {
+            candidates.push(match exercise_type {
+                ExerciseType::Pinyin => format_pinyin(&syllables, tone_pair),
+                _ => format!("{}{}", tone_pair.0, tone_pair.1),
+            });
+        }
+    }
+
+    let mut choices = BTreeSet::from([expected_answer]);
+    for candidate in candidates {
+        if choices.len() >= nb_choices {
+            break;
+        }
+        if !excluded_answers.contains(&candidate) {
+            choices.insert(candidate);
+        }
+    }
+    choices.into_iter().collect()
+}
+
+/// Returns up to `nb_choices` recordings to pick from for `hanzi_pair` in the reverse exercise,
+/// in alphabetical order of their pinyin, the pair included. The others are corpus pairs with
+/// the same syllables, each with other tones.
+pub fn get_audio_choices(
+    hanzi_pair: &HanziPair,
+    index: &MinimalPairIndex,
+    corpus: &(impl HanziPairSource + ?Sized),
+    nb_choices: usize,
+) -> Vec<HanziPair> {
+    let syllables = get_syllables_from_pinyin(&hanzi_pair.pronounced_pinyin).concat();
+    let mut choices = vec![hanzi_pair.clone()];
+    for &idx in index.get_group(&syllables).unwrap_or_default() {
+        if choices.len() >= nb_choices {
+            break;
+        }
+        let tone_pair = corpus.pronounced_tone_pair(idx);
+        if choices
+            .iter()
+            .all(|choice| choice.pronounced_tone_pair != tone_pair)
+        {
+            choices.push(corpus.hanzi_pair(idx));
+        }
+    }
+    choices.sort_by(|a, b| a.pronounced_pinyin.cmp(&b.pronounced_pinyin));
+    choices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::parse_hanzi_pair;
+
+    fn get_corpus() -> Vec<HanziPair> {
+        [
+            "学校 xue2xiao4 24",
+            "学习 xue2xi2 22",
+            "小学 xiao3xue2 32",
+            "时候 shi2hou 25",
+            "事后 shi4hou4 44",
+            "地道 di4dao 45 di4dao4",
+            "事实 shi4shi2 42",
+            "实施 shi2shi1 21",
+        ]
+        .into_iter()
+        .map(|line| parse_hanzi_pair(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn tone_choices_are_confusable_tone_pairs() {
+        let corpus = get_corpus();
+        let choices = get_answer_choices(&corpus[0], ExerciseType::ToneOnly, &corpus[..], 4);
+        // The 2 and 4 tones are first taken for 3 and 1
+        assert_eq!(choices, vec!["14", "21", "24", "34"]);
+        // Choices do not depend on the call
+        assert_eq!(
+            get_answer_choices(&corpus[0], ExerciseType::ToneOnly, &corpus[..], 4),
+            choices
+        );
+    }
+
+    #[test]
+    fn pinyin_choices_start_with_corpus_words() {
+        let corpus = get_corpus();
+        let choices = get_answer_choices(&corpus[3], ExerciseType::Pinyin, &corpus[..], 3);
+        assert!(choices.contains(&"shi2hou".to_string()));
+        assert!(choices.contains(&"shi4hou4".to_string()));
+        assert_eq!(choices.len(), 3);
+        let choices = get_answer_choices(&corpus[0], ExerciseType::NoTonePinyin, &corpus[..], 4);
+        assert_eq!(choices, vec!["xuexi", "xuexiao"]);
+    }
+
+    #[test]
+    fn audio_choices_have_the_same_syllables() {
+        let corpus = get_corpus();
+        let index = MinimalPairIndex::new(&corpus[..]);
+        let characters = |choices: Vec<HanziPair>| -> Vec<String> {
+            choices
+                .into_iter()
+                .map(|choice| choice.characters)
+                .collect()
+        };
+        assert_eq!(
+            characters(get_audio_choices(&corpus[3], &index, &corpus[..], 4)),
+            vec!["时候", "事后"]
+        );
+        assert_eq!(
+            characters(get_audio_choices(&corpus[6], &index, &corpus[..], 4)),
+            vec!["实施", "事实"]
+        );
+        assert_eq!(
+            characters(get_audio_choices(&corpus[6], &index, &corpus[..], 1)),
+            vec!["事实"]
+        );
+        // Without a minimal pair, only the pair itself is offered
+        assert_eq!(
+            characters(get_audio_choices(&corpus[0], &index, &corpus[..], 4)),
+            vec!["学校"]
+        );
+    }
+
+    #[test]
+    fn other_readings_are_not_distractors() {
+        let corpus = get_corpus();
+        let choices = get_answer_choices(&corpus[5], ExerciseType::ToneOnly, &corpus[..], 6);
+        assert!(choices.contains(&"45".to_string()));
+        assert!(!choices.contains(&"44".to_string()));
+        assert_eq!(choices.len(), 6);
+    }
+}
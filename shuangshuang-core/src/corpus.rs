@@ -0,0 +1,588 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::exercise::{AudioQuality, HanziMetadata, HanziPair, HanziReading, Tone};
+use crate::utils::{get_pronounced_pinyin, get_syllables_from_pinyin, get_tones_from_pinyin};
+
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum CorpusError {
+    #[error("Expected 3 space separated fields, found {0}.")]
+    InvalidFieldCount(usize),
+    #[error("The word {0} is not exactly two hanzi long.")]
+    WordIsNotTwoHanziLong(String),
+    #[error("The pinyin {0} should only contain lowercase letters and tone numbers.")]
+    InvalidPinyin(String),
+    #[error("The tones {0} should be two tone numbers between 1 and 5.")]
+    InvalidToneNumbers(String),
+    #[error("The tone numbers of the pinyin {0} do not match the tones {1}.")]
+    PinyinToneNumbersUnmatch(String, String),
+    #[error("Invalid JSON record: {0}")]
+    InvalidRecord(String),
+    #[error("The audio quality {0} is unknown.")]
+    InvalidAudioQuality(String),
+    #[error(
+        "Version {0} of the corpus format is not supported, the latest is {CORPUS_FORMAT_VERSION}."
+    )]
+    UnsupportedFormatVersion(u32),
+}
+
+fn is_valid_pinyin(pinyin: &str) -> bool {
+    let is_valid_pinyin_char =
+        |c: char| c.is_ascii_lowercase() || c == ':' || ('1'..='5').contains(&c);
+    pinyin.chars().all(is_valid_pinyin_char) && get_syllables_from_pinyin(pinyin).len() == 2
+}
+
+// Alternative readings carry no tones field, their first syllable needs a tone number for the
+// syllables to be told apart
+fn parse_alternative_reading(characters: &str, pinyin: &str) -> Result<HanziReading, CorpusError> {
+    if !is_valid_pinyin(pinyin) {
+        return Err(CorpusError::InvalidPinyin(pinyin.to_string()));
+    }
+    let tones = get_tones_from_pinyin(pinyin);
+    let pronounced_pinyin = get_pronounced_pinyin(characters, pinyin, &(tones[0], tones[1]));
+    let pronounced_tone_pair = get_tones_from_pinyin(&pronounced_pinyin);
+    Ok(HanziReading {
+        pinyin: pinyin.to_string(),
+        pronounced_pinyin,
+        pronounced_tone_pair: (pronounced_tone_pair[0], pronounced_tone_pair[1]),
+    })
+}
+
+/// Parses one line of `hanzi_pairs.txt`, formatted as `hanzi pinyin tones`, e.g.
+/// `我们 wo3men 35`. Neutral tones have no number in the pinyin. The reading of the recording
+/// can be followed by the other readings of the word, separated by commas, e.g.
+/// `地道 di4dao 45 di4dao4`.
+pub fn parse_hanzi_pair(line: &str) -> Result<HanziPair, CorpusError> {
+    let splits: Vec<&str> = line.split(' ').collect();
+    let (characters, pinyin, tones, alternatives) = match splits[..] {
+        [characters, pinyin, tones] => (characters, pinyin, tones, None),
+        [characters, pinyin, tones, alternatives] => {
+            (characters, pinyin, tones, Some(alternatives))
+        }
+        _ => return Err(CorpusError::InvalidFieldCount(splits.len())),
+    };
+    if characters.chars().count() != 2 {
+        return Err(CorpusError::WordIsNotTwoHanziLong(characters.to_string()));
+    }
+    if !is_valid_pinyin(pinyin) {
+        return Err(CorpusError::InvalidPinyin(pinyin.to_string()));
+    }
+    let tone_list = tones
+        .chars()
+        .map(|c| Tone::from_str(&c.to_string()))
+        .collect::<Result<Vec<Tone>, _>>()
+        .map_err(|_| CorpusError::InvalidToneNumbers(tones.to_string()))?;
+    let [first_tone, second_tone] = tone_list[..] else {
+        return Err(CorpusError::InvalidToneNumbers(tones.to_string()));
+    };
+    let neutral_tone = Tone::NeutralTone.to_string();
+    if pinyin.replace(char::is_alphabetic, "").replace(':', "") != tones.replace(&neutral_tone, "")
+    {
+        return Err(CorpusError::PinyinToneNumbersUnmatch(
+            pinyin.to_string(),
+            tones.to_string(),
+        ));
+    }
+    let alternative_readings = alternatives
+        .map(|alternatives| {
+            alternatives
+                .split(',')
+                .map(|alternative| parse_alternative_reading(characters, alternative))
+                .collect::<Result<Vec<HanziReading>, CorpusError>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let tone_pair = (first_tone, second_tone);
+    let pronounced_pinyin = get_pronounced_pinyin(characters, pinyin, &tone_pair);
+    let pronounced_tone_pair = get_tones_from_pinyin(&pronounced_pinyin);
+    Ok(HanziPair {
+        characters: characters.to_string(),
+        pinyin: pinyin.to_string(),
+        pronounced_pinyin,
+        tone_pair,
+        pronounced_tone_pair: (pronounced_tone_pair[0], pronounced_tone_pair[1]),
+        pronunciation_override: None,
+        alternative_readings,
+        metadata: HanziMetadata::default(),
+    })
+}
+
+/// A recording pronounced differently from what the dictionary pinyin and tone sandhi give
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PronunciationOverride {
+    pub characters: String,
+    pub pronounced_pinyin: String,
+    pub reason: String,
+}
+
+/// Parses one line of `pronunciation_overrides.txt`, formatted as `hanzi pronounced_pinyin
+/// reason`, e.g. `好处 hao3chu The recording has a neutral second syllable`
+pub fn parse_pronunciation_override(line: &str) -> Result<PronunciationOverride, CorpusError> {
+    let splits: Vec<&str> = line.splitn(3, ' ').collect();
+    let [characters, pronounced_pinyin, reason] = splits[..] else {
+        return Err(CorpusError::InvalidFieldCount(splits.len()));
+    };
+    if characters.chars().count() != 2 {
+        return Err(CorpusError::WordIsNotTwoHanziLong(characters.to_string()));
+    }
+    // The first syllable needs a tone number for the syllables to be told apart
+    if !is_valid_pinyin(pronounced_pinyin) {
+        return Err(CorpusError::InvalidPinyin(pronounced_pinyin.to_string()));
+    }
+    Ok(PronunciationOverride {
+        characters: characters.to_string(),
+        pronounced_pinyin: pronounced_pinyin.to_string(),
+        reason: reason.trim().to_string(),
+    })
+}
+
+/// Parses every line of `pronunciation_overrides.txt` that is neither empty nor a `#`
+/// comment, along with its line number
+pub fn parse_pronunciation_overrides(
+    text: &str,
+) -> Vec<(usize, Result<PronunciationOverride, CorpusError>)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| (idx + 1, parse_pronunciation_override(line)))
+        .collect()
+}
+
+/// Replaces the sandhi pronunciation of the pairs having an override, and returns the overrides
+/// matching no pair
+pub fn apply_pronunciation_overrides<'a>(
+    hanzi_pairs: &mut [HanziPair],
+    overrides: &'a [PronunciationOverride],
+) -> Vec<&'a PronunciationOverride> {
+    let mut unused_overrides: Vec<&PronunciationOverride> = vec![];
+    for pronunciation_override in overrides {
+        let mut is_used = false;
+        for hanzi_pair in hanzi_pairs
+            .iter_mut()
+            .filter(|hanzi_pair| hanzi_pair.characters == pronunciation_override.characters)
+        {
+            let pronounced_tone_pair =
+                get_tones_from_pinyin(&pronunciation_override.pronounced_pinyin);
+            hanzi_pair.pronounced_pinyin = pronunciation_override.pronounced_pinyin.clone();
+            hanzi_pair.pronounced_tone_pair = (pronounced_tone_pair[0], pronounced_tone_pair[1]);
+            hanzi_pair.pronunciation_override = Some(pronunciation_override.reason.clone());
+            is_used = true;
+        }
+        if !is_used {
+            unused_overrides.push(pronunciation_override);
+        }
+    }
+    unused_overrides
+}
+
+/// Parses every non empty line of `hanzi_pairs.txt`, along with its line number
+pub fn parse_hanzi_pairs(text: &str) -> Vec<(usize, Result<HanziPair, CorpusError>)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| (idx + 1, parse_hanzi_pair(line)))
+        .collect()
+}
+
+/// Read access to the pairs of a corpus, whatever its storage
+pub trait HanziPairSource {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn pronounced_tone_pair(&self, idx: usize) -> (Tone, Tone);
+
+    /// The dictionary pinyin, before any tone sandhi
+    fn pinyin(&self, idx: usize) -> Cow<'_, str>;
+
+    fn pronounced_pinyin(&self, idx: usize) -> Cow<'_, str>;
+
+    fn hanzi_pair(&self, idx: usize) -> HanziPair;
+}
+
+impl HanziPairSource for [HanziPair] {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pronounced_tone_pair(&self, idx: usize) -> (Tone, Tone) {
+        self[idx].pronounced_tone_pair
+    }
+
+    fn pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(&self[idx].pinyin)
+    }
+
+    fn pronounced_pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(&self[idx].pronounced_pinyin)
+    }
+
+    fn hanzi_pair(&self, idx: usize) -> HanziPair {
+        self[idx].clone()
+    }
+}
+
+impl HanziPairSource for Vec<HanziPair> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn pronounced_tone_pair(&self, idx: usize) -> (Tone, Tone) {
+        self[idx].pronounced_tone_pair
+    }
+
+    fn pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(&self[idx].pinyin)
+    }
+
+    fn pronounced_pinyin(&self, idx: usize) -> Cow<'_, str> {
+        Cow::Borrowed(&self[idx].pronounced_pinyin)
+    }
+
+    fn hanzi_pair(&self, idx: usize) -> HanziPair {
+        self[idx].clone()
+    }
+}
+
+/// Name of the structured corpus format, in its header
+pub const CORPUS_FORMAT: &str = "shuangshuang-corpus";
+/// Latest version of the structured corpus format, described by `data/hanzi_pairs.schema.json`
+pub const CORPUS_FORMAT_VERSION: u32 = 1;
+
+/// First line of `hanzi_pairs.jsonl`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CorpusHeader {
+    pub format: String,
+    pub version: u32,
+}
+
+impl Default for CorpusHeader {
+    fn default() -> Self {
+        Self {
+            format: CORPUS_FORMAT.to_string(),
+            version: CORPUS_FORMAT_VERSION,
+        }
+    }
+}
+
+/// A line of `hanzi_pairs.jsonl` after the header. The readings use the fields of
+/// `hanzi_pairs.txt`, the metadata is optional.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+pub struct HanziRecord {
+    pub characters: String,
+    pub pinyin: String,
+    pub tones: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traditional: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hsk_level: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_rank: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub glosses: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_qualities: Option<Vec<String>>,
+}
+
+impl HanziRecord {
+    /// The record as a line of `hanzi_pairs.txt`, without its metadata
+    pub fn to_text_line(&self) -> String {
+        let mut line = format!("{} {} {}", self.characters, self.pinyin, self.tones);
+        if !self.alternatives.is_empty() {
+            line.push(' ');
+            line.push_str(&self.alternatives.join(","));
+        }
+        line
+    }
+}
+
+impl From<&HanziPair> for HanziRecord {
+    fn from(hanzi_pair: &HanziPair) -> Self {
+        let metadata = &hanzi_pair.metadata;
+        Self {
+            characters: hanzi_pair.characters.clone(),
+            pinyin: hanzi_pair.pinyin.clone(),
+            tones: format!("{}{}", hanzi_pair.tone_pair.0, hanzi_pair.tone_pair.1),
+            alternatives: hanzi_pair
+                .alternative_readings
+                .iter()
+                .map(|reading| reading.pinyin.clone())
+                .collect(),
+            traditional: metadata.traditional.clone(),
+            hsk_level: metadata.hsk_level,
+            frequency_rank: metadata.frequency_rank,
+            glosses: metadata.glosses.clone(),
+            audio_qualities: metadata.audio_qualities.as_ref().map(|audio_qualities| {
+                audio_qualities
+                    .iter()
+                    .map(|audio_quality| audio_quality.to_string())
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Parses one record of `hanzi_pairs.jsonl`, its readings are validated like the lines of
+/// `hanzi_pairs.txt`
+pub fn parse_hanzi_record(line: &str) -> Result<HanziPair, CorpusError> {
+    let record: HanziRecord =
+        serde_json::from_str(line).map_err(|e| CorpusError::InvalidRecord(e.to_string()))?;
+    let mut hanzi_pair = parse_hanzi_pair(&record.to_text_line())?;
+    let audio_qualities = record
+        .audio_qualities
+        .map(|audio_qualities| {
+            audio_qualities
+                .iter()
+                .map(|audio_quality| {
+                    AudioQuality::from_str(audio_quality)
+                        .map_err(|_| CorpusError::InvalidAudioQuality(audio_quality.clone()))
+                })
+                .collect::<Result<Vec<AudioQuality>, CorpusError>>()
+        })
+        .transpose()?;
+    hanzi_pair.metadata = HanziMetadata {
+        traditional: record.traditional,
+        hsk_level: record.hsk_level,
+        frequency_rank: record.frequency_rank,
+        glosses: record.glosses,
+        audio_qualities,
+    };
+    Ok(hanzi_pair)
+}
+
+/// Parses a corpus in either format: `hanzi_pairs.jsonl`, recognized by its header, or the
+/// older `hanzi_pairs.txt`. Every pair comes with its line number.
+pub fn parse_corpus(text: &str) -> Vec<(usize, Result<HanziPair, CorpusError>)> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .and_then(|(_, line)| serde_json::from_str::<CorpusHeader>(line).ok())
+        .filter(|header| header.format == CORPUS_FORMAT);
+    match header {
+        None => parse_hanzi_pairs(text),
+        Some(header) if header.version > CORPUS_FORMAT_VERSION => vec![(
+            1,
+            Err(CorpusError::UnsupportedFormatVersion(header.version)),
+        )],
+        Some(_) => lines
+            .map(|(idx, line)| (idx + 1, parse_hanzi_record(line)))
+            .collect(),
+    }
+}
+
+/// Formats a corpus as `hanzi_pairs.jsonl`
+pub fn format_corpus_jsonl(records: &[HanziRecord]) -> String {
+    let mut text =
+        serde_json::to_string(&CorpusHeader::default()).expect("Corpus header should serialize");
+    text.push('\n');
+    for record in records {
+        text.push_str(&serde_json::to_string(record).expect("Hanzi record should serialize"));
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hanzi_pair_applies_sandhi() {
+        let hanzi_pair = parse_hanzi_pair("你好 ni3hao3 33").unwrap();
+        assert_eq!(hanzi_pair.tone_pair, (Tone::Tone3, Tone::Tone3));
+        assert_eq!(hanzi_pair.pronounced_pinyin, "ni2hao3");
+        assert_eq!(hanzi_pair.pronounced_tone_pair, (Tone::Tone2, Tone::Tone3));
+        let hanzi_pair = parse_hanzi_pair("我们 wo3men 35").unwrap();
+        assert_eq!(
+            hanzi_pair.pronounced_tone_pair,
+            (Tone::Tone3, Tone::NeutralTone)
+        );
+    }
+
+    #[test]
+    fn parse_hanzi_pair_reads_alternative_readings() {
+        let hanzi_pair = parse_hanzi_pair("一点 yi1dian3 13 yi1dian,yi4dian3").unwrap();
+        assert_eq!(hanzi_pair.pronounced_pinyin, "yi4dian3");
+        assert_eq!(
+            hanzi_pair.alternative_readings,
+            vec![
+                HanziReading {
+                    pinyin: "yi1dian".to_string(),
+                    pronounced_pinyin: "yi4dian".to_string(),
+                    pronounced_tone_pair: (Tone::Tone4, Tone::NeutralTone),
+                },
+                HanziReading {
+                    pinyin: "yi4dian3".to_string(),
+                    pronounced_pinyin: "yi4dian3".to_string(),
+                    pronounced_tone_pair: (Tone::Tone4, Tone::Tone3),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_hanzi_pair("一点 yi1dian3 13 yidian3"),
+            Err(CorpusError::InvalidPinyin("yidian3".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_hanzi_pair_rejects_invalid_lines() {
+        assert_eq!(
+            parse_hanzi_pair("我们 wo3men"),
+            Err(CorpusError::InvalidFieldCount(2))
+        );
+        assert_eq!(
+            parse_hanzi_pair("我们 wo3men 35 wo3men5 x"),
+            Err(CorpusError::InvalidFieldCount(5))
+        );
+        assert_eq!(
+            parse_hanzi_pair("我 wo3 3"),
+            Err(CorpusError::WordIsNotTwoHanziLong("我".to_string()))
+        );
+        assert_eq!(
+            parse_hanzi_pair("我们 Wo3men 35"),
+            Err(CorpusError::InvalidPinyin("Wo3men".to_string()))
+        );
+        assert_eq!(
+            parse_hanzi_pair("我们 wo3men 36"),
+            Err(CorpusError::InvalidToneNumbers("36".to_string()))
+        );
+        assert_eq!(
+            parse_hanzi_pair("我们 wo3men 34"),
+            Err(CorpusError::PinyinToneNumbersUnmatch(
+                "wo3men".to_string(),
+                "34".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn pronunciation_overrides_replace_sandhi() {
+        let mut hanzi_pairs = vec![
+            parse_hanzi_pair("好处 hao3chu3 33").unwrap(),
+            parse_hanzi_pair("学校 xue2xiao4 24").unwrap(),
+        ];
+        assert_eq!(hanzi_pairs[0].pronounced_pinyin, "hao2chu3");
+        let overrides: Vec<PronunciationOverride> = parse_pronunciation_overrides(
+            "# comment\n\n好处 hao3chu The recording has a neutral tone\n第一 di4yi1 Ordinal\n",
+        )
+        .into_iter()
+        .map(|(_, pronunciation_override)| pronunciation_override.unwrap())
+        .collect();
+        let unused_overrides = apply_pronunciation_overrides(&mut hanzi_pairs, &overrides);
+        assert_eq!(unused_overrides, vec![&overrides[1]]);
+        assert_eq!(hanzi_pairs[0].pronounced_pinyin, "hao3chu");
+        assert_eq!(
+            hanzi_pairs[0].pronounced_tone_pair,
+            (Tone::Tone3, Tone::NeutralTone)
+        );
+        assert_eq!(
+            hanzi_pairs[0].pronunciation_override.as_deref(),
+            Some("The recording has a neutral tone")
+        );
+        assert_eq!(hanzi_pairs[1].pronunciation_override, None);
+    }
+
+    #[test]
+    fn parse_pronunciation_override_rejects_invalid_lines() {
+        assert_eq!(
+            parse_pronunciation_override("好处 hao3chu"),
+            Err(CorpusError::InvalidFieldCount(2))
+        );
+        assert_eq!(
+            parse_pronunciation_override("好处 haochu3 No tone on the first syllable"),
+            Err(CorpusError::InvalidPinyin("haochu3".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_corpus_reads_both_formats() {
+        let text_corpus = "学校 xue2xiao4 24\n\n一点 yi1dian3 13 yi1dian\n";
+        let hanzi_pairs: Vec<HanziPair> = parse_corpus(text_corpus)
+            .into_iter()
+            .map(|(_, hanzi_pair)| hanzi_pair.unwrap())
+            .collect();
+        assert_eq!(hanzi_pairs[0].metadata, HanziMetadata::default());
+        let mut records: Vec<HanziRecord> = hanzi_pairs.iter().map(HanziRecord::from).collect();
+        records[0].hsk_level = Some(1);
+        records[0].traditional = Some("學校".to_string());
+        records[0]
+            .glosses
+            .insert("en".to_string(), "school".to_string());
+        records[0].audio_qualities = Some(vec!["64k".to_string()]);
+        let jsonl_corpus = format_corpus_jsonl(&records);
+        assert!(jsonl_corpus.starts_with(r#"{"format":"shuangshuang-corpus","version":1}"#));
+        let parsed_pairs: Vec<HanziPair> = parse_corpus(&jsonl_corpus)
+            .into_iter()
+            .map(|(_, hanzi_pair)| hanzi_pair.unwrap())
+            .collect();
+        assert_eq!(parsed_pairs[1], hanzi_pairs[1]);
+        assert_eq!(parsed_pairs[0].pronounced_pinyin, "xue2xiao4");
+        assert_eq!(parsed_pairs[0].metadata.hsk_level, Some(1));
+        assert_eq!(parsed_pairs[0].metadata.glosses["en"], "school");
+        assert_eq!(
+            parsed_pairs[0].metadata.audio_qualities,
+            Some(vec![AudioQuality::Q64k])
+        );
+    }
+
+    #[test]
+    fn parse_corpus_rejects_invalid_records() {
+        let corpus = "{\"format\":\"shuangshuang-corpus\",\"version\":1}\n\
+            {\"characters\":\"学校\",\"pinyin\":\"xue2xiao4\",\"tones\":\"23\"}\n\
+            {\"characters\":\"学校\",\"pinyin\":\"xue2xiao4\",\"tones\":\"24\",\"audio_qualities\":[\"8k\"]}\n\
+            {\"characters\":\"学校\"}\n";
+        let results = parse_corpus(corpus);
+        assert_eq!(results[0].0, 2);
+        assert!(matches!(
+            results[0].1,
+            Err(CorpusError::PinyinToneNumbersUnmatch(_, _))
+        ));
+        assert_eq!(
+            results[1].1,
+            Err(CorpusError::InvalidAudioQuality("8k".to_string()))
+        );
+        assert!(matches!(results[2].1, Err(CorpusError::InvalidRecord(_))));
+        assert_eq!(
+            parse_corpus("{\"format\":\"shuangshuang-corpus\",\"version\":2}\n"),
+            vec![(1, Err(CorpusError::UnsupportedFormatVersion(2)))]
+        );
+    }
+
+    #[test]
+    fn bundled_corpus_is_valid() {
+        let corpus = include_str!("../../data/hanzi_pairs.txt");
+        let mut hanzi_pairs: Vec<HanziPair> = vec![];
+        for (line_number, hanzi_pair) in parse_hanzi_pairs(corpus) {
+            assert!(hanzi_pair.is_ok(), "line {line_number}: {hanzi_pair:?}");
+            hanzi_pairs.extend(hanzi_pair);
+        }
+        let jsonl_corpus = include_str!("../../data/hanzi_pairs.jsonl");
+        let jsonl_pairs: Vec<HanziPair> = parse_corpus(jsonl_corpus)
+            .into_iter()
+            .map(|(line_number, hanzi_pair)| {
+                hanzi_pair.unwrap_or_else(|e| panic!("jsonl line {line_number}: {e}"))
+            })
+            .collect();
+        assert_eq!(jsonl_pairs.len(), hanzi_pairs.len());
+        let overrides = include_str!("../../data/pronunciation_overrides.txt");
+        let overrides: Vec<PronunciationOverride> = parse_pronunciation_overrides(overrides)
+            .into_iter()
+            .map(|(line_number, pronunciation_override)| {
+                pronunciation_override
+                    .unwrap_or_else(|e| panic!("overrides line {line_number}: {e}"))
+            })
+            .collect();
+        assert!(apply_pronunciation_overrides(&mut hanzi_pairs, &overrides).is_empty());
+    }
+}
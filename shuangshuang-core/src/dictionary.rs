@@ -0,0 +1,192 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Replaced by the characters of the word in a URL template
+pub const HANZI_PLACEHOLDER: &str = "{hanzi}";
+/// Replaced by the pinyin of the word, with tone numbers, in a URL template
+pub const PINYIN_PLACEHOLDER: &str = "{pinyin}";
+
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum DictionaryError {
+    #[error("Invalid str value for DictionaryProvider")]
+    ParseDictionaryProviderError,
+    #[error("The URL template {0} has no {HANZI_PLACEHOLDER} or {PINYIN_PLACEHOLDER}.")]
+    MissingPlaceholder(String),
+    #[error("The URL template {0} is not a URL.")]
+    InvalidUrl(String),
+}
+
+/// Where the dictionary links of the app lead, stored in the user settings
+#[derive(Default, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DictionaryProvider {
+    #[default]
+    Mdbg,
+    Pleco,
+    Wiktionary,
+    YellowBridge,
+    /// Chinese to French
+    Reverso,
+    /// A URL template entered by the user, see `DictionaryProvider::custom`
+    Custom(String),
+}
+
+impl fmt::Display for DictionaryProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Mdbg => "mdbg",
+            Self::Pleco => "pleco",
+            Self::Wiktionary => "wiktionary",
+            Self::YellowBridge => "yellowbridge",
+            Self::Reverso => "reverso",
+            Self::Custom(_) => "custom",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Only parses the built-in providers, custom ones come from `DictionaryProvider::custom`
+impl FromStr for DictionaryProvider {
+    type Err = DictionaryError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mdbg" => Ok(Self::Mdbg),
+            "pleco" => Ok(Self::Pleco),
+            "wiktionary" => Ok(Self::Wiktionary),
+            "yellowbridge" => Ok(Self::YellowBridge),
+            "reverso" => Ok(Self::Reverso),
+            _ => Err(DictionaryError::ParseDictionaryProviderError),
+        }
+    }
+}
+
+impl DictionaryProvider {
+    pub const BUILT_IN: [Self; 5] = [
+        Self::Mdbg,
+        Self::Pleco,
+        Self::Wiktionary,
+        Self::YellowBridge,
+        Self::Reverso,
+    ];
+
+    /// Provider used until the user picks one, a dictionary in the language of the app
+    pub fn default_for_language(language: &str) -> Self {
+        match language {
+            "fr" => Self::Reverso,
+            _ => Self::Mdbg,
+        }
+    }
+
+    /// A provider following `template`, which needs at least one placeholder
+    pub fn custom(template: &str) -> Result<Self, DictionaryError> {
+        let template = template.trim();
+        if !template.contains(HANZI_PLACEHOLDER) && !template.contains(PINYIN_PLACEHOLDER) {
+            return Err(DictionaryError::MissingPlaceholder(template.to_string()));
+        }
+        // Apps like Pleco have their own scheme, so any scheme is accepted
+        let is_url = template.split_once("://").is_some_and(|(scheme, _)| {
+            !scheme.is_empty() && scheme.chars().all(char::is_alphanumeric)
+        });
+        if !is_url {
+            return Err(DictionaryError::InvalidUrl(template.to_string()));
+        }
+        Ok(Self::Custom(template.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Mdbg => "MDBG",
+            Self::Pleco => "Pleco",
+            Self::Wiktionary => "Wiktionary",
+            Self::YellowBridge => "YellowBridge",
+            Self::Reverso => "Reverso",
+            Self::Custom(_) => "Custom",
+        }
+    }
+
+    /// Template of the page of a word
+    pub fn hanzi_url_template(&self) -> &str {
+        match self {
+            Self::Mdbg => "https://www.mdbg.net/chinese/dictionary?wdqb={hanzi}",
+            Self::Pleco => "plecoapi://x-callback-url/s?q={hanzi}",
+            Self::Wiktionary => "https://en.wiktionary.org/wiki/{hanzi}#Chinese",
+            Self::YellowBridge => {
+                "https://www.yellowbridge.com/chinese/dictionary.php?word={hanzi}"
+            }
+            Self::Reverso => "https://dictionnaire.reverso.net/chinois-francais/{hanzi}",
+            Self::Custom(template) => template,
+        }
+    }
+
+    /// Template of a search by pinyin, for the providers that have one
+    pub fn pinyin_url_template(&self) -> Option<&str> {
+        match self {
+            Self::Mdbg => Some("https://www.mdbg.net/chinese/dictionary?wdqb={pinyin}"),
+            Self::Pleco => Some("plecoapi://x-callback-url/s?q={pinyin}"),
+            Self::Wiktionary | Self::YellowBridge | Self::Reverso => None,
+            Self::Custom(template) => (!template.contains(HANZI_PLACEHOLDER)).then_some(template),
+        }
+    }
+
+    /// Link to the page of a word, custom templates can also use its pinyin
+    pub fn hanzi_url(&self, hanzi: &str, pinyin: &str) -> String {
+        fill_template(self.hanzi_url_template(), hanzi, pinyin)
+    }
+
+    /// Link to a search of `pinyin`, when the provider has one
+    pub fn pinyin_url(&self, pinyin: &str) -> Option<String> {
+        self.pinyin_url_template()
+            .map(|template| fill_template(template, "", pinyin))
+    }
+}
+
+fn fill_template(template: &str, hanzi: &str, pinyin: &str) -> String {
+    template
+        .replace(HANZI_PLACEHOLDER, &urlencoding::encode(hanzi))
+        .replace(PINYIN_PLACEHOLDER, &urlencoding::encode(pinyin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_providers_format_urls() {
+        assert_eq!(
+            DictionaryProvider::Mdbg.hanzi_url("学校", "xue2xiao4"),
+            "https://www.mdbg.net/chinese/dictionary?wdqb=%E5%AD%A6%E6%A0%A1"
+        );
+        assert_eq!(
+            DictionaryProvider::Pleco.pinyin_url("xue2xiao4").as_deref(),
+            Some("plecoapi://x-callback-url/s?q=xue2xiao4")
+        );
+        assert_eq!(DictionaryProvider::Wiktionary.pinyin_url("xue2xiao4"), None);
+        for provider in DictionaryProvider::BUILT_IN {
+            assert_eq!(provider.to_string().parse(), Ok(provider));
+        }
+    }
+
+    #[test]
+    fn custom_providers_are_validated() {
+        let provider = DictionaryProvider::custom(" https://example.com/?q={hanzi}&p={pinyin} ")
+            .expect("Template should be valid");
+        assert_eq!(
+            provider.hanzi_url("学校", "xue2xiao4"),
+            "https://example.com/?q=%E5%AD%A6%E6%A0%A1&p=xue2xiao4"
+        );
+        assert_eq!(provider.pinyin_url("xue2xiao4"), None);
+        assert_eq!(
+            DictionaryProvider::custom("https://example.com/?q=").unwrap_err(),
+            DictionaryError::MissingPlaceholder("https://example.com/?q=".to_string())
+        );
+        assert_eq!(
+            DictionaryProvider::custom("example.com/{hanzi}").unwrap_err(),
+            DictionaryError::InvalidUrl("example.com/{hanzi}".to_string())
+        );
+        assert_eq!(
+            "custom".parse::<DictionaryProvider>(),
+            Err(DictionaryError::ParseDictionaryProviderError)
+        );
+    }
+}
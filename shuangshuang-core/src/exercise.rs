@@ -0,0 +1,737 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+use thiserror::Error;
+
+use crate::pinyin_drills::PinyinContrast;
+
+/// Language of the glosses every corpus word should have
+pub const FALLBACK_GLOSS_LANGUAGE: &str = "en";
+
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum ExerciseError {
+    #[error("Invalid str value for ExerciseDifficulty")]
+    ParseExerciseDifficultyError,
+    #[error("Invalid str value for Shuffling mode")]
+    ParseShuffleModeError,
+    #[error("Invalid str value for ExerciseType")]
+    ParseExerciseTypeError,
+    #[error("Invalid str value for InputStyle")]
+    ParseInputStyleError,
+    #[error("Invalid str value for AudioQuality")]
+    ParseAudioQualityError,
+    #[error("Invalid str value for NoiseType")]
+    ParseNoiseTypeError,
+    #[error("Invalid str value for PinyinContrast")]
+    ParsePinyinContrastError,
+    #[error("Invalid str value for GameMode")]
+    ParseGameModeError,
+    #[error("Unknown error")]
+    Unknown,
+}
+
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum ToneError {
+    #[error("Invalid str value for Tone")]
+    ParseToneError,
+    #[error("Unknown error")]
+    Unknown,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[allow(dead_code)]
+pub enum Tone {
+    Tone1,
+    Tone2,
+    Tone3,
+    Tone4,
+    #[default]
+    NeutralTone,
+}
+
+impl FromStr for Tone {
+    type Err = ToneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::Tone1),
+            "2" => Ok(Self::Tone2),
+            "3" => Ok(Self::Tone3),
+            "4" => Ok(Self::Tone4),
+            "5" => Ok(Self::NeutralTone),
+            _ => Err(ToneError::ParseToneError),
+        }
+    }
+}
+
+impl From<char> for Tone {
+    fn from(value: char) -> Self {
+        match value {
+            '1' => Self::Tone1,
+            '2' => Self::Tone2,
+            '3' => Self::Tone3,
+            '4' => Self::Tone4,
+            '5' => Self::NeutralTone,
+            _ => Self::NeutralTone,
+        }
+    }
+}
+
+impl fmt::Display for Tone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Tone1 => "1",
+            Self::Tone2 => "2",
+            Self::Tone3 => "3",
+            Self::Tone4 => "4",
+            Self::NeutralTone => "5",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// A tone sandhi rule changing how a pair is pronounced
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum SandhiRule {
+    /// Two third tones, the first becomes a second tone
+    ThirdTone,
+    /// 不 becomes a second tone before a fourth tone
+    BuBeforeFourthTone,
+    /// 一 becomes a second tone before a fourth tone
+    YiBeforeFourthTone,
+    /// 一 becomes a fourth tone before the other tones
+    YiBeforeOtherTones,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum ExerciseDifficulty {
+    #[default]
+    FirstTime,
+    Easy,
+    Normal,
+    Hard,
+    Native,
+    Custom,
+}
+
+impl fmt::Display for ExerciseDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::FirstTime => "first_time",
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Native => "native",
+            Self::Custom => "custom",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for ExerciseDifficulty {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first_time" => Ok(Self::FirstTime),
+            "easy" => Ok(Self::Easy),
+            "normal" => Ok(Self::Normal),
+            "hard" => Ok(Self::Hard),
+            "native" => Ok(Self::Native),
+            "custom" => Ok(Self::Custom),
+            _ => Err(ExerciseError::ParseExerciseDifficultyError),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[allow(dead_code)]
+pub enum ExerciseType {
+    #[default]
+    ToneOnly,
+    NoTonePinyin,
+    Pinyin,
+    Production,
+    /// Two words differing only in their tones are played, see `minimal_pairs`
+    MinimalPair,
+    /// The pair is shown and the learner picks its recording among others with the same
+    /// syllables, see `choices::get_audio_choices`
+    ReverseAudio,
+    /// The characters of the pair are typed, any word of the corpus read the same is accepted
+    /// unless `ExerciseParams::exact_hanzi_match` is set
+    Hanzi,
+    /// The pair is shown with its dictionary pinyin and the learner gives the tones it is
+    /// pronounced with, its recording is played once answered
+    Sandhi,
+    /// A syllable of the played word is asked for one side of an initial or final contrast,
+    /// like zh/z, see `pinyin_drills`
+    PinyinDrill,
+    /// Each pair is asked as tone only, toneless pinyin or full pinyin, in the proportions of
+    /// `ExerciseParams::exercise_mix`
+    Mixed,
+}
+
+impl fmt::Display for ExerciseType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::ToneOnly => "tone_only",
+            Self::NoTonePinyin => "no_tone_pinyin",
+            Self::Pinyin => "pinyin",
+            Self::Production => "production",
+            Self::MinimalPair => "minimal_pair",
+            Self::ReverseAudio => "reverse_audio",
+            Self::Hanzi => "hanzi",
+            Self::Sandhi => "sandhi",
+            Self::PinyinDrill => "pinyin_drill",
+            Self::Mixed => "mixed",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for ExerciseType {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tone_only" => Ok(Self::ToneOnly),
+            "no_tone_pinyin" => Ok(Self::NoTonePinyin),
+            "pinyin" => Ok(Self::Pinyin),
+            "production" => Ok(Self::Production),
+            "minimal_pair" => Ok(Self::MinimalPair),
+            "reverse_audio" => Ok(Self::ReverseAudio),
+            "hanzi" => Ok(Self::Hanzi),
+            "sandhi" => Ok(Self::Sandhi),
+            "pinyin_drill" => Ok(Self::PinyinDrill),
+            "mixed" => Ok(Self::Mixed),
+            _ => Err(ExerciseError::ParseExerciseTypeError),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum InputStyle {
+    #[default]
+    Keyboard,
+    Touch,
+    /// A few answers to pick from, see `choices::get_answer_choices`
+    MultipleChoice,
+}
+
+impl fmt::Display for InputStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Keyboard => "keyboard",
+            Self::Touch => "touch",
+            Self::MultipleChoice => "multiple_choice",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for InputStyle {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyboard" => Ok(Self::Keyboard),
+            "touch" => Ok(Self::Touch),
+            "multiple_choice" => Ok(Self::MultipleChoice),
+            _ => Err(ExerciseError::ParseInputStyleError),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum ShuffleMode {
+    Random,
+    #[default]
+    Even,
+}
+
+impl fmt::Display for ShuffleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Random => "random",
+            Self::Even => "even",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for ShuffleMode {
+    type Err = ExerciseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "even" => Ok(Self::Even),
+            _ => Err(ExerciseError::ParseShuffleModeError),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum AudioQuality {
+    Q18k,
+    Q24k,
+    #[default]
+    Q64k,
+    Q96k,
+}
+
+impl fmt::Display for AudioQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Q18k => "18k-abr",
+            Self::Q24k => "24k-abr",
+            Self::Q64k => "64k",
+            Self::Q96k => "96k",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for AudioQuality {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "18k-abr" => Ok(Self::Q18k),
+            "24k-abr" => Ok(Self::Q24k),
+            "64k" => Ok(Self::Q64k),
+            "96k" => Ok(Self::Q96k),
+            _ => Err(ExerciseError::ParseAudioQualityError),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum NoiseType {
+    #[default]
+    Silence,
+    WhiteNoise,
+    PinkNoise,
+    CafeLoop,
+    StreetLoop,
+}
+
+impl fmt::Display for NoiseType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Silence => "silence",
+            Self::WhiteNoise => "white",
+            Self::PinkNoise => "pink",
+            Self::CafeLoop => "cafe",
+            Self::StreetLoop => "street",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for NoiseType {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "silence" => Ok(Self::Silence),
+            "white" => Ok(Self::WhiteNoise),
+            "pink" => Ok(Self::PinkNoise),
+            "cafe" => Ok(Self::CafeLoop),
+            "street" => Ok(Self::StreetLoop),
+            _ => Err(ExerciseError::ParseNoiseTypeError),
+        }
+    }
+}
+
+/// Relative proportions of the exercise types of a mixed session, e.g. 2, 1 and 1 ask half of
+/// the pairs as tone only
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExerciseMix {
+    pub tone_only: u32,
+    pub no_tone_pinyin: u32,
+    pub pinyin: u32,
+}
+
+impl Default for ExerciseMix {
+    fn default() -> Self {
+        Self {
+            tone_only: 1,
+            no_tone_pinyin: 1,
+            pinyin: 1,
+        }
+    }
+}
+
+impl ExerciseMix {
+    pub fn weights(&self) -> [(ExerciseType, u32); 3] {
+        [
+            (ExerciseType::ToneOnly, self.tone_only),
+            (ExerciseType::NoTonePinyin, self.no_tone_pinyin),
+            (ExerciseType::Pinyin, self.pinyin),
+        ]
+    }
+}
+
+/// How a session ends. The survival and time attack modes ask pairs from the whole corpus,
+/// without repeats until it is exhausted, and their scores are kept as high scores
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum GameMode {
+    /// The session ends after `ExerciseParams::exercise_size` pairs
+    #[default]
+    Standard,
+    /// The session ends at the `max_mistakes`th incorrect answer
+    Survival { max_mistakes: u32 },
+    /// The session ends when `time_limit_secs` seconds have passed
+    TimeAttack { time_limit_secs: u32 },
+}
+
+impl GameMode {
+    /// Whether pairs are asked until the session ends instead of a fixed number of them
+    pub fn is_endless(&self) -> bool {
+        *self != Self::Standard
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Survival { max_mistakes } => write!(f, "survival_{max_mistakes}"),
+            Self::TimeAttack { time_limit_secs } => write!(f, "time_attack_{time_limit_secs}"),
+        }
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "standard" {
+            return Ok(Self::Standard);
+        }
+        // A mode with no mistake allowed or no time would end before the first pair
+        let parse_limit = |limit: &str| match limit.parse::<u32>() {
+            Ok(limit) if limit > 0 => Ok(limit),
+            _ => Err(ExerciseError::ParseGameModeError),
+        };
+        if let Some(max_mistakes) = s.strip_prefix("survival_") {
+            Ok(Self::Survival {
+                max_mistakes: parse_limit(max_mistakes)?,
+            })
+        } else if let Some(time_limit_secs) = s.strip_prefix("time_attack_") {
+            Ok(Self::TimeAttack {
+                time_limit_secs: parse_limit(time_limit_secs)?,
+            })
+        } else {
+            Err(ExerciseError::ParseGameModeError)
+        }
+    }
+}
+
+/// Noise mixed under the word recordings, with its signal-to-noise ratio in dB
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BackgroundNoise {
+    pub noise_type: NoiseType,
+    pub snr_db: i32,
+}
+
+impl Default for BackgroundNoise {
+    fn default() -> Self {
+        Self {
+            noise_type: NoiseType::default(),
+            snr_db: 20,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExerciseParams {
+    pub exercise_size: u32,
+    pub exercise_type: ExerciseType,
+    pub input_style: InputStyle,
+    pub shuffle_mode: ShuffleMode,
+    pub timer_on: bool,
+    pub audio_quality: AudioQuality,
+    pub audio_retries: u32,
+    pub background_noise: BackgroundNoise,
+    /// Whether the other dictionary readings of a pair are correct answers too
+    pub accept_alternative_readings: bool,
+    /// Whether the meaning of each pair is shown after answering, before the next one
+    pub show_meaning_after_answer: bool,
+    /// Whether each answer is graded right away, before the next pair
+    pub immediate_feedback: bool,
+    /// How many times a missed pair can be asked in a session, it is asked again after the
+    /// other pairs. 1 means no retry
+    pub max_attempts: u32,
+    /// Whether the hanzi exercise only accepts the characters of the recording, not its
+    /// homophones
+    pub exact_hanzi_match: bool,
+    /// The contrast asked by the initial and final drill, every contrast when None
+    pub drill_contrast: Option<PinyinContrast>,
+    /// Proportions of the exercise types of a mixed session
+    pub exercise_mix: ExerciseMix,
+    pub game_mode: GameMode,
+    /// The difficulty the params come from, high scores are kept per difficulty
+    pub difficulty: ExerciseDifficulty,
+}
+
+impl Default for ExerciseParams {
+    fn default() -> Self {
+        Self {
+            exercise_size: 10,
+            exercise_type: ExerciseType::default(),
+            input_style: InputStyle::default(),
+            shuffle_mode: ShuffleMode::default(),
+            timer_on: false,
+            audio_quality: AudioQuality::default(),
+            audio_retries: 3,
+            background_noise: BackgroundNoise::default(),
+            accept_alternative_readings: false,
+            show_meaning_after_answer: false,
+            immediate_feedback: false,
+            max_attempts: 1,
+            exact_hanzi_match: false,
+            drill_contrast: None,
+            exercise_mix: ExerciseMix::default(),
+            game_mode: GameMode::default(),
+            difficulty: ExerciseDifficulty::Custom,
+        }
+    }
+}
+
+impl From<ExerciseDifficulty> for ExerciseParams {
+    fn from(value: ExerciseDifficulty) -> Self {
+        match value {
+            ExerciseDifficulty::FirstTime => Self {
+                exercise_size: 10,
+                exercise_type: ExerciseType::ToneOnly,
+                input_style: InputStyle::Touch,
+                shuffle_mode: ShuffleMode::default(),
+                timer_on: false,
+                audio_quality: AudioQuality::Q64k,
+                audio_retries: 10,
+                background_noise: BackgroundNoise::default(),
+                accept_alternative_readings: false,
+                show_meaning_after_answer: false,
+                immediate_feedback: false,
+                max_attempts: 1,
+                exact_hanzi_match: false,
+                drill_contrast: None,
+                exercise_mix: ExerciseMix::default(),
+                game_mode: GameMode::default(),
+                difficulty: ExerciseDifficulty::FirstTime,
+            },
+            ExerciseDifficulty::Easy => Self {
+                exercise_size: 15,
+                exercise_type: ExerciseType::ToneOnly,
+                input_style: InputStyle::Keyboard,
+                shuffle_mode: ShuffleMode::default(),
+                timer_on: false,
+                audio_quality: AudioQuality::Q64k,
+                audio_retries: 5,
+                background_noise: BackgroundNoise::default(),
+                accept_alternative_readings: false,
+                show_meaning_after_answer: false,
+                immediate_feedback: false,
+                max_attempts: 1,
+                exact_hanzi_match: false,
+                drill_contrast: None,
+                exercise_mix: ExerciseMix::default(),
+                game_mode: GameMode::default(),
+                difficulty: ExerciseDifficulty::Easy,
+            },
+            ExerciseDifficulty::Normal => Self {
+                exercise_size: 20,
+                exercise_type: ExerciseType::Pinyin,
+                input_style: InputStyle::Keyboard,
+                shuffle_mode: ShuffleMode::default(),
+                timer_on: false,
+                audio_quality: AudioQuality::Q64k,
+                audio_retries: 3,
+                background_noise: BackgroundNoise::default(),
+                accept_alternative_readings: false,
+                show_meaning_after_answer: false,
+                immediate_feedback: false,
+                max_attempts: 1,
+                exact_hanzi_match: false,
+                drill_contrast: None,
+                exercise_mix: ExerciseMix::default(),
+                game_mode: GameMode::default(),
+                difficulty: ExerciseDifficulty::Normal,
+            },
+            ExerciseDifficulty::Hard => Self {
+                exercise_size: 25,
+                exercise_type: ExerciseType::Pinyin,
+                input_style: InputStyle::Keyboard,
+                shuffle_mode: ShuffleMode::default(),
+                timer_on: false,
+                audio_quality: AudioQuality::Q24k,
+                audio_retries: 2,
+                background_noise: BackgroundNoise {
+                    noise_type: NoiseType::PinkNoise,
+                    snr_db: 10,
+                },
+                accept_alternative_readings: false,
+                show_meaning_after_answer: false,
+                immediate_feedback: false,
+                max_attempts: 1,
+                exact_hanzi_match: false,
+                drill_contrast: None,
+                exercise_mix: ExerciseMix::default(),
+                game_mode: GameMode::default(),
+                difficulty: ExerciseDifficulty::Hard,
+            },
+            ExerciseDifficulty::Native => Self {
+                exercise_size: 40,
+                exercise_type: ExerciseType::Pinyin,
+                input_style: InputStyle::Keyboard,
+                shuffle_mode: ShuffleMode::default(),
+                timer_on: true,
+                audio_quality: AudioQuality::Q18k,
+                audio_retries: 1,
+                background_noise: BackgroundNoise {
+                    noise_type: NoiseType::CafeLoop,
+                    snr_db: 5,
+                },
+                accept_alternative_readings: false,
+                show_meaning_after_answer: false,
+                immediate_feedback: false,
+                max_attempts: 1,
+                exact_hanzi_match: false,
+                drill_contrast: None,
+                exercise_mix: ExerciseMix::default(),
+                game_mode: GameMode::default(),
+                difficulty: ExerciseDifficulty::Native,
+            },
+            ExerciseDifficulty::Custom => Self::default(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShuangElement {
+    pub hanzi_pair: HanziPair,
+    /// The final answer, after any retry
+    pub user_answer: String,
+    pub is_correct: bool,
+    /// The answer given the first time the pair was asked, when it took several attempts
+    pub first_answer: Option<String>,
+    /// How many times the pair was answered, the earlier session included for a review
+    pub attempts: u32,
+    /// How the pair was asked, which varies in a mixed session
+    pub exercise_type: ExerciseType,
+}
+
+impl ShuangElement {
+    /// Whether the first answer to the pair was incorrect, even if a retry was correct
+    pub fn was_missed(&self) -> bool {
+        !self.is_correct || self.first_answer.is_some()
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct HanziPair {
+    pub characters: String,
+    pub pinyin: String,
+    pub pronounced_pinyin: String,
+    pub tone_pair: (Tone, Tone),
+    pub pronounced_tone_pair: (Tone, Tone),
+    /// Why the recording differs from the dictionary, when a pronunciation override applies
+    pub pronunciation_override: Option<String>,
+    /// Other dictionary readings of the word, the fields above match the recording
+    pub alternative_readings: Vec<HanziReading>,
+    pub metadata: HanziMetadata,
+}
+
+/// What the corpus knows about a hanzi pair besides its readings, the text format has none of it
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct HanziMetadata {
+    pub traditional: Option<String>,
+    pub hsk_level: Option<u8>,
+    /// Reserved, the corpus pipeline does not produce it yet
+    pub frequency_rank: Option<u32>,
+    /// Glosses by language code, e.g. `en`
+    pub glosses: BTreeMap<String, String>,
+    /// Qualities the recording is available in, `None` when it is available in all of them.
+    /// Reserved, the corpus pipeline does not produce it yet
+    pub audio_qualities: Option<Vec<AudioQuality>>,
+}
+
+impl HanziMetadata {
+    /// Gloss in `language`, or in English when the corpus has none in that language
+    pub fn gloss(&self, language: &str) -> Option<&str> {
+        self.glosses
+            .get(language)
+            .or_else(|| self.glosses.get(FALLBACK_GLOSS_LANGUAGE))
+            .map(String::as_str)
+    }
+}
+
+/// A reading of a hanzi pair, with its tone sandhi applied
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct HanziReading {
+    pub pinyin: String,
+    pub pronounced_pinyin: String,
+    pub pronounced_tone_pair: (Tone, Tone),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExerciseSummary {
+    pub correct_answers: u32,
+    /// Pairs answered correctly at the first attempt
+    pub first_attempt_correct_answers: u32,
+    pub test_elements: Vec<ShuangElement>,
+    pub tone_pair_mistakes: HashMap<(Tone, Tone), u32>,
+    /// Correct answers and answers, per exercise type of the pairs
+    pub exercise_type_answers: HashMap<ExerciseType, (u32, u32)>,
+}
+
+impl From<Vec<ShuangElement>> for ExerciseSummary {
+    fn from(value: Vec<ShuangElement>) -> Self {
+        let mut tone_pair_mistakes: HashMap<(Tone, Tone), u32> = HashMap::new();
+        let mut exercise_type_answers: HashMap<ExerciseType, (u32, u32)> = HashMap::new();
+        for elem in value.iter() {
+            let answers = exercise_type_answers.entry(elem.exercise_type).or_default();
+            answers.0 += elem.is_correct as u32;
+            answers.1 += 1;
+            if !elem.is_correct {
+                *tone_pair_mistakes
+                    .entry(elem.hanzi_pair.pronounced_tone_pair)
+                    .or_insert(0) += 1;
+            }
+        }
+        Self {
+            correct_answers: value.iter().map(|x| x.is_correct as u32).sum(),
+            first_attempt_correct_answers: value.iter().map(|x| !x.was_missed() as u32).sum(),
+            test_elements: value,
+            tone_pair_mistakes,
+            exercise_type_answers,
+        }
+    }
+}
+
+impl ExerciseSummary {
+    /// Percentage of correct answers, 0 when nothing was answered
+    pub fn get_correct_percentage(&self) -> f32 {
+        if self.test_elements.is_empty() {
+            return 0.0;
+        }
+        (self.correct_answers as f32 / self.test_elements.len() as f32) * 100.0
+    }
+
+    /// Percentage of correct answers among the pairs asked as `exercise_type`, if any
+    pub fn get_exercise_type_percentage(&self, exercise_type: ExerciseType) -> Option<f32> {
+        self.exercise_type_answers
+            .get(&exercise_type)
+            .map(|&(correct_answers, answers)| (correct_answers as f32 / answers as f32) * 100.0)
+    }
+}
+
+pub struct UserData {
+    pub tone_pairs_correct_answers: HashMap<(Tone, Tone), u32>,
+    pub tone_pairs_incorrect_answers: HashMap<(Tone, Tone), u32>,
+}
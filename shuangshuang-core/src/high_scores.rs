@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::exercise::{ExerciseDifficulty, GameMode};
+
+/// Best scores of the survival and time attack sessions, as correct answers, per game mode and
+/// difficulty. The web app keeps them in local storage
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[serde(transparent)]
+pub struct HighScores {
+    /// Keyed like `survival_3/normal`, so that each mode limit has its own table
+    scores: BTreeMap<String, u32>,
+}
+
+impl HighScores {
+    fn key(game_mode: GameMode, difficulty: ExerciseDifficulty) -> String {
+        format!("{game_mode}/{difficulty}")
+    }
+
+    pub fn get(&self, game_mode: GameMode, difficulty: ExerciseDifficulty) -> Option<u32> {
+        self.scores.get(&Self::key(game_mode, difficulty)).copied()
+    }
+
+    /// Keeps `score` if it beats the high score of its game mode and difficulty, and tells
+    /// whether it did. Standard sessions have no high score
+    pub fn record(
+        &mut self,
+        game_mode: GameMode,
+        difficulty: ExerciseDifficulty,
+        score: u32,
+    ) -> bool {
+        if !game_mode.is_endless() || self.get(game_mode, difficulty) >= Some(score) {
+            return false;
+        }
+        self.scores.insert(Self::key(game_mode, difficulty), score);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn game_modes_are_parsed_back() {
+        for game_mode in [
+            GameMode::Standard,
+            GameMode::Survival { max_mistakes: 3 },
+            GameMode::TimeAttack {
+                time_limit_secs: 120,
+            },
+        ] {
+            assert_eq!(GameMode::from_str(&game_mode.to_string()), Ok(game_mode));
+        }
+        assert!(GameMode::from_str("survival_0").is_err());
+        assert!(GameMode::from_str("time_attack").is_err());
+    }
+
+    #[test]
+    fn only_better_scores_are_kept() {
+        let survival = GameMode::Survival { max_mistakes: 3 };
+        let mut high_scores = HighScores::default();
+        assert!(!high_scores.record(GameMode::Standard, ExerciseDifficulty::Easy, 10));
+        assert!(high_scores.record(survival, ExerciseDifficulty::Easy, 0));
+        assert!(high_scores.record(survival, ExerciseDifficulty::Easy, 12));
+        assert!(!high_scores.record(survival, ExerciseDifficulty::Easy, 12));
+        assert!(!high_scores.record(survival, ExerciseDifficulty::Easy, 8));
+        assert_eq!(
+            high_scores.get(survival, ExerciseDifficulty::Easy),
+            Some(12)
+        );
+        // Each difficulty and mode limit has its own high score
+        assert_eq!(high_scores.get(survival, ExerciseDifficulty::Hard), None);
+        assert_eq!(
+            high_scores.get(
+                GameMode::Survival { max_mistakes: 5 },
+                ExerciseDifficulty::Easy
+            ),
+            None
+        );
+        let json = serde_json::to_string(&high_scores).unwrap();
+        assert_eq!(json, r#"{"survival_3/easy":12}"#);
+        assert_eq!(
+            serde_json::from_str::<HighScores>(&json).unwrap(),
+            high_scores
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! Domain logic of ShuangShuang, free of any UI or browser dependency: tones and exercise
+//! types, the hanzi pairs corpus parser, tone sandhi, sampling, answer choices, minimal pairs, initial and final drills, grading and high scores.
+
+pub mod binary_corpus;
+pub mod choices;
+pub mod corpus;
+pub mod dictionary;
+pub mod exercise;
+pub mod high_scores;
+pub mod minimal_pairs;
+pub mod pinyin_drills;
+pub mod session;
+pub mod utils;
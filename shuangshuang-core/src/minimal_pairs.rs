@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, HashSet};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::corpus::HanziPairSource;
+use crate::exercise::{HanziPair, Tone};
+use crate::utils::{get_syllables_from_pinyin, WHILE_BREAK_LIMIT};
+
+/// The two words of a minimal pair question, in the order they are played
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MinimalPairAnswer {
+    First,
+    Second,
+}
+
+/// Hanzi pairs of the corpus sharing their syllables but not their tones, e.g. 事实 shi4shi2
+/// and 实施 shi2shi1
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct MinimalPairIndex {
+    /// Indexes of the corpus pairs, grouped by their pronounced pinyin without tones. Only the
+    /// groups with several tone pairs are kept
+    groups: BTreeMap<String, Vec<usize>>,
+    /// Every two pairs of a group with different pronounced tone pairs, in the order of the
+    /// groups
+    minimal_pairs: Vec<(usize, usize)>,
+}
+
+impl MinimalPairIndex {
+    pub fn new(corpus: &(impl HanziPairSource + ?Sized)) -> Self {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for idx in 0..corpus.len() {
+            let syllables = get_syllables_from_pinyin(&corpus.pronounced_pinyin(idx)).concat();
+            groups.entry(syllables).or_default().push(idx);
+        }
+        let mut minimal_pairs: Vec<(usize, usize)> = vec![];
+        groups.retain(|_, idxs| {
+            let nb_minimal_pairs = minimal_pairs.len();
+            for (i, &first_idx) in idxs.iter().enumerate() {
+                for &second_idx in idxs[i + 1..].iter() {
+                    if corpus.pronounced_tone_pair(first_idx)
+                        != corpus.pronounced_tone_pair(second_idx)
+                    {
+                        minimal_pairs.push((first_idx, second_idx));
+                    }
+                }
+            }
+            minimal_pairs.len() > nb_minimal_pairs
+        });
+        Self {
+            groups,
+            minimal_pairs,
+        }
+    }
+
+    /// Number of minimal pairs in the corpus
+    pub fn len(&self) -> usize {
+        self.minimal_pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.minimal_pairs.is_empty()
+    }
+
+    /// Corpus indexes of the pairs read like `syllables`, which have no tone numbers
+    pub fn get_group(&self, syllables: &str) -> Option<&[usize]> {
+        self.groups.get(syllables).map(Vec::as_slice)
+    }
+
+    pub fn minimal_pairs(&self) -> &[(usize, usize)] {
+        &self.minimal_pairs
+    }
+}
+
+/// Picks `nb_elements` different corpus pairs having a minimal pair, fewer when the corpus has
+/// fewer of them
+pub fn get_random_contrasted_idxs(nb_elements: u32, index: &MinimalPairIndex) -> Vec<usize> {
+    let mut idxs: Vec<usize> = index.groups.values().flatten().copied().collect();
+    idxs.shuffle(&mut rand::rng());
+    idxs.truncate(nb_elements as usize);
+    idxs
+}
+
+/// A question of the minimal pair exercise: which of the two played words has the asked tones
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct MinimalPairQuestion {
+    pub first: HanziPair,
+    pub second: HanziPair,
+    pub asked_tone_pair: (Tone, Tone),
+}
+
+impl MinimalPairQuestion {
+    pub fn expected_answer(&self) -> MinimalPairAnswer {
+        if self.first.pronounced_tone_pair == self.asked_tone_pair {
+            MinimalPairAnswer::First
+        } else {
+            MinimalPairAnswer::Second
+        }
+    }
+
+    /// The two tone pairs to tell apart, in the same order whichever word is played first
+    pub fn tone_contrast(&self) -> String {
+        get_tone_contrast(
+            self.first.pronounced_tone_pair,
+            self.second.pronounced_tone_pair,
+        )
+    }
+}
+
+/// Formats two tone pairs as a contrast like `24-34`, sorted so that the order they were played
+/// in does not matter
+pub fn get_tone_contrast(first: (Tone, Tone), second: (Tone, Tone)) -> String {
+    let mut tone_pairs = [
+        format!("{}{}", first.0, first.1),
+        format!("{}{}", second.0, second.1),
+    ];
+    tone_pairs.sort();
+    tone_pairs.join("-")
+}
+
+/// Picks `nb_questions` different minimal pairs, with a random order and asked word for each.
+/// There can be fewer questions when the corpus has fewer minimal pairs
+pub fn get_random_minimal_pair_questions(
+    nb_questions: u32,
+    index: &MinimalPairIndex,
+    corpus: &(impl HanziPairSource + ?Sized),
+) -> Vec<MinimalPairQuestion> {
+    let mut questions: Vec<MinimalPairQuestion> = vec![];
+    if index.is_empty() {
+        return questions;
+    }
+    let mut rng = rand::rng();
+    let mut used_idxs: HashSet<usize> = HashSet::new();
+    let mut break_counter = 0;
+    while questions.len() < nb_questions as usize && used_idxs.len() < index.len() {
+        let random_idx: usize = rng.random_range(0..index.len());
+        if !used_idxs.insert(random_idx) {
+            break_counter += 1;
+            if break_counter >= WHILE_BREAK_LIMIT {
+                break;
+            }
+            continue;
+        }
+        let (mut first_idx, mut second_idx) = index.minimal_pairs()[random_idx];
+        if rng.random_bool(0.5) {
+            (first_idx, second_idx) = (second_idx, first_idx);
+        }
+        let first = corpus.hanzi_pair(first_idx);
+        let second = corpus.hanzi_pair(second_idx);
+        let asked_tone_pair = if rng.random_bool(0.5) {
+            first.pronounced_tone_pair
+        } else {
+            second.pronounced_tone_pair
+        };
+        questions.push(MinimalPairQuestion {
+            first,
+            second,
+            asked_tone_pair,
+        });
+    }
+    questions
+}
+
+/// Correct answers out of the questions asked for a tone contrast
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ContrastScore {
+    pub correct_answers: u32,
+    pub total_answers: u32,
+}
+
+/// Scores of a minimal pair session, per tone contrast like `24-34`
+pub fn get_contrast_scores(
+    answers: &[(MinimalPairQuestion, MinimalPairAnswer)],
+) -> BTreeMap<String, ContrastScore> {
+    let mut scores: BTreeMap<String, ContrastScore> = BTreeMap::new();
+    for (question, answer) in answers.iter() {
+        let score = scores.entry(question.tone_contrast()).or_default();
+        score.total_answers += 1;
+        if question.expected_answer() == *answer {
+            score.correct_answers += 1;
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::parse_hanzi_pair;
+
+    fn get_corpus() -> Vec<HanziPair> {
+        [
+            "事实 shi4shi2 42",
+            "实施 shi2shi1 21",
+            "时事 shi2shi4 24",
+            "学校 xue2xiao4 24",
+            "买卖 mai3mai 35",
+            "麦卖 mai4mai4 44",
+            "老虎 lao3hu3 33",
+            "老狐 lao2hu2 22",
+        ]
+        .into_iter()
+        .map(|line| parse_hanzi_pair(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn index_finds_minimal_pairs_by_toneless_pinyin() {
+        let corpus = get_corpus();
+        let index = MinimalPairIndex::new(&corpus[..]);
+        assert_eq!(index.get_group("shishi"), Some(&[0, 1, 2][..]));
+        assert_eq!(index.get_group("xuexiao"), None);
+        // 老虎 is pronounced lao2hu3 after the third tone sandhi, unlike 老狐. Minimal pairs are
+        // sorted by their syllables
+        assert_eq!(
+            index.minimal_pairs(),
+            &[(6, 7), (4, 5), (0, 1), (0, 2), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn questions_are_minimal_pairs() {
+        let corpus = get_corpus();
+        let index = MinimalPairIndex::new(&corpus[..]);
+        let questions = get_random_minimal_pair_questions(10, &index, &corpus[..]);
+        // There are only 5 minimal pairs
+        assert_eq!(questions.len(), 5);
+        for question in questions.iter() {
+            assert_ne!(
+                question.first.pronounced_tone_pair,
+                question.second.pronounced_tone_pair
+            );
+            assert!([
+                question.first.pronounced_tone_pair,
+                question.second.pronounced_tone_pair
+            ]
+            .contains(&question.asked_tone_pair));
+        }
+        assert!(
+            get_random_minimal_pair_questions(3, &MinimalPairIndex::default(), &corpus[..])
+                .is_empty()
+        );
+        let mut idxs = get_random_contrasted_idxs(10, &index);
+        idxs.sort();
+        // 学校 has no minimal pair
+        assert_eq!(idxs, vec![0, 1, 2, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn scores_are_per_tone_contrast() {
+        let corpus = get_corpus();
+        let question = MinimalPairQuestion {
+            first: corpus[0].clone(),
+            second: corpus[2].clone(),
+            asked_tone_pair: corpus[2].pronounced_tone_pair,
+        };
+        let reversed_question = MinimalPairQuestion {
+            first: corpus[2].clone(),
+            second: corpus[0].clone(),
+            asked_tone_pair: corpus[2].pronounced_tone_pair,
+        };
+        assert_eq!(question.expected_answer(), MinimalPairAnswer::Second);
+        assert_eq!(question.tone_contrast(), "24-42");
+        assert_eq!(reversed_question.tone_contrast(), "24-42");
+        let scores = get_contrast_scores(&[
+            (question, MinimalPairAnswer::Second),
+            (reversed_question, MinimalPairAnswer::Second),
+        ]);
+        assert_eq!(
+            scores["24-42"],
+            ContrastScore {
+                correct_answers: 1,
+                total_answers: 2
+            }
+        );
+    }
+}
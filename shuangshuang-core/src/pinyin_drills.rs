@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::corpus::HanziPairSource;
+use crate::exercise::{ExerciseError, HanziPair};
+use crate::minimal_pairs::ContrastScore;
+use crate::utils::{get_syllables_from_pinyin, split_pinyin_syllable, WHILE_BREAK_LIMIT};
+
+/// Shown in place of the asked segment of a syllable
+const BLANK: &str = "_";
+
+/// Corpus pair and syllable indexes of the syllables with each segment of a contrast
+type SegmentCandidates = BTreeMap<&'static str, Vec<(usize, usize)>>;
+
+/// Initials and finals learners mix up, the drill only asks which side of a contrast a word
+/// syllable has
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum PinyinContrast {
+    ZhZ,
+    ChC,
+    ShS,
+    /// Finals ending in -n or -ng
+    NNg,
+    /// ü and u after n and l, the corpus writes ü as v
+    UmlautU,
+    JQX,
+}
+
+impl PinyinContrast {
+    pub const ALL: [Self; 6] = [
+        Self::ZhZ,
+        Self::ChC,
+        Self::ShS,
+        Self::NNg,
+        Self::UmlautU,
+        Self::JQX,
+    ];
+
+    /// The answers offered for the contrast
+    pub fn segments(&self) -> &'static [&'static str] {
+        match self {
+            Self::ZhZ => &["zh", "z"],
+            Self::ChC => &["ch", "c"],
+            Self::ShS => &["sh", "s"],
+            Self::NNg => &["n", "ng"],
+            Self::UmlautU => &["u", "ü"],
+            Self::JQX => &["j", "q", "x"],
+        }
+    }
+
+    /// The segments of the contrast joined like `zh/z`
+    pub fn label(&self) -> String {
+        self.segments().join("/")
+    }
+}
+
+impl fmt::Display for PinyinContrast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::ZhZ => "zh_z",
+            Self::ChC => "ch_c",
+            Self::ShS => "sh_s",
+            Self::NNg => "n_ng",
+            Self::UmlautU => "umlaut_u",
+            Self::JQX => "j_q_x",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl FromStr for PinyinContrast {
+    type Err = ExerciseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zh_z" => Ok(Self::ZhZ),
+            "ch_c" => Ok(Self::ChC),
+            "sh_s" => Ok(Self::ShS),
+            "n_ng" => Ok(Self::NNg),
+            "umlaut_u" => Ok(Self::UmlautU),
+            "j_q_x" => Ok(Self::JQX),
+            _ => Err(ExerciseError::ParsePinyinContrastError),
+        }
+    }
+}
+
+/// The segment of a toneless syllable taking part in `contrast`, as one of its `segments`, and
+/// the syllable with this segment blanked
+pub fn get_contrast_segment(
+    syllable: &str,
+    contrast: PinyinContrast,
+) -> Option<(&'static str, String)> {
+    let (initial, final_) = split_pinyin_syllable(syllable);
+    let segment = match contrast {
+        PinyinContrast::NNg => {
+            if final_.ends_with("ng") {
+                "ng"
+            } else if final_.ends_with('n') && final_.len() > 1 {
+                "n"
+            } else {
+                return None;
+            }
+        }
+        PinyinContrast::UmlautU => {
+            if initial != "n" && initial != "l" {
+                return None;
+            }
+            // Only a leading u of the final can be heard as ü, as in lu and lüe
+            if final_.starts_with('v') {
+                "ü"
+            } else if final_ == "u" {
+                "u"
+            } else {
+                return None;
+            }
+        }
+        _ => contrast
+            .segments()
+            .iter()
+            .find(|&&segment| segment == initial)
+            .copied()?,
+    };
+    let blanked_syllable = match contrast {
+        PinyinContrast::NNg => {
+            format!("{}{BLANK}", &syllable[..syllable.len() - segment.len()])
+        }
+        PinyinContrast::UmlautU => format!("{initial}{BLANK}{}", &final_[1..]),
+        _ => format!("{BLANK}{final_}"),
+    };
+    Some((segment, blanked_syllable))
+}
+
+/// A question of the initial and final drill: which segment of `contrast` a syllable of the
+/// played word has
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PinyinDrillQuestion {
+    pub hanzi_pair: HanziPair,
+    pub contrast: PinyinContrast,
+    pub syllable_idx: usize,
+    pub segment: &'static str,
+}
+
+impl PinyinDrillQuestion {
+    /// The toneless pinyin of the word with the asked segment blanked, e.g. `_ong guo`
+    pub fn blanked_pinyin(&self) -> String {
+        get_syllables_from_pinyin(&self.hanzi_pair.pronounced_pinyin)
+            .iter()
+            .enumerate()
+            .map(|(idx, syllable)| {
+                let syllable = if idx == self.syllable_idx {
+                    get_contrast_segment(syllable, self.contrast)
+                        .map_or(syllable.clone(), |(_, blanked_syllable)| blanked_syllable)
+                } else {
+                    syllable.clone()
+                };
+                syllable.replace('v', "ü")
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// Picks `nb_questions` questions on words of the corpus, each word at most once. With no given
+/// contrast, every contrast is asked in turn. Segments of a contrast are asked as often, whatever
+/// their frequency in the corpus. There can be fewer questions when the corpus has too few words
+pub fn get_random_drill_questions(
+    nb_questions: u32,
+    contrast: Option<PinyinContrast>,
+    corpus: &(impl HanziPairSource + ?Sized),
+) -> Vec<PinyinDrillQuestion> {
+    let contrasts: Vec<PinyinContrast> = match contrast {
+        Some(contrast) => vec![contrast],
+        None => PinyinContrast::ALL.to_vec(),
+    };
+    let mut candidates: BTreeMap<PinyinContrast, SegmentCandidates> = BTreeMap::new();
+    for idx in 0..corpus.len() {
+        let syllables = get_syllables_from_pinyin(&corpus.pronounced_pinyin(idx));
+        for (syllable_idx, syllable) in syllables.iter().enumerate() {
+            for &contrast in contrasts.iter() {
+                if let Some((segment, _)) = get_contrast_segment(syllable, contrast) {
+                    candidates
+                        .entry(contrast)
+                        .or_default()
+                        .entry(segment)
+                        .or_default()
+                        .push((idx, syllable_idx));
+                }
+            }
+        }
+    }
+
+    let mut rng = rand::rng();
+    let mut questions: Vec<PinyinDrillQuestion> = vec![];
+    let mut used_idxs: HashSet<usize> = HashSet::new();
+    let mut asked_contrasts: Vec<PinyinContrast> = candidates.keys().copied().collect();
+    asked_contrasts.shuffle(&mut rng);
+    let mut break_counter = 0;
+    while questions.len() < nb_questions as usize && !candidates.is_empty() {
+        let contrast = asked_contrasts[questions.len() % asked_contrasts.len()];
+        let Some(segments) = candidates.get_mut(&contrast) else {
+            // This contrast has no word left, the others are asked instead
+            asked_contrasts.retain(|&asked_contrast| asked_contrast != contrast);
+            continue;
+        };
+        let segment = *segments
+            .keys()
+            .nth(rng.random_range(0..segments.len()))
+            .expect("Segments of a contrast should not be empty");
+        let segment_candidates = segments
+            .get_mut(segment)
+            .expect("Segment should be a key of its contrast");
+        let (idx, syllable_idx) =
+            segment_candidates.swap_remove(rng.random_range(0..segment_candidates.len()));
+        if segment_candidates.is_empty() {
+            segments.remove(segment);
+            if segments.is_empty() {
+                candidates.remove(&contrast);
+            }
+        }
+        if !used_idxs.insert(idx) {
+            break_counter += 1;
+            if break_counter >= WHILE_BREAK_LIMIT {
+                break;
+            }
+            continue;
+        }
+        questions.push(PinyinDrillQuestion {
+            hanzi_pair: corpus.hanzi_pair(idx),
+            contrast,
+            syllable_idx,
+            segment,
+        });
+    }
+    questions
+}
+
+/// Scores of a drill session, per contrast label like `zh/z`
+pub fn get_drill_scores(
+    answers: &[(PinyinDrillQuestion, String)],
+) -> BTreeMap<String, ContrastScore> {
+    let mut scores: BTreeMap<String, ContrastScore> = BTreeMap::new();
+    for (question, answer) in answers.iter() {
+        let score = scores.entry(question.contrast.label()).or_default();
+        score.total_answers += 1;
+        if question.segment == answer {
+            score.correct_answers += 1;
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::parse_hanzi_pair;
+
+    fn get_corpus() -> Vec<HanziPair> {
+        [
+            "中国 zhong1guo2 12",
+            "总是 zong3shi4 34",
+            "所以 suo3yi3 33",
+            "女儿 nv3er2 32",
+            "努力 nu3li4 34",
+            "学校 xue2xiao4 24",
+        ]
+        .into_iter()
+        .map(|line| parse_hanzi_pair(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn contrast_segments_are_found_and_blanked() {
+        assert_eq!(
+            get_contrast_segment("zhong", PinyinContrast::ZhZ),
+            Some(("zh", "_ong".to_string()))
+        );
+        assert_eq!(get_contrast_segment("zhong", PinyinContrast::ShS), None);
+        assert_eq!(
+            get_contrast_segment("zhong", PinyinContrast::NNg),
+            Some(("ng", "zho_".to_string()))
+        );
+        assert_eq!(
+            get_contrast_segment("lve", PinyinContrast::UmlautU),
+            Some(("ü", "l_e".to_string()))
+        );
+        // After j, q and x, u is always read ü
+        assert_eq!(get_contrast_segment("ju", PinyinContrast::UmlautU), None);
+        assert_eq!(get_contrast_segment("n", PinyinContrast::NNg), None);
+        let question = PinyinDrillQuestion {
+            hanzi_pair: get_corpus()[3].clone(),
+            contrast: PinyinContrast::UmlautU,
+            syllable_idx: 0,
+            segment: "ü",
+        };
+        assert_eq!(question.blanked_pinyin(), "n_ er");
+    }
+
+    #[test]
+    fn questions_are_on_the_asked_contrast() {
+        let corpus = get_corpus();
+        let questions = get_random_drill_questions(10, Some(PinyinContrast::ZhZ), &corpus[..]);
+        // Only 中国 and 总是 have zh or z
+        assert_eq!(questions.len(), 2);
+        let mut segments: Vec<&str> = questions.iter().map(|question| question.segment).collect();
+        segments.sort();
+        assert_eq!(segments, vec!["z", "zh"]);
+        let questions = get_random_drill_questions(10, None, &corpus[..]);
+        // Every word has a contrast, and is asked once
+        assert_eq!(questions.len(), 6);
+        let mut characters: Vec<&str> = questions
+            .iter()
+            .map(|question| question.hanzi_pair.characters.as_str())
+            .collect();
+        characters.sort();
+        characters.dedup();
+        assert_eq!(characters.len(), 6);
+    }
+
+    #[test]
+    fn scores_are_per_contrast() {
+        let corpus = get_corpus();
+        let question =
+            |idx: usize, contrast: PinyinContrast, segment: &'static str| PinyinDrillQuestion {
+                hanzi_pair: corpus[idx].clone(),
+                contrast,
+                syllable_idx: 0,
+                segment,
+            };
+        let scores = get_drill_scores(&[
+            (question(0, PinyinContrast::ZhZ, "zh"), "zh".to_string()),
+            (question(1, PinyinContrast::ZhZ, "z"), "zh".to_string()),
+            (question(4, PinyinContrast::UmlautU, "u"), "u".to_string()),
+        ]);
+        assert_eq!(
+            scores["zh/z"],
+            ContrastScore {
+                correct_answers: 1,
+                total_answers: 2
+            }
+        );
+        assert_eq!(scores["u/ü"].correct_answers, 1);
+    }
+}
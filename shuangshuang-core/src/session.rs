@@ -0,0 +1,928 @@
+use crate::corpus::HanziPairSource;
+use crate::exercise::{
+    AudioQuality, ExerciseMix, ExerciseParams, ExerciseSummary, ExerciseType, GameMode, HanziPair,
+    ShuangElement,
+};
+use crate::minimal_pairs::{get_random_contrasted_idxs, MinimalPairIndex};
+use crate::utils::{
+    format_word_url, get_random_exercise_types, get_random_hanzi_pairs_idxs,
+    get_random_sandhi_idxs, get_shuffled_hanzi_pairs_idxs, get_tones_only_from_pronounced_pinyin,
+};
+
+/// Pairs drawn at once from the corpus by an endless session
+const ENDLESS_BATCH_SIZE: usize = 10;
+
+/// Step of an exercise session
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SessionState {
+    #[default]
+    Ready,
+    Playing,
+    Answered,
+    Finished,
+}
+
+/// What can happen during an exercise session
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SessionEvent {
+    Start,
+    ReplayAudio,
+    SubmitAnswer(String),
+    Next,
+    /// The time of a time attack session ran out, the current pair is not counted
+    TimeUp,
+}
+
+/// What the view has to do after an event was handled
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SessionEffect {
+    /// Play the current word audio, loading it first if `new_audio` is true
+    PlayAudio {
+        url: String,
+        new_audio: bool,
+    },
+    ResetTimer,
+}
+
+/// Returns the answer expected for a hanzi pair, as typed by the user
+pub fn get_expected_answer(hanzi_pair: &HanziPair, exercise_type: ExerciseType) -> String {
+    let pronounced_pinyin = &hanzi_pair.pronounced_pinyin;
+    match exercise_type {
+        ExerciseType::ToneOnly
+        | ExerciseType::Production
+        | ExerciseType::MinimalPair
+        | ExerciseType::Sandhi => get_tones_only_from_pronounced_pinyin(pronounced_pinyin),
+        ExerciseType::NoTonePinyin | ExerciseType::PinyinDrill => {
+            pronounced_pinyin.replace(char::is_numeric, "")
+        }
+        // The recordings of the reverse exercise are told apart by their pinyin, and the pairs
+        // of a mixed session are each given another type by the session
+        ExerciseType::Pinyin | ExerciseType::ReverseAudio | ExerciseType::Mixed => {
+            pronounced_pinyin.clone()
+        }
+        ExerciseType::Hanzi => hanzi_pair.characters.clone(),
+    }
+}
+
+/// Whether `answer` is correct for a hanzi pair. Only the reading of the recording is expected,
+/// unless `accept_alternative_readings` is set
+pub fn is_correct_answer(
+    hanzi_pair: &HanziPair,
+    exercise_type: ExerciseType,
+    accept_alternative_readings: bool,
+    answer: &str,
+) -> bool {
+    if answer == get_expected_answer(hanzi_pair, exercise_type) {
+        return true;
+    }
+    accept_alternative_readings
+        && hanzi_pair.alternative_readings.iter().any(|reading| {
+            let hanzi_pair = HanziPair {
+                pronounced_pinyin: reading.pronounced_pinyin.clone(),
+                ..hanzi_pair.clone()
+            };
+            answer == get_expected_answer(&hanzi_pair, exercise_type)
+        })
+}
+
+/// Characters of the other corpus pairs read like `hanzi_pair` once pronounced, e.g. 时事 for
+/// 实事 shi2shi4
+pub fn get_homophones(
+    hanzi_pair: &HanziPair,
+    corpus: &(impl HanziPairSource + ?Sized),
+) -> Vec<String> {
+    (0..corpus.len())
+        .filter(|&idx| corpus.pronounced_pinyin(idx) == hanzi_pair.pronounced_pinyin)
+        .map(|idx| corpus.hanzi_pair(idx).characters)
+        .filter(|characters| *characters != hanzi_pair.characters)
+        .collect()
+}
+
+/// State machine of an exercise session, independent from the view. Events that make no sense
+/// in the current state are ignored
+#[derive(Clone, PartialEq, Debug)]
+pub struct SessionEngine {
+    hanzi_pairs: Vec<HanziPair>,
+    exercise_type: ExerciseType,
+    audio_quality: AudioQuality,
+    audio_retries: u32,
+    timer_on: bool,
+    accept_alternative_readings: bool,
+    exact_hanzi_match: bool,
+    max_attempts: u32,
+    state: SessionState,
+    /// Indexes in `hanzi_pairs` of the pairs to ask, missed pairs are pushed again at the end
+    queue: Vec<usize>,
+    current_idx: usize,
+    remaining_listenings: u32,
+    /// One per pair of `hanzi_pairs` already asked, in the same order
+    shuang_elements: Vec<ShuangElement>,
+    /// Answers of an earlier session, when reviewing its mistakes
+    earlier_elements: Vec<Option<ShuangElement>>,
+    /// Other correct answers of the hanzi exercise, one list per pair of `hanzi_pairs`
+    homophones: Vec<Vec<String>>,
+    /// How each pair of `hanzi_pairs` is asked, all as `exercise_type` unless it is mixed
+    item_types: Vec<ExerciseType>,
+    exercise_mix: ExerciseMix,
+    game_mode: GameMode,
+    /// Incorrect answers given in this session
+    mistakes: u32,
+    /// Corpus indexes of the pairs an endless session can still draw, the next one last
+    pool: Vec<usize>,
+}
+
+impl SessionEngine {
+    /// Creates a session asking `hanzi_pairs` in order
+    pub fn new(hanzi_pairs: Vec<HanziPair>, exercise_params: &ExerciseParams) -> Self {
+        Self {
+            queue: (0..hanzi_pairs.len()).collect(),
+            earlier_elements: vec![None; hanzi_pairs.len()],
+            homophones: vec![vec![]; hanzi_pairs.len()],
+            item_types: match exercise_params.exercise_type {
+                ExerciseType::Mixed => {
+                    get_random_exercise_types(hanzi_pairs.len(), exercise_params.exercise_mix)
+                }
+                exercise_type => vec![exercise_type; hanzi_pairs.len()],
+            },
+            hanzi_pairs,
+            exercise_type: exercise_params.exercise_type,
+            exercise_mix: exercise_params.exercise_mix,
+            game_mode: exercise_params.game_mode,
+            mistakes: 0,
+            pool: vec![],
+            audio_quality: exercise_params.audio_quality,
+            audio_retries: exercise_params.audio_retries,
+            timer_on: exercise_params.timer_on,
+            accept_alternative_readings: exercise_params.accept_alternative_readings,
+            exact_hanzi_match: exercise_params.exact_hanzi_match,
+            max_attempts: exercise_params.max_attempts.max(1),
+            state: SessionState::Ready,
+            current_idx: 0,
+            remaining_listenings: exercise_params.audio_retries,
+            shuang_elements: vec![],
+        }
+    }
+
+    /// Creates a session asking again the pairs incorrectly answered in `shuang_elements`, as
+    /// they were asked. Its summary keeps their first answer and counts their attempts
+    pub fn from_mistakes(
+        shuang_elements: &[ShuangElement],
+        exercise_params: &ExerciseParams,
+    ) -> Self {
+        let mistakes: Vec<&ShuangElement> = shuang_elements
+            .iter()
+            .filter(|elem| !elem.is_correct)
+            .collect();
+        let mut engine = Self::new(
+            mistakes
+                .iter()
+                .map(|elem| elem.hanzi_pair.clone())
+                .collect(),
+            exercise_params,
+        );
+        engine.item_types = mistakes.iter().map(|elem| elem.exercise_type).collect();
+        engine.earlier_elements = mistakes.into_iter().cloned().map(Some).collect();
+        // Reviewing ends with the mistakes, however the earlier session ended
+        engine.game_mode = GameMode::Standard;
+        engine
+    }
+
+    /// Creates a session asking pairs picked from the corpus according to `exercise_params`. An
+    /// endless session only draws its first pairs, see `refill`
+    pub fn from_corpus(
+        corpus: &(impl HanziPairSource + ?Sized),
+        exercise_params: &ExerciseParams,
+    ) -> Self {
+        let endless = exercise_params.game_mode.is_endless();
+        let nb_elements = if endless {
+            corpus.len() as u32
+        } else {
+            exercise_params.exercise_size
+        };
+        // The reverse exercise needs pairs with recordings to confuse them with
+        let idxs = match exercise_params.exercise_type {
+            ExerciseType::ReverseAudio => {
+                get_random_contrasted_idxs(nb_elements, &MinimalPairIndex::new(corpus))
+            }
+            ExerciseType::Sandhi => get_random_sandhi_idxs(nb_elements, corpus),
+            _ if endless => get_shuffled_hanzi_pairs_idxs(corpus),
+            _ => get_random_hanzi_pairs_idxs(
+                exercise_params.exercise_size,
+                corpus,
+                exercise_params.shuffle_mode,
+            ),
+        };
+        if endless {
+            let mut engine = Self::new(vec![], exercise_params);
+            engine.pool = idxs.into_iter().rev().collect();
+            engine.refill(corpus);
+            return engine;
+        }
+        let hanzi_pairs = idxs.into_iter().map(|idx| corpus.hanzi_pair(idx)).collect();
+        Self::new(hanzi_pairs, exercise_params).with_homophones(corpus)
+    }
+
+    /// Accepts the homophones found in `corpus` as answers of the hanzi exercise, unless only
+    /// exact matches are. Other exercises are unchanged
+    pub fn with_homophones(mut self, corpus: &(impl HanziPairSource + ?Sized)) -> Self {
+        if self.exercise_type == ExerciseType::Hanzi && !self.exact_hanzi_match {
+            self.homophones = self
+                .hanzi_pairs
+                .iter()
+                .map(|hanzi_pair| get_homophones(hanzi_pair, corpus))
+                .collect();
+        }
+        self
+    }
+
+    /// Draws the next pairs of an endless session from `corpus` when it is about to run out of
+    /// them, to be called before moving on. The corpus pairs are each asked once, the session
+    /// finishes once they all were
+    pub fn refill(&mut self, corpus: &(impl HanziPairSource + ?Sized)) {
+        if self.remaining_pairs() > 1 || self.pool.is_empty() {
+            return;
+        }
+        let nb_pairs = self.pool.len().min(ENDLESS_BATCH_SIZE);
+        let idxs = self.pool.split_off(self.pool.len() - nb_pairs);
+        let item_types = match self.exercise_type {
+            ExerciseType::Mixed => get_random_exercise_types(nb_pairs, self.exercise_mix),
+            exercise_type => vec![exercise_type; nb_pairs],
+        };
+        let with_homophones = self.exercise_type == ExerciseType::Hanzi && !self.exact_hanzi_match;
+        for (idx, item_type) in idxs.into_iter().rev().zip(item_types) {
+            let hanzi_pair = corpus.hanzi_pair(idx);
+            self.homophones.push(if with_homophones {
+                get_homophones(&hanzi_pair, corpus)
+            } else {
+                vec![]
+            });
+            self.queue.push(self.hanzi_pairs.len());
+            self.hanzi_pairs.push(hanzi_pair);
+            self.earlier_elements.push(None);
+            self.item_types.push(item_type);
+        }
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    pub fn exercise_type(&self) -> ExerciseType {
+        self.exercise_type
+    }
+
+    /// How the current pair is asked, the session exercise type unless it is mixed
+    pub fn current_exercise_type(&self) -> ExerciseType {
+        self.queue
+            .get(self.current_idx)
+            .and_then(|&pair_idx| self.item_types.get(pair_idx))
+            .copied()
+            .unwrap_or(self.exercise_type)
+    }
+
+    pub fn current_hanzi_pair(&self) -> Option<&HanziPair> {
+        match self.state {
+            SessionState::Playing | SessionState::Answered => self
+                .queue
+                .get(self.current_idx)
+                .and_then(|&pair_idx| self.hanzi_pairs.get(pair_idx)),
+            _ => None,
+        }
+    }
+
+    /// The recording of the current pair, the reverse exercise plays the recordings of its
+    /// choices instead, and the sandhi quiz only plays it once answered
+    pub fn current_audio_url(&self) -> Option<String> {
+        match (self.exercise_type, self.state) {
+            (ExerciseType::ReverseAudio, _) | (ExerciseType::Sandhi, SessionState::Playing) => {
+                return None
+            }
+            _ => (),
+        }
+        self.current_hanzi_pair()
+            .map(|hanzi_pair| format_word_url(&hanzi_pair.characters, self.audio_quality))
+    }
+
+    /// Number of pairs left to answer, the current one included. It grows when a missed pair
+    /// is asked again
+    pub fn remaining_pairs(&self) -> usize {
+        match self.state {
+            SessionState::Ready => self.queue.len(),
+            SessionState::Playing => self.queue.len() - self.current_idx,
+            // An empty session finishes without asking anything
+            SessionState::Answered | SessionState::Finished => {
+                self.queue.len().saturating_sub(self.current_idx + 1)
+            }
+        }
+    }
+
+    pub fn game_mode(&self) -> GameMode {
+        self.game_mode
+    }
+
+    /// Incorrect answers a survival session still allows, the session ends at the next one
+    /// when it is 1
+    pub fn remaining_mistakes(&self) -> Option<u32> {
+        match self.game_mode {
+            GameMode::Survival { max_mistakes } => Some(max_mistakes.saturating_sub(self.mistakes)),
+            _ => None,
+        }
+    }
+
+    pub fn remaining_listenings(&self) -> u32 {
+        self.remaining_listenings
+    }
+
+    /// The last answered pair, graded
+    pub fn last_answer(&self) -> Option<&ShuangElement> {
+        match self.state {
+            SessionState::Answered | SessionState::Finished => self
+                .queue
+                .get(self.current_idx)
+                .and_then(|&pair_idx| self.shuang_elements.get(pair_idx)),
+            _ => None,
+        }
+    }
+
+    pub fn get_summary(&self) -> ExerciseSummary {
+        ExerciseSummary::from(self.shuang_elements.clone())
+    }
+
+    pub fn handle(&mut self, event: SessionEvent) -> Vec<SessionEffect> {
+        match (self.state, event) {
+            (SessionState::Ready, SessionEvent::Start) => self.play_pair(0),
+            (SessionState::Playing, SessionEvent::ReplayAudio) => {
+                if self.remaining_listenings == 0 {
+                    return vec![];
+                }
+                self.remaining_listenings -= 1;
+                self.current_audio_url()
+                    .map(|url| SessionEffect::PlayAudio {
+                        url,
+                        new_audio: false,
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            (SessionState::Playing, SessionEvent::SubmitAnswer(answer)) => {
+                self.submit_answer(answer);
+                self.state = SessionState::Answered;
+                // The recording confirms the answer of the sandhi quiz
+                match self.exercise_type {
+                    ExerciseType::Sandhi => self
+                        .current_audio_url()
+                        .map(|url| SessionEffect::PlayAudio {
+                            url,
+                            new_audio: true,
+                        })
+                        .into_iter()
+                        .collect(),
+                    _ => vec![],
+                }
+            }
+            (SessionState::Answered, SessionEvent::Next) => self.play_pair(self.current_idx + 1),
+            (SessionState::Playing | SessionState::Answered, SessionEvent::TimeUp) => {
+                self.state = SessionState::Finished;
+                vec![]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn submit_answer(&mut self, answer: String) {
+        let pair_idx = self.queue[self.current_idx];
+        let hanzi_pair = &self.hanzi_pairs[pair_idx];
+        let exercise_type = self.item_types[pair_idx];
+        let is_correct = is_correct_answer(
+            hanzi_pair,
+            exercise_type,
+            self.accept_alternative_readings,
+            &answer,
+        ) || self.homophones[pair_idx].contains(&answer);
+        // Pairs are first asked in order, so a pair without an element is asked for the first
+        // time in this session
+        let (first_answer, session_attempts) = match self.shuang_elements.get(pair_idx) {
+            Some(elem) => (
+                elem.first_answer
+                    .clone()
+                    .or_else(|| Some(elem.user_answer.clone())),
+                elem.attempts - self.earlier_attempts(pair_idx) + 1,
+            ),
+            None => (
+                self.earlier_elements[pair_idx]
+                    .as_ref()
+                    .map(|elem| elem.first_answer.as_ref().unwrap_or(&elem.user_answer))
+                    .cloned(),
+                1,
+            ),
+        };
+        let elem = ShuangElement {
+            hanzi_pair: hanzi_pair.clone(),
+            user_answer: answer,
+            is_correct,
+            first_answer,
+            attempts: self.earlier_attempts(pair_idx) + session_attempts,
+            exercise_type,
+        };
+        if !is_correct {
+            self.mistakes += 1;
+            if session_attempts < self.max_attempts {
+                self.queue.push(pair_idx);
+            }
+        }
+        if pair_idx < self.shuang_elements.len() {
+            self.shuang_elements[pair_idx] = elem;
+        } else {
+            self.shuang_elements.push(elem);
+        }
+    }
+
+    fn earlier_attempts(&self, pair_idx: usize) -> u32 {
+        self.earlier_elements[pair_idx]
+            .as_ref()
+            .map_or(0, |elem| elem.attempts)
+    }
+
+    fn play_pair(&mut self, idx: usize) -> Vec<SessionEffect> {
+        if idx >= self.queue.len() || self.remaining_mistakes() == Some(0) {
+            self.state = SessionState::Finished;
+            return vec![];
+        }
+        self.current_idx = idx;
+        self.remaining_listenings = self.audio_retries;
+        self.state = SessionState::Playing;
+        let mut effects = vec![];
+        if let Some(url) = self.current_audio_url() {
+            effects.push(SessionEffect::PlayAudio {
+                url,
+                new_audio: true,
+            });
+        }
+        if self.timer_on {
+            effects.push(SessionEffect::ResetTimer);
+        }
+        effects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise::{ExerciseMix, HanziMetadata, HanziReading, Tone};
+
+    fn get_hanzi_pair(characters: &str, pronounced_pinyin: &str, tones: (Tone, Tone)) -> HanziPair {
+        HanziPair {
+            characters: characters.to_string(),
+            pinyin: pronounced_pinyin.to_string(),
+            pronounced_pinyin: pronounced_pinyin.to_string(),
+            tone_pair: tones,
+            pronounced_tone_pair: tones,
+            pronunciation_override: None,
+            alternative_readings: vec![],
+            metadata: HanziMetadata::default(),
+        }
+    }
+
+    fn get_engine(exercise_params: ExerciseParams) -> SessionEngine {
+        SessionEngine::new(
+            vec![
+                get_hanzi_pair("学校", "xue2xiao4", (Tone::Tone2, Tone::Tone4)),
+                get_hanzi_pair("我们", "wo3men", (Tone::Tone3, Tone::NeutralTone)),
+            ],
+            &exercise_params,
+        )
+    }
+
+    #[test]
+    fn session_goes_through_all_states() {
+        let mut engine = get_engine(ExerciseParams::default());
+        assert_eq!(engine.state(), SessionState::Ready);
+        assert_eq!(engine.current_hanzi_pair(), None);
+        // Answers are ignored until the session starts
+        assert!(engine
+            .handle(SessionEvent::SubmitAnswer("24".to_string()))
+            .is_empty());
+        assert!(engine.get_summary().test_elements.is_empty());
+
+        let effects = engine.handle(SessionEvent::Start);
+        assert_eq!(engine.state(), SessionState::Playing);
+        assert_eq!(
+            effects,
+            vec![SessionEffect::PlayAudio {
+                url: engine.current_audio_url().unwrap(),
+                new_audio: true
+            }]
+        );
+        assert_eq!(engine.remaining_pairs(), 2);
+
+        engine.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        assert_eq!(engine.state(), SessionState::Answered);
+        assert!(engine.get_summary().test_elements[0].is_correct);
+        assert_eq!(engine.last_answer().unwrap().hanzi_pair.characters, "学校");
+        // A second answer to the same pair is ignored
+        engine.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        assert_eq!(engine.get_summary().test_elements.len(), 1);
+
+        engine.handle(SessionEvent::Next);
+        assert_eq!(engine.state(), SessionState::Playing);
+        assert_eq!(engine.current_hanzi_pair().unwrap().characters, "我们");
+        engine.handle(SessionEvent::SubmitAnswer("33".to_string()));
+        assert!(!engine.get_summary().test_elements[1].is_correct);
+
+        assert!(engine.handle(SessionEvent::Next).is_empty());
+        assert_eq!(engine.state(), SessionState::Finished);
+        assert_eq!(engine.current_hanzi_pair(), None);
+        assert_eq!(engine.remaining_pairs(), 0);
+        assert_eq!(engine.get_summary().correct_answers, 1);
+    }
+
+    #[test]
+    fn missed_pairs_are_asked_again_until_correct() {
+        let mut engine = get_engine(ExerciseParams {
+            max_attempts: 3,
+            ..Default::default()
+        });
+        engine.handle(SessionEvent::Start);
+        engine.handle(SessionEvent::SubmitAnswer("22".to_string()));
+        // The missed pair is asked again after the other one
+        assert_eq!(engine.remaining_pairs(), 2);
+        engine.handle(SessionEvent::Next);
+        assert_eq!(engine.current_hanzi_pair().unwrap().characters, "我们");
+        engine.handle(SessionEvent::SubmitAnswer("35".to_string()));
+        engine.handle(SessionEvent::Next);
+        assert_eq!(engine.current_hanzi_pair().unwrap().characters, "学校");
+        engine.handle(SessionEvent::SubmitAnswer("23".to_string()));
+        engine.handle(SessionEvent::Next);
+        engine.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        assert_eq!(engine.last_answer().unwrap().attempts, 3);
+        assert!(engine.handle(SessionEvent::Next).is_empty());
+        assert_eq!(engine.state(), SessionState::Finished);
+
+        let summary = engine.get_summary();
+        assert_eq!(summary.test_elements.len(), 2);
+        assert_eq!(summary.correct_answers, 2);
+        assert_eq!(summary.first_attempt_correct_answers, 1);
+        assert_eq!(summary.test_elements[0].first_answer.as_deref(), Some("22"));
+        assert_eq!(summary.test_elements[0].user_answer, "24");
+        assert_eq!(summary.test_elements[1].first_answer, None);
+    }
+
+    #[test]
+    fn attempts_are_limited() {
+        let mut engine = get_engine(ExerciseParams {
+            max_attempts: 2,
+            ..Default::default()
+        });
+        engine.handle(SessionEvent::Start);
+        for answer in ["11", "35", "11"] {
+            engine.handle(SessionEvent::SubmitAnswer(answer.to_string()));
+            engine.handle(SessionEvent::Next);
+        }
+        assert_eq!(engine.state(), SessionState::Finished);
+        let summary = engine.get_summary();
+        assert!(!summary.test_elements[0].is_correct);
+        assert_eq!(summary.test_elements[0].attempts, 2);
+    }
+
+    #[test]
+    fn mistakes_can_be_reviewed() {
+        let mut engine = get_engine(ExerciseParams::default());
+        engine.handle(SessionEvent::Start);
+        for answer in ["22", "35"] {
+            engine.handle(SessionEvent::SubmitAnswer(answer.to_string()));
+            engine.handle(SessionEvent::Next);
+        }
+        let mut review = SessionEngine::from_mistakes(
+            &engine.get_summary().test_elements,
+            &ExerciseParams::default(),
+        );
+        assert_eq!(review.remaining_pairs(), 1);
+        review.handle(SessionEvent::Start);
+        assert_eq!(review.current_hanzi_pair().unwrap().characters, "学校");
+        review.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        review.handle(SessionEvent::Next);
+        assert_eq!(review.state(), SessionState::Finished);
+        let elem = &review.get_summary().test_elements[0];
+        assert!(elem.is_correct);
+        assert_eq!(elem.first_answer.as_deref(), Some("22"));
+        assert_eq!(elem.attempts, 2);
+    }
+
+    #[test]
+    fn reverse_audio_sessions_play_no_audio() {
+        let mut engine = get_engine(ExerciseParams {
+            exercise_type: ExerciseType::ReverseAudio,
+            ..Default::default()
+        });
+        assert!(engine.handle(SessionEvent::Start).is_empty());
+        assert_eq!(engine.current_audio_url(), None);
+        assert!(engine.handle(SessionEvent::ReplayAudio).is_empty());
+        engine.handle(SessionEvent::SubmitAnswer("xue2xiao4".to_string()));
+        assert!(engine.last_answer().unwrap().is_correct);
+    }
+
+    #[test]
+    fn hanzi_sessions_accept_homophones_unless_exact() {
+        let corpus = vec![
+            get_hanzi_pair("实事", "shi2shi4", (Tone::Tone2, Tone::Tone4)),
+            get_hanzi_pair("时事", "shi2shi4", (Tone::Tone2, Tone::Tone4)),
+            get_hanzi_pair("事实", "shi4shi2", (Tone::Tone4, Tone::Tone2)),
+        ];
+        assert_eq!(get_homophones(&corpus[0], &corpus), vec!["时事"]);
+        for (exact_hanzi_match, homophone_is_correct) in [(false, true), (true, false)] {
+            let exercise_params = ExerciseParams {
+                exercise_type: ExerciseType::Hanzi,
+                exact_hanzi_match,
+                ..Default::default()
+            };
+            let mut engine =
+                SessionEngine::new(vec![corpus[0].clone(), corpus[0].clone()], &exercise_params)
+                    .with_homophones(&corpus);
+            engine.handle(SessionEvent::Start);
+            engine.handle(SessionEvent::SubmitAnswer("实事".to_string()));
+            assert!(engine.last_answer().unwrap().is_correct);
+            engine.handle(SessionEvent::Next);
+            engine.handle(SessionEvent::SubmitAnswer("时事".to_string()));
+            assert_eq!(
+                engine.last_answer().unwrap().is_correct,
+                homophone_is_correct
+            );
+        }
+    }
+
+    #[test]
+    fn sandhi_quiz_plays_audio_once_answered() {
+        let mut engine = get_engine(ExerciseParams {
+            exercise_type: ExerciseType::Sandhi,
+            ..Default::default()
+        });
+        assert!(engine.handle(SessionEvent::Start).is_empty());
+        assert_eq!(engine.current_audio_url(), None);
+        assert!(engine.handle(SessionEvent::ReplayAudio).is_empty());
+        let effects = engine.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        assert!(engine.last_answer().unwrap().is_correct);
+        assert_eq!(
+            effects,
+            vec![SessionEffect::PlayAudio {
+                url: format_word_url("学校", AudioQuality::default()),
+                new_audio: true,
+            }]
+        );
+        // The next pair is silent again
+        assert!(engine.handle(SessionEvent::Next).is_empty());
+    }
+
+    #[test]
+    fn mixed_sessions_grade_each_pair_as_asked() {
+        let exercise_params = ExerciseParams {
+            exercise_type: ExerciseType::Mixed,
+            exercise_mix: ExerciseMix {
+                tone_only: 1,
+                no_tone_pinyin: 0,
+                pinyin: 1,
+            },
+            max_attempts: 2,
+            ..Default::default()
+        };
+        let mut engine = get_engine(exercise_params);
+        engine.handle(SessionEvent::Start);
+        let mut asked_types = vec![];
+        while engine.state() == SessionState::Playing {
+            let exercise_type = engine.current_exercise_type();
+            asked_types.push(exercise_type);
+            let hanzi_pair = engine.current_hanzi_pair().unwrap().clone();
+            // The first pair is missed, and asked again the same way
+            let answer = if asked_types.len() == 1 {
+                "?".to_string()
+            } else {
+                get_expected_answer(&hanzi_pair, exercise_type)
+            };
+            engine.handle(SessionEvent::SubmitAnswer(answer));
+            engine.handle(SessionEvent::Next);
+        }
+        assert_eq!(asked_types.len(), 3);
+        assert_eq!(asked_types[0], asked_types[2]);
+        assert_ne!(asked_types[0], asked_types[1]);
+        let summary = engine.get_summary();
+        assert_eq!(summary.correct_answers, 2);
+        assert_eq!(
+            summary.get_exercise_type_percentage(asked_types[1]),
+            Some(100.0)
+        );
+        assert_eq!(
+            summary.get_exercise_type_percentage(ExerciseType::NoTonePinyin),
+            None
+        );
+        let element_types: Vec<ExerciseType> = summary
+            .test_elements
+            .iter()
+            .map(|elem| elem.exercise_type)
+            .collect();
+        assert_eq!(element_types, vec![asked_types[0], asked_types[1]]);
+    }
+
+    fn get_endless_corpus(nb_pairs: usize) -> Vec<HanziPair> {
+        (0..nb_pairs)
+            .map(|idx| get_hanzi_pair(&idx.to_string(), "xue2xiao4", (Tone::Tone2, Tone::Tone4)))
+            .collect()
+    }
+
+    /// Answers the current pair then moves on, drawing pairs like the web app
+    fn answer_and_move_on(
+        engine: &mut SessionEngine,
+        corpus: &[HanziPair],
+        answer: &str,
+    ) -> String {
+        let characters = engine.current_hanzi_pair().unwrap().characters.clone();
+        engine.handle(SessionEvent::SubmitAnswer(answer.to_string()));
+        engine.refill(corpus);
+        engine.handle(SessionEvent::Next);
+        characters
+    }
+
+    #[test]
+    fn survival_sessions_go_on_until_the_last_mistake() {
+        let corpus = get_endless_corpus(30);
+        let mut engine = SessionEngine::from_corpus(
+            &corpus[..],
+            &ExerciseParams {
+                game_mode: GameMode::Survival { max_mistakes: 2 },
+                ..Default::default()
+            },
+        );
+        engine.handle(SessionEvent::Start);
+        let mut asked: Vec<String> = (0..15)
+            .map(|_| answer_and_move_on(&mut engine, &corpus, "24"))
+            .collect();
+        asked.push(answer_and_move_on(&mut engine, &corpus, "11"));
+        assert_eq!(engine.remaining_mistakes(), Some(1));
+        assert_eq!(engine.state(), SessionState::Playing);
+        asked.push(answer_and_move_on(&mut engine, &corpus, "11"));
+        assert_eq!(engine.state(), SessionState::Finished);
+        assert_eq!(engine.remaining_mistakes(), Some(0));
+        // More pairs than a batch were drawn, none of them twice
+        asked.sort();
+        asked.dedup();
+        assert_eq!(asked.len(), 17);
+        assert_eq!(engine.get_summary().correct_answers, 15);
+    }
+
+    #[test]
+    fn endless_sessions_end_with_the_corpus_or_the_time() {
+        let corpus = get_endless_corpus(3);
+        let mut engine = SessionEngine::from_corpus(
+            &corpus[..],
+            &ExerciseParams {
+                game_mode: GameMode::Survival { max_mistakes: 1 },
+                ..Default::default()
+            },
+        );
+        engine.handle(SessionEvent::Start);
+        for _ in 0..3 {
+            answer_and_move_on(&mut engine, &corpus, "24");
+        }
+        assert_eq!(engine.state(), SessionState::Finished);
+        assert_eq!(engine.get_summary().correct_answers, 3);
+
+        let mut engine = SessionEngine::from_corpus(
+            &corpus[..],
+            &ExerciseParams {
+                game_mode: GameMode::TimeAttack {
+                    time_limit_secs: 60,
+                },
+                ..Default::default()
+            },
+        );
+        engine.handle(SessionEvent::Start);
+        answer_and_move_on(&mut engine, &corpus, "24");
+        engine.handle(SessionEvent::TimeUp);
+        assert_eq!(engine.state(), SessionState::Finished);
+        // The pair asked when the time ran out is not counted
+        assert_eq!(engine.get_summary().test_elements.len(), 1);
+        let review = SessionEngine::from_mistakes(
+            &engine.get_summary().test_elements,
+            &ExerciseParams::default(),
+        );
+        assert_eq!(review.game_mode(), GameMode::Standard);
+    }
+
+    #[test]
+    fn empty_session_finishes_on_start() {
+        let mut engine = SessionEngine::new(vec![], &ExerciseParams::default());
+        assert!(engine.handle(SessionEvent::Start).is_empty());
+        assert_eq!(engine.state(), SessionState::Finished);
+        assert_eq!(engine.remaining_pairs(), 0);
+    }
+
+    #[test]
+    fn empty_session_scores_zero() {
+        let mut engine = SessionEngine::new(vec![], &ExerciseParams::default());
+        engine.handle(SessionEvent::Start);
+        assert_eq!(engine.get_summary().get_correct_percentage(), 0.0);
+    }
+
+    #[test]
+    fn replays_are_limited_per_pair() {
+        let mut engine = get_engine(ExerciseParams {
+            audio_retries: 1,
+            ..Default::default()
+        });
+        engine.handle(SessionEvent::Start);
+        let effects = engine.handle(SessionEvent::ReplayAudio);
+        assert!(matches!(
+            effects[..],
+            [SessionEffect::PlayAudio {
+                new_audio: false,
+                ..
+            }]
+        ));
+        assert_eq!(engine.remaining_listenings(), 0);
+        assert!(engine.handle(SessionEvent::ReplayAudio).is_empty());
+        engine.handle(SessionEvent::SubmitAnswer("24".to_string()));
+        engine.handle(SessionEvent::Next);
+        assert_eq!(engine.remaining_listenings(), 1);
+    }
+
+    #[test]
+    fn timer_is_reset_for_each_pair_when_on() {
+        let mut engine = get_engine(ExerciseParams {
+            timer_on: true,
+            ..Default::default()
+        });
+        assert!(engine
+            .handle(SessionEvent::Start)
+            .contains(&SessionEffect::ResetTimer));
+        engine.handle(SessionEvent::SubmitAnswer("".to_string()));
+        assert!(engine
+            .handle(SessionEvent::Next)
+            .contains(&SessionEffect::ResetTimer));
+    }
+
+    #[test]
+    fn alternative_readings_are_optional_answers() {
+        let mut hanzi_pair = get_hanzi_pair("地道", "di4dao", (Tone::Tone4, Tone::NeutralTone));
+        hanzi_pair.alternative_readings = vec![HanziReading {
+            pinyin: "di4dao4".to_string(),
+            pronounced_pinyin: "di4dao4".to_string(),
+            pronounced_tone_pair: (Tone::Tone4, Tone::Tone4),
+        }];
+        assert!(is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::ToneOnly,
+            false,
+            "45"
+        ));
+        assert!(!is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::ToneOnly,
+            false,
+            "44"
+        ));
+        assert!(is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::ToneOnly,
+            true,
+            "44"
+        ));
+        assert!(is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::Pinyin,
+            true,
+            "di4dao4"
+        ));
+        assert!(!is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::Pinyin,
+            true,
+            "di4dao2"
+        ));
+        // Other readings are read from the same characters
+        assert!(!is_correct_answer(
+            &hanzi_pair,
+            ExerciseType::Hanzi,
+            true,
+            ""
+        ));
+    }
+
+    #[test]
+    fn get_expected_answer_works() {
+        let hanzi_pair = get_hanzi_pair("我们", "wo3men", (Tone::Tone3, Tone::NeutralTone));
+        assert_eq!(
+            get_expected_answer(&hanzi_pair, ExerciseType::ToneOnly),
+            "35"
+        );
+        assert_eq!(
+            get_expected_answer(&hanzi_pair, ExerciseType::NoTonePinyin),
+            "women"
+        );
+        assert_eq!(
+            get_expected_answer(&hanzi_pair, ExerciseType::Pinyin),
+            "wo3men"
+        );
+        assert_eq!(
+            get_expected_answer(&hanzi_pair, ExerciseType::Hanzi),
+            "我们"
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+// use leptos::leptos_dom::logging::console_log;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use thiserror::Error;
+
+use crate::corpus::HanziPairSource;
+use crate::exercise::{
+    AudioQuality, ExerciseMix, ExerciseType, NoiseType, SandhiRule, ShuffleMode, Tone,
+};
+
+/// Draws of an already used index a random picking loop allows before giving up
+pub(crate) const WHILE_BREAK_LIMIT: u32 = 150;
+/// Share of the sandhi quiz pairs read differently from their dictionary pinyin, when the
+/// corpus has enough of them
+const SANDHI_PAIRS_RATIO: f64 = 0.7;
+/// Pinyin initials, the two letter ones first. The y and w spellings of syllables without an
+/// initial are kept as initials
+const PINYIN_INITIALS: [&str; 23] = [
+    "zh", "ch", "sh", "b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "j", "q", "x", "r",
+    "z", "c", "s", "y", "w",
+];
+
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum TextHandlingError {
+    #[error("The text handled is not exactly two hanzi long.")]
+    WordIsNotTwoHanziLong,
+    #[error("The tone numbers obtained from pinyin were not correct.")]
+    PinyinToneNumbersUnmatch,
+    #[error("Unkown error")]
+    Unknown,
+}
+
+pub fn format_word_url(chinese_word: &str, audio_quality: AudioQuality) -> String {
+    format!(
+        "https://github.com/hugolpz/audio-cmn/raw/refs/heads/master/{audio_quality}/hsk/cmn-{chinese_word}.mp3"
+    )
+}
+
+pub fn format_toned_syllable_url(syllable: &str, tone: &str) -> String {
+    format!(
+        "https://github.com/hugolpz/audio-cmn/raw/refs/heads/master/64k/syllabs/cmn-{syllable}{tone}.mp3"
+    )
+}
+
+// Ambience loops are bundled with the app, see `public/noise/`
+pub fn format_noise_loop_url(noise_type: NoiseType) -> Option<String> {
+    match noise_type {
+        NoiseType::CafeLoop => Some("/noise/cafe.wav".to_string()),
+        NoiseType::StreetLoop => Some("/noise/street.wav".to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_random_hanzi_pairs_idxs(
+    nb_elements: u32,
+    hanzi_pairs: &(impl HanziPairSource + ?Sized),
+    shuffle_mode: ShuffleMode,
+) -> Vec<usize> {
+    let mut idxs: Vec<usize> = vec![];
+    let mut used_idxs: HashSet<usize> = HashSet::new();
+    let mut rng = rand::rng();
+    let mut break_counter = 0;
+    match shuffle_mode {
+        ShuffleMode::Random => {
+            for _ in 0..nb_elements {
+                let mut random_idx: usize = rng.random_range(0..hanzi_pairs.len());
+                while used_idxs.contains(&random_idx) {
+                    random_idx = rng.random_range(0..hanzi_pairs.len());
+                    break_counter += 1;
+                    if break_counter >= WHILE_BREAK_LIMIT {
+                        break;
+                    }
+                }
+                idxs.push(random_idx);
+                used_idxs.insert(random_idx);
+            }
+        }
+        ShuffleMode::Even => {
+            let mut tone_pairs_map: HashMap<(Tone, Tone), Vec<usize>> = HashMap::new();
+            for i in 0..hanzi_pairs.len() {
+                let tone_pair_key = hanzi_pairs.pronounced_tone_pair(i);
+                tone_pairs_map.entry(tone_pair_key).or_default().push(i);
+            }
+            let tone_pairs_keys: Vec<&(Tone, Tone)> = tone_pairs_map.keys().collect();
+            for _ in 0..nb_elements {
+                let random_tone_pair_key_idx: usize = rng.random_range(0..tone_pairs_keys.len());
+                let random_tone_pair_key = tone_pairs_keys[random_tone_pair_key_idx];
+                let mut random_idx: usize =
+                    rng.random_range(0..tone_pairs_map[random_tone_pair_key].len());
+                let mut hanzi_pair_idx: usize = tone_pairs_map[random_tone_pair_key][random_idx];
+                while used_idxs.contains(&hanzi_pair_idx) {
+                    random_idx = rng.random_range(0..tone_pairs_map[random_tone_pair_key].len());
+                    hanzi_pair_idx = tone_pairs_map[random_tone_pair_key][random_idx];
+                    break_counter += 1;
+                    if break_counter >= WHILE_BREAK_LIMIT {
+                        break;
+                    }
+                }
+                idxs.push(hanzi_pair_idx);
+                used_idxs.insert(hanzi_pair_idx);
+            }
+        }
+    }
+    idxs
+}
+
+/// Every pair of the corpus in a random order, for the sessions asking pairs until they end
+pub fn get_shuffled_hanzi_pairs_idxs(hanzi_pairs: &(impl HanziPairSource + ?Sized)) -> Vec<usize> {
+    let mut idxs: Vec<usize> = (0..hanzi_pairs.len()).collect();
+    idxs.shuffle(&mut rand::rng());
+    idxs
+}
+
+/// Picks `nb_elements` different pairs for the sandhi quiz, most of them pronounced with other
+/// tones than their dictionary pinyin so that the rules are practiced, the others so that they
+/// are not applied everywhere
+pub fn get_random_sandhi_idxs(
+    nb_elements: u32,
+    hanzi_pairs: &(impl HanziPairSource + ?Sized),
+) -> Vec<usize> {
+    let (mut sandhi_idxs, mut other_idxs): (Vec<usize>, Vec<usize>) = (0..hanzi_pairs.len())
+        .partition(|&idx| hanzi_pairs.pinyin(idx) != hanzi_pairs.pronounced_pinyin(idx));
+    let mut rng = rand::rng();
+    sandhi_idxs.shuffle(&mut rng);
+    other_idxs.shuffle(&mut rng);
+    let mut idxs: Vec<usize> = vec![];
+    for _ in 0..nb_elements {
+        let idx = if rng.random_bool(SANDHI_PAIRS_RATIO) {
+            sandhi_idxs.pop().or_else(|| other_idxs.pop())
+        } else {
+            other_idxs.pop().or_else(|| sandhi_idxs.pop())
+        };
+        match idx {
+            Some(idx) => idxs.push(idx),
+            None => break,
+        }
+    }
+    idxs
+}
+
+/// Gives an exercise type to each of `nb_elements` pairs of a mixed session, as close to the
+/// proportions of `exercise_mix` as the number of pairs allows, in a random order. Types are
+/// evenly mixed when every proportion is 0
+pub fn get_random_exercise_types(
+    nb_elements: usize,
+    exercise_mix: ExerciseMix,
+) -> Vec<ExerciseType> {
+    let mut weights = exercise_mix.weights();
+    if weights.iter().all(|&(_, weight)| weight == 0) {
+        weights = ExerciseMix::default().weights();
+    }
+    let total_weight: u32 = weights.iter().map(|&(_, weight)| weight).sum();
+    // Largest remainder method, the remainders being out of `total_weight`
+    let mut counts: Vec<(ExerciseType, usize, u32)> = weights
+        .iter()
+        .map(|&(exercise_type, weight)| {
+            let share = nb_elements as u32 * weight;
+            (
+                exercise_type,
+                (share / total_weight) as usize,
+                share % total_weight,
+            )
+        })
+        .collect();
+    let nb_counted: usize = counts.iter().map(|&(_, count, _)| count).sum();
+    counts.sort_by_key(|&(_, _, remainder)| std::cmp::Reverse(remainder));
+    for count in counts.iter_mut().take(nb_elements - nb_counted) {
+        count.1 += 1;
+    }
+    let mut exercise_types: Vec<ExerciseType> = counts
+        .into_iter()
+        .flat_map(|(exercise_type, count, _)| std::iter::repeat_n(exercise_type, count))
+        .collect();
+    exercise_types.shuffle(&mut rand::rng());
+    exercise_types
+}
+
+#[allow(dead_code)]
+pub fn get_length_of_chinese_string(text: &str) -> usize {
+    let mut length = 0;
+    for idx in 0..text.len() {
+        if text.is_char_boundary(idx) {
+            length += 1;
+        }
+    }
+    length
+}
+
+pub fn get_tones_only_from_pronounced_pinyin(pinyin: &str) -> String {
+    let mut pinyin_tone_numbers = pinyin.replace(char::is_alphabetic, "");
+    // // Add Neutral tone marker if needed
+    if pinyin_tone_numbers.len() == 1 {
+        pinyin_tone_numbers.push_str(&Tone::NeutralTone.to_string());
+    }
+    pinyin_tone_numbers.to_string()
+}
+
+pub fn get_pronounced_pinyin(word: &str, pinyin: &str, tone_pair: &(Tone, Tone)) -> String {
+    let mut pronounced_pinyin = String::from(pinyin);
+    // Bu becomes second tone with fourth tone rule
+    if bu_is_first_hanzi(word) {
+        if let Tone::Tone4 = tone_pair.1 {
+            pronounced_pinyin =
+                pinyin.replacen(&Tone::Tone4.to_string(), &Tone::Tone2.to_string(), 1);
+        }
+    }
+    if yi_is_first_hanzi(word) {
+        // Yi becomes second tone with fourth tone rule
+        if let Tone::Tone4 = tone_pair.1 {
+            pronounced_pinyin = pinyin.replace(&Tone::Tone1.to_string(), &Tone::Tone2.to_string());
+        } else {
+            // Yi becomes fourth tone with other tones rule
+            // NOTE: Pairs where Yi1 acts as an ordinal number keep their first tone, they need an
+            // entry in data/pronunciation_overrides.txt
+            pronounced_pinyin =
+                pinyin.replacen(&Tone::Tone1.to_string(), &Tone::Tone4.to_string(), 1);
+        }
+    }
+    // Double 3 tone change rule
+    if tone_pair.0 == Tone::Tone3 && tone_pair.1 == Tone::Tone3 {
+        pronounced_pinyin = pinyin.replacen(&Tone::Tone3.to_string(), &Tone::Tone2.to_string(), 1);
+    }
+    pronounced_pinyin
+}
+
+/// The tone sandhi rule `get_pronounced_pinyin` applies to a pair, if it changes its pinyin
+pub fn get_sandhi_rule(word: &str, pinyin: &str, tone_pair: &(Tone, Tone)) -> Option<SandhiRule> {
+    if get_pronounced_pinyin(word, pinyin, tone_pair) == pinyin {
+        return None;
+    }
+    if *tone_pair == (Tone::Tone3, Tone::Tone3) {
+        Some(SandhiRule::ThirdTone)
+    } else if bu_is_first_hanzi(word) {
+        Some(SandhiRule::BuBeforeFourthTone)
+    } else if yi_is_first_hanzi(word) && tone_pair.1 == Tone::Tone4 {
+        Some(SandhiRule::YiBeforeFourthTone)
+    } else if yi_is_first_hanzi(word) {
+        Some(SandhiRule::YiBeforeOtherTones)
+    } else {
+        None
+    }
+}
+
+pub fn bu_is_first_hanzi(word: &str) -> bool {
+    word.starts_with("不")
+}
+
+pub fn yi_is_first_hanzi(word: &str) -> bool {
+    word.starts_with("一")
+}
+
+/// Splits a pinyin with tone numbers into its syllables, without their tone numbers
+pub fn get_syllables_from_pinyin(pinyin_with_nums: &str) -> Vec<String> {
+    pinyin_with_nums
+        .split(char::is_numeric)
+        .filter(|syllable| !syllable.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits a toneless pinyin syllable into its initial and final, e.g. `zhong` into `zh` and
+/// `ong`. The initial is empty for syllables like `ai`
+pub fn split_pinyin_syllable(syllable: &str) -> (&str, &str) {
+    PINYIN_INITIALS
+        .iter()
+        .find(|initial| syllable.len() > initial.len() && syllable.starts_with(*initial))
+        .map_or(("", syllable), |initial| syllable.split_at(initial.len()))
+}
+
+pub fn get_tones_from_pinyin(pinyin_with_nums: &str) -> Vec<Tone> {
+    let mut tones: Vec<Tone> = vec![];
+    let pinyin_tone_numbers = pinyin_with_nums.replace(char::is_alphabetic, "");
+    for tone_number in pinyin_tone_numbers.chars() {
+        tones.push(
+            Tone::from_str(&tone_number.to_string())
+                .unwrap_or_else(|_| panic!("Failed to build Tone from tone number: {tone_number}")),
+        );
+    }
+    // Add a neutral tone if needed
+    if tones.len() == 1 {
+        tones.push(Tone::NeutralTone);
+    }
+    tones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::parse_hanzi_pair;
+    use crate::exercise::HanziPair;
+    use pinyin::ToPinyin;
+
+    pub fn get_pinyin_from_chinese_word(word: &str) -> Option<String> {
+        let mut pinyin_output = String::new();
+        for pinyin in word.to_pinyin().flatten() {
+            pinyin_output.push_str(pinyin.with_tone_num_end());
+        }
+        if !pinyin_output.is_empty() {
+            Some(pinyin_output)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn sandhi_pairs_are_over_represented() {
+        let hanzi_pairs: Vec<HanziPair> = [
+            "你好 ni3hao3 33",
+            "不要 bu4yao4 44",
+            "学校 xue2xiao4 24",
+            "时候 shi2hou 25",
+            "事实 shi4shi2 42",
+        ]
+        .into_iter()
+        .map(|line| parse_hanzi_pair(line).unwrap())
+        .collect();
+        let mut idxs = get_random_sandhi_idxs(10, &hanzi_pairs);
+        idxs.sort();
+        assert_eq!(idxs, vec![0, 1, 2, 3, 4]);
+        let mut nb_sandhi_pairs = 0;
+        for _ in 0..50 {
+            let idxs = get_random_sandhi_idxs(2, &hanzi_pairs);
+            nb_sandhi_pairs += idxs.iter().filter(|&&idx| idx < 2).count();
+        }
+        // 40% of the corpus, 70% of the picks on average
+        assert!(
+            nb_sandhi_pairs > 50,
+            "{nb_sandhi_pairs} sandhi pairs picked"
+        );
+    }
+
+    #[test]
+    fn get_tones_from_pinyin_works() {
+        let pinyin_with_nums = "ni3hao3";
+        let expected_tones = vec![Tone::Tone3, Tone::Tone3];
+        let result_tones = get_tones_from_pinyin(pinyin_with_nums);
+        assert_eq!(expected_tones, result_tones);
+    }
+
+    #[test]
+    fn get_syllables_from_pinyin_works() {
+        assert_eq!(get_syllables_from_pinyin("xue2xiao4"), vec!["xue", "xiao"]);
+        assert_eq!(get_syllables_from_pinyin("wo3men"), vec!["wo", "men"]);
+    }
+
+    #[test]
+    fn exercise_types_follow_the_mix() {
+        let count = |exercise_types: &[ExerciseType], exercise_type: ExerciseType| {
+            exercise_types
+                .iter()
+                .filter(|&&asked_type| asked_type == exercise_type)
+                .count()
+        };
+        let exercise_mix = ExerciseMix {
+            tone_only: 2,
+            no_tone_pinyin: 0,
+            pinyin: 1,
+        };
+        let exercise_types = get_random_exercise_types(10, exercise_mix);
+        assert_eq!(exercise_types.len(), 10);
+        assert_eq!(count(&exercise_types, ExerciseType::ToneOnly), 7);
+        assert_eq!(count(&exercise_types, ExerciseType::NoTonePinyin), 0);
+        assert_eq!(count(&exercise_types, ExerciseType::Pinyin), 3);
+        let exercise_mix = ExerciseMix {
+            tone_only: 0,
+            no_tone_pinyin: 0,
+            pinyin: 0,
+        };
+        let exercise_types = get_random_exercise_types(3, exercise_mix);
+        assert_eq!(count(&exercise_types, ExerciseType::NoTonePinyin), 1);
+    }
+
+    #[test]
+    fn split_pinyin_syllable_works() {
+        assert_eq!(split_pinyin_syllable("zhong"), ("zh", "ong"));
+        assert_eq!(split_pinyin_syllable("zong"), ("z", "ong"));
+        assert_eq!(split_pinyin_syllable("lv"), ("l", "v"));
+        assert_eq!(split_pinyin_syllable("ai"), ("", "ai"));
+        assert_eq!(split_pinyin_syllable("er"), ("", "er"));
+        // A nasal alone is a final
+        assert_eq!(split_pinyin_syllable("n"), ("", "n"));
+    }
+
+    #[test]
+    fn get_sandhi_rule_works() {
+        let rule = |word: &str, pinyin: &str| {
+            let tones = get_tones_from_pinyin(pinyin);
+            get_sandhi_rule(word, pinyin, &(tones[0], tones[1]))
+        };
+        assert_eq!(rule("你好", "ni3hao3"), Some(SandhiRule::ThirdTone));
+        assert_eq!(
+            rule("不要", "bu4yao4"),
+            Some(SandhiRule::BuBeforeFourthTone)
+        );
+        assert_eq!(
+            rule("一会", "yi1hui4"),
+            Some(SandhiRule::YiBeforeFourthTone)
+        );
+        assert_eq!(
+            rule("一点", "yi1dian3"),
+            Some(SandhiRule::YiBeforeOtherTones)
+        );
+        assert_eq!(rule("不同", "bu4tong2"), None);
+        assert_eq!(rule("学校", "xue2xiao4"), None);
+    }
+
+    #[test]
+    fn test_no_tone_change_works() {
+        let chinese_word = "严肃";
+        let expected_pinyin = "yan2su4";
+        let tone_pair = (Tone::Tone2, Tone::Tone4);
+        let normal_pinyin = get_pinyin_from_chinese_word(chinese_word);
+        let result_pinyin = get_pronounced_pinyin(
+            chinese_word,
+            &normal_pinyin.expect("No pinyin detected"),
+            &tone_pair,
+        );
+        assert_eq!(expected_pinyin, &result_pinyin);
+    }
+
+    #[test]
+    fn test_third_tone_change_works() {
+        let chinese_word = "你好";
+        let expected_pinyin = "ni2hao3";
+        let tone_pair = (Tone::Tone3, Tone::Tone3);
+        let normal_pinyin = get_pinyin_from_chinese_word(chinese_word);
+        let result_pinyin = get_pronounced_pinyin(
+            chinese_word,
+            &normal_pinyin.expect("No pinyin detected"),
+            &tone_pair,
+        );
+        assert_eq!(expected_pinyin, &result_pinyin);
+    }
+
+    #[test]
+    fn test_yi_1_2_tone_change_works() {
+        let chinese_word = "一会";
+        let expected_pinyin = "yi2hui4";
+        let tone_pair = (Tone::Tone1, Tone::Tone4);
+        let normal_pinyin = get_pinyin_from_chinese_word(chinese_word);
+        let result_pinyin = get_pronounced_pinyin(
+            chinese_word,
+            &normal_pinyin.expect("No pinyin detected"),
+            &tone_pair,
+        );
+        assert_eq!(expected_pinyin, &result_pinyin);
+    }
+
+    #[test]
+    fn test_yi_1_4_tone_change_works() {
+        let chinese_word = "一点";
+        let expected_pinyin = "yi4dian3";
+        let tone_pair = (Tone::Tone1, Tone::Tone3);
+        let normal_pinyin = get_pinyin_from_chinese_word(chinese_word);
+        let result_pinyin = get_pronounced_pinyin(
+            chinese_word,
+            &normal_pinyin.expect("No pinyin detected"),
+            &tone_pair,
+        );
+        assert_eq!(expected_pinyin, &result_pinyin);
+    }
+
+    #[test]
+    fn test_bu_4_2_tone_change_works() {
+        let chinese_word = "不要";
+        let expected_pinyin = "bu2yao4";
+        let tone_pair = (Tone::Tone4, Tone::Tone4);
+        let normal_pinyin = get_pinyin_from_chinese_word(chinese_word);
+        let result_pinyin = get_pronounced_pinyin(
+            chinese_word,
+            &normal_pinyin.expect("No pinyin detected"),
+            &tone_pair,
+        );
+        assert_eq!(expected_pinyin, &result_pinyin);
+    }
+}
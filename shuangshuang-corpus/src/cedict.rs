@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// An entry of CC-CEDICT, its pinyin is as written in the dictionary, e.g. `xue2 xiao4`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CedictEntry {
+    pub traditional: String,
+    pub pinyin: String,
+    pub definitions: Vec<String>,
+}
+
+/// Maps every simplified word of a CC-CEDICT file to its entries, in order
+pub fn parse_cedict(text: &str) -> HashMap<String, Vec<CedictEntry>> {
+    let mut entries_by_word: HashMap<String, Vec<CedictEntry>> = HashMap::new();
+    for line in text.lines().filter(|line| !line.starts_with('#')) {
+        // Lines look like `學校 学校 [xue2 xiao4] /school/`
+        let mut splits = line.splitn(3, ' ');
+        let (Some(traditional), Some(simplified), Some(rest)) =
+            (splits.next(), splits.next(), splits.next())
+        else {
+            continue;
+        };
+        let Some((pinyin, definitions)) =
+            rest.strip_prefix('[').and_then(|rest| rest.split_once(']'))
+        else {
+            continue;
+        };
+        entries_by_word
+            .entry(simplified.to_string())
+            .or_default()
+            .push(CedictEntry {
+                traditional: traditional.to_string(),
+                pinyin: pinyin.to_string(),
+                definitions: definitions
+                    .split('/')
+                    .map(str::trim)
+                    .filter(|definition| !definition.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            });
+    }
+    entries_by_word
+}
+
+/// Converts a CC-CEDICT pinyin to the pinyin and tones fields of `hanzi_pairs.txt`, where
+/// neutral tones have no number in the pinyin
+pub fn get_corpus_pinyin_and_tones(cedict_pinyin: &str) -> (String, String) {
+    let pinyin = cedict_pinyin
+        .to_lowercase()
+        .replace(' ', "")
+        .replace("u:", "v");
+    let mut tones = pinyin.replace(|c: char| c.is_ascii_alphabetic(), "");
+    // Adding possibly missing neutral tone
+    if tones.len() == 1 {
+        tones.push('5');
+    }
+    (pinyin.replace('5', ""), tones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cedict_keeps_every_entry() {
+        let cedict = "# CC-CEDICT\n\
+            學校 学校 [xue2 xiao4] /school/\n\
+            把手 把手 [ba3 shou3] /handle/\n\
+            把手 把手 [ba3 shou5] /another reading/\n";
+        let entries_by_word = parse_cedict(cedict);
+        assert_eq!(
+            entries_by_word["学校"],
+            vec![CedictEntry {
+                traditional: "學校".to_string(),
+                pinyin: "xue2 xiao4".to_string(),
+                definitions: vec!["school".to_string()],
+            }]
+        );
+        let pinyins: Vec<&str> = entries_by_word["把手"]
+            .iter()
+            .map(|entry| entry.pinyin.as_str())
+            .collect();
+        assert_eq!(pinyins, vec!["ba3 shou3", "ba3 shou5"]);
+        assert_eq!(entries_by_word.len(), 2);
+    }
+
+    #[test]
+    fn get_corpus_pinyin_and_tones_works() {
+        assert_eq!(
+            get_corpus_pinyin_and_tones("wo3 men5"),
+            ("wo3men".to_string(), "35".to_string())
+        );
+        assert_eq!(
+            get_corpus_pinyin_and_tones("Lu:3 xing2"),
+            ("lv3xing2".to_string(), "32".to_string())
+        );
+    }
+}
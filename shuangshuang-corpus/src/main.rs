@@ -0,0 +1,322 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use shuangshuang_core::binary_corpus::BinaryCorpus;
+use shuangshuang_core::corpus::{
+    apply_pronunciation_overrides, format_corpus_jsonl, parse_corpus,
+    parse_pronunciation_overrides, HanziRecord, PronunciationOverride,
+};
+use shuangshuang_core::exercise::HanziPair;
+use thiserror::Error;
+
+mod cedict;
+mod pipeline;
+
+use crate::pipeline::{add_glosses, add_hsk_levels, build_corpus, format_stats, BuildError};
+
+#[derive(Error, Debug)]
+pub enum CorpusToolError {
+    #[error("Failed to access {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("{0} error(s), nothing was written.")]
+    Build(usize),
+    #[error("{0} has {1} invalid line(s).")]
+    InvalidCorpus(PathBuf, usize),
+    #[error("{0} has {1} invalid or unused line(s).")]
+    InvalidPronunciationOverrides(PathBuf, usize),
+    #[error("{0} would not load the same hanzi pairs: {1}")]
+    InvalidBinaryCorpus(PathBuf, String),
+}
+
+/// Builds and validates the hanzi pairs corpus of the app
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Builds the corpus from the HSK list, CC-CEDICT and the overrides, then writes its stats
+    Build {
+        /// Path of a local CC-CEDICT file (cedict_ts.u8)
+        #[arg(long)]
+        cedict: PathBuf,
+        /// Path of the HSK word list
+        #[arg(long, default_value = "data/HSK2012_all.txt")]
+        hsk: PathBuf,
+        /// Path of the list of words without a recording
+        #[arg(long, default_value = "data/in_HSK2012_all_missing-audios.o.txt")]
+        missing_audio: PathBuf,
+        /// Path of the manual corrections, applied over CC-CEDICT
+        #[arg(long, default_value = "data/hanzi_pairs_overrides.txt")]
+        overrides: PathBuf,
+        /// Path of the generated corpus, in the structured format
+        #[arg(long, default_value = "data/hanzi_pairs.jsonl")]
+        output: PathBuf,
+        /// Path of the generated corpus, in the older text format without metadata
+        #[arg(long, default_value = "data/hanzi_pairs.txt")]
+        text_output: PathBuf,
+        /// Path of the generated corpus, in the binary format loaded by the app
+        #[arg(long, default_value = "data/hanzi_pairs.bin")]
+        binary_output: PathBuf,
+        /// Path of the generated stats
+        #[arg(long, default_value = "data/hanzi_pairs_stats.txt")]
+        stats: PathBuf,
+        /// Glosses in another language, from a dictionary in the CC-CEDICT format, e.g.
+        /// `fr=cfdict.u8`
+        #[arg(long, value_parser = parse_glosses_arg)]
+        glosses: Vec<(String, PathBuf)>,
+        /// Path of the pronunciations of the recordings, checked against the generated corpus
+        #[arg(long, default_value = "data/pronunciation_overrides.txt")]
+        pronunciation_overrides: PathBuf,
+    },
+    /// Converts a corpus in the text format to the structured and binary formats, with the HSK
+    /// levels
+    Convert {
+        /// Path of the corpus in the text format
+        #[arg(long, default_value = "data/hanzi_pairs.txt")]
+        corpus: PathBuf,
+        /// Path of the HSK word list
+        #[arg(long, default_value = "data/HSK2012_all.txt")]
+        hsk: PathBuf,
+        /// Path of the converted corpus
+        #[arg(long, default_value = "data/hanzi_pairs.jsonl")]
+        output: PathBuf,
+        /// Glosses, from a dictionary in the CC-CEDICT format, e.g. `en=cedict_ts.u8`
+        #[arg(long, value_parser = parse_glosses_arg)]
+        glosses: Vec<(String, PathBuf)>,
+        /// Path of the converted corpus, in the binary format loaded by the app
+        #[arg(long, default_value = "data/hanzi_pairs.bin")]
+        binary_output: PathBuf,
+        /// Path of the pronunciations of the recordings, applied to the binary corpus
+        #[arg(long, default_value = "data/pronunciation_overrides.txt")]
+        pronunciation_overrides: PathBuf,
+    },
+    /// Checks that the app accepts every line of a corpus, in either format
+    Check {
+        /// Path of the corpus
+        #[arg(long, default_value = "data/hanzi_pairs.jsonl")]
+        corpus: PathBuf,
+        /// Path of the pronunciations of the recordings, checked against the corpus
+        #[arg(long, default_value = "data/pronunciation_overrides.txt")]
+        pronunciation_overrides: PathBuf,
+        /// Also regenerates the stats of the corpus at this path
+        #[arg(long)]
+        write_stats: Option<PathBuf>,
+    },
+}
+
+fn parse_glosses_arg(arg: &str) -> Result<(String, PathBuf), String> {
+    let (language, path) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected <language>=<path>, got `{arg}`"))?;
+    Ok((language.to_string(), PathBuf::from(path)))
+}
+
+// Missing glosses are only reported, the app shows none for these words
+fn write_glosses(
+    records: &mut [HanziRecord],
+    glosses: &[(String, PathBuf)],
+) -> Result<(), CorpusToolError> {
+    for (language, path) in glosses {
+        let missing_words = add_glosses(records, language, &read(path)?);
+        if !missing_words.is_empty() {
+            eprintln!(
+                "{} hanzi pairs have no gloss in {}: {}",
+                missing_words.len(),
+                path.display(),
+                missing_words.join(" ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<String, CorpusToolError> {
+    std::fs::read_to_string(path).map_err(|e| CorpusToolError::Io(path.to_path_buf(), e))
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), CorpusToolError> {
+    std::fs::write(path, contents).map_err(|e| CorpusToolError::Io(path.to_path_buf(), e))
+}
+
+fn check_corpus(path: &Path) -> Result<Vec<HanziPair>, CorpusToolError> {
+    check_corpus_text(path, &read(path)?)
+}
+
+// Every invalid line is reported before failing, `path` is where the text is or will be written
+fn check_corpus_text(path: &Path, text: &str) -> Result<Vec<HanziPair>, CorpusToolError> {
+    let mut hanzi_pairs: Vec<HanziPair> = vec![];
+    let mut invalid_lines = 0;
+    for (line_number, hanzi_pair) in parse_corpus(text) {
+        match hanzi_pair {
+            Ok(hanzi_pair) => hanzi_pairs.push(hanzi_pair),
+            Err(e) => {
+                eprintln!("{} line {line_number}: {e}", path.display());
+                invalid_lines += 1;
+            }
+        }
+    }
+    if invalid_lines > 0 {
+        return Err(CorpusToolError::InvalidCorpus(
+            path.to_path_buf(),
+            invalid_lines,
+        ));
+    }
+    Ok(hanzi_pairs)
+}
+
+// Like the corpus, every invalid override is reported before failing. An override matching no
+// pair is an error too, as the app would silently ignore it.
+fn check_pronunciation_overrides(
+    path: &Path,
+    hanzi_pairs: &mut [HanziPair],
+) -> Result<(), CorpusToolError> {
+    let mut overrides: Vec<PronunciationOverride> = vec![];
+    let mut invalid_lines = 0;
+    for (line_number, pronunciation_override) in parse_pronunciation_overrides(&read(path)?) {
+        match pronunciation_override {
+            Ok(pronunciation_override) => overrides.push(pronunciation_override),
+            Err(e) => {
+                eprintln!("{} line {line_number}: {e}", path.display());
+                invalid_lines += 1;
+            }
+        }
+    }
+    for unused_override in apply_pronunciation_overrides(hanzi_pairs, &overrides) {
+        eprintln!(
+            "{}: {} is not in the corpus",
+            path.display(),
+            unused_override.characters
+        );
+        invalid_lines += 1;
+    }
+    if invalid_lines > 0 {
+        return Err(CorpusToolError::InvalidPronunciationOverrides(
+            path.to_path_buf(),
+            invalid_lines,
+        ));
+    }
+    Ok(())
+}
+
+// The encoded corpus is decoded back, so that the app loads exactly the checked pairs
+fn encode_binary_corpus(
+    path: &Path,
+    hanzi_pairs: &[HanziPair],
+) -> Result<Vec<u8>, CorpusToolError> {
+    let bytes = BinaryCorpus::encode(hanzi_pairs);
+    let corpus = BinaryCorpus::decode(&bytes)
+        .map_err(|e| CorpusToolError::InvalidBinaryCorpus(path.to_path_buf(), e.to_string()))?;
+    if let Some(idx) = (0..hanzi_pairs.len().max(corpus.len())).find(|&idx| {
+        corpus.get(idx).map(|pair_ref| pair_ref.to_hanzi_pair()) != hanzi_pairs.get(idx).cloned()
+    }) {
+        return Err(CorpusToolError::InvalidBinaryCorpus(
+            path.to_path_buf(),
+            format!("pair {} differs once decoded", idx + 1),
+        ));
+    }
+    Ok(bytes)
+}
+
+fn run(args: Args) -> Result<(), CorpusToolError> {
+    match args.command {
+        Command::Build {
+            cedict,
+            hsk,
+            missing_audio,
+            overrides,
+            output,
+            text_output,
+            binary_output,
+            stats,
+            glosses,
+            pronunciation_overrides,
+        } => {
+            let mut records = build_corpus(
+                &read(&hsk)?,
+                &read(&missing_audio)?,
+                &read(&cedict)?,
+                &read(&overrides)?,
+            )
+            .map_err(|errors: Vec<BuildError>| {
+                for e in errors.iter() {
+                    eprintln!("{e}");
+                }
+                CorpusToolError::Build(errors.len())
+            })?;
+            write_glosses(&mut records, &glosses)?;
+            // Everything is validated before writing, so a failed build leaves no file behind
+            let corpus = format_corpus_jsonl(&records);
+            let mut hanzi_pairs = check_corpus_text(&output, &corpus)?;
+            let corpus_stats = format_stats(&hanzi_pairs);
+            check_pronunciation_overrides(&pronunciation_overrides, &mut hanzi_pairs)?;
+            let binary_corpus = encode_binary_corpus(&binary_output, &hanzi_pairs)?;
+            let text_corpus: String = records
+                .iter()
+                .map(|record| format!("{}\n", record.to_text_line()))
+                .collect();
+            write(&output, corpus)?;
+            write(&text_output, text_corpus)?;
+            write(&stats, corpus_stats)?;
+            write(&binary_output, binary_corpus)?;
+            eprintln!(
+                "Wrote {} hanzi pairs to {}",
+                records.len(),
+                output.display()
+            );
+        }
+        Command::Convert {
+            corpus,
+            hsk,
+            output,
+            glosses,
+            binary_output,
+            pronunciation_overrides,
+        } => {
+            let mut records: Vec<HanziRecord> = check_corpus(&corpus)?
+                .iter()
+                .map(HanziRecord::from)
+                .collect();
+            add_hsk_levels(&mut records, &read(&hsk)?);
+            write_glosses(&mut records, &glosses)?;
+            let corpus = format_corpus_jsonl(&records);
+            let mut hanzi_pairs = check_corpus_text(&output, &corpus)?;
+            check_pronunciation_overrides(&pronunciation_overrides, &mut hanzi_pairs)?;
+            let binary_corpus = encode_binary_corpus(&binary_output, &hanzi_pairs)?;
+            write(&output, corpus)?;
+            write(&binary_output, binary_corpus)?;
+            eprintln!(
+                "Wrote {} hanzi pairs to {}",
+                records.len(),
+                output.display()
+            );
+        }
+        Command::Check {
+            corpus,
+            pronunciation_overrides,
+            write_stats,
+        } => {
+            let mut hanzi_pairs = check_corpus(&corpus)?;
+            if let Some(stats) = write_stats {
+                write(&stats, format_stats(&hanzi_pairs))?;
+            }
+            check_pronunciation_overrides(&pronunciation_overrides, &mut hanzi_pairs)?;
+            eprintln!("{} hanzi pairs are valid", hanzi_pairs.len());
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+
+use shuangshuang_core::corpus::{parse_hanzi_pair, CorpusError, HanziRecord};
+use shuangshuang_core::exercise::HanziPair;
+use thiserror::Error;
+
+use crate::cedict::{get_corpus_pinyin_and_tones, parse_cedict, CedictEntry};
+
+const MAX_GLOSS_MEANINGS: usize = 2;
+
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum BuildError {
+    #[error("{0} is not in the CC-CEDICT file.")]
+    MissingFromCedict(String),
+    #[error("Overrides line {0}: {1}")]
+    InvalidOverride(usize, CorpusError),
+    #[error("The override of {0} matches no word of the corpus.")]
+    UnusedOverride(String),
+    #[error("Line `{0}` would be rejected by the app: {1}")]
+    InvalidLine(String, CorpusError),
+}
+
+fn is_hanzi(character: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&character)
+}
+
+fn is_hanzi_pair(word: &str) -> bool {
+    word.chars().count() == 2 && word.chars().all(is_hanzi)
+}
+
+/// Parses the overrides file, which uses the `hanzi_pairs.txt` format with `#` comments.
+/// Every override is validated with the app's parser.
+pub fn parse_overrides(text: &str) -> Result<HashMap<String, HanziRecord>, Vec<BuildError>> {
+    let mut overrides: HashMap<String, HanziRecord> = HashMap::new();
+    let mut errors: Vec<BuildError> = vec![];
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_hanzi_pair(line) {
+            Ok(hanzi_pair) => {
+                overrides.insert(
+                    hanzi_pair.characters.clone(),
+                    HanziRecord::from(&hanzi_pair),
+                );
+            }
+            Err(e) => errors.push(BuildError::InvalidOverride(idx + 1, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(overrides)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Maps every word of the HSK list to its level, the list has a `HSK<level>` line before the
+/// words of each level
+pub fn parse_hsk_levels(hsk: &str) -> HashMap<String, u8> {
+    let mut hsk_levels: HashMap<String, u8> = HashMap::new();
+    let mut current_level: Option<u8> = None;
+    for line in hsk.lines().map(str::trim) {
+        if let Some(level) = line.strip_prefix("HSK") {
+            current_level = level.parse().ok();
+        } else if let Some(level) = current_level {
+            hsk_levels.entry(line.to_string()).or_insert(level);
+        }
+    }
+    hsk_levels
+}
+
+/// Sets the HSK level of the records found in the HSK list
+pub fn add_hsk_levels(records: &mut [HanziRecord], hsk: &str) {
+    let hsk_levels = parse_hsk_levels(hsk);
+    for record in records.iter_mut() {
+        record.hsk_level = hsk_levels.get(&record.characters).copied();
+    }
+}
+
+// The other readings of a word, proper nouns and readings the app would reject are left out
+fn get_alternatives(word: &str, pinyin: &str, entries: &[CedictEntry]) -> Vec<String> {
+    let mut alternatives: Vec<String> = vec![];
+    for entry in entries
+        .iter()
+        .filter(|entry| !entry.pinyin.starts_with(char::is_uppercase))
+    {
+        let (alternative, tones) = get_corpus_pinyin_and_tones(&entry.pinyin);
+        if alternative != pinyin
+            && !alternatives.contains(&alternative)
+            && parse_hanzi_pair(&format!("{word} {alternative} {tones}")).is_ok()
+        {
+            alternatives.push(alternative);
+        }
+    }
+    alternatives
+}
+
+// Cross-references and classifiers say little about the meaning of a word
+fn is_meaning(definition: &str) -> bool {
+    ![
+        "CL:",
+        "variant of",
+        "old variant of",
+        "see ",
+        "also pr.",
+        "also written",
+    ]
+    .iter()
+    .any(|prefix| definition.starts_with(prefix))
+}
+
+// The first meanings of an entry, short enough to be shown under a word
+fn get_gloss(entry: &CedictEntry) -> Option<String> {
+    let meanings: Vec<&str> = entry
+        .definitions
+        .iter()
+        .map(String::as_str)
+        .filter(|definition| is_meaning(definition))
+        .take(MAX_GLOSS_MEANINGS)
+        .collect();
+    (!meanings.is_empty()).then(|| meanings.join("; "))
+}
+
+// The gloss of the entry with the same reading is preferred, proper nouns are left out
+fn get_reading_gloss(entries: &[CedictEntry], pinyin: &str) -> Option<String> {
+    entries
+        .iter()
+        .filter(|entry| get_corpus_pinyin_and_tones(&entry.pinyin).0 == pinyin)
+        .chain(
+            entries
+                .iter()
+                .filter(|entry| !entry.pinyin.starts_with(char::is_uppercase)),
+        )
+        .find_map(get_gloss)
+}
+
+/// Sets the gloss in `language` of the records found in a dictionary in the CC-CEDICT format,
+/// e.g. CFDICT for French, and returns the words it has no gloss for
+pub fn add_glosses(records: &mut [HanziRecord], language: &str, dictionary: &str) -> Vec<String> {
+    let entries_by_word = parse_cedict(dictionary);
+    let mut missing_words: Vec<String> = vec![];
+    for record in records.iter_mut() {
+        let gloss = entries_by_word
+            .get(&record.characters)
+            .and_then(|entries| get_reading_gloss(entries, &record.pinyin));
+        match gloss {
+            Some(gloss) => {
+                record.glosses.insert(language.to_string(), gloss);
+            }
+            None => missing_words.push(record.characters.clone()),
+        }
+    }
+    missing_words
+}
+
+/// Builds the corpus records from the two hanzi words of the HSK list that have a recording,
+/// taking their pinyin, traditional form and English gloss from CC-CEDICT then applying the
+/// overrides. The other CC-CEDICT readings are kept as alternatives. Every record is validated
+/// with the app's parser, and all errors are returned at once.
+pub fn build_corpus(
+    hsk: &str,
+    missing_audio: &str,
+    cedict: &str,
+    overrides: &str,
+) -> Result<Vec<HanziRecord>, Vec<BuildError>> {
+    let mut overrides = parse_overrides(overrides)?;
+    let missing_audio: HashSet<&str> = missing_audio.lines().map(str::trim).collect();
+    let entries_by_word = parse_cedict(cedict);
+    let mut records: Vec<HanziRecord> = vec![];
+    let mut errors: Vec<BuildError> = vec![];
+    for word in hsk.lines().map(str::trim) {
+        if !is_hanzi_pair(word) || missing_audio.contains(word) {
+            continue;
+        }
+        let Some(entries) = entries_by_word.get(word) else {
+            errors.push(BuildError::MissingFromCedict(word.to_string()));
+            continue;
+        };
+        // The recordings follow the first reading
+        let (pinyin, tones) = get_corpus_pinyin_and_tones(&entries[0].pinyin);
+        let mut record = HanziRecord {
+            characters: word.to_string(),
+            alternatives: get_alternatives(word, &pinyin, entries),
+            pinyin,
+            tones,
+            traditional: Some(entries[0].traditional.clone())
+                .filter(|traditional| traditional != word),
+            ..Default::default()
+        };
+        if let Some(override_record) = overrides.remove(word) {
+            record.pinyin = override_record.pinyin;
+            record.tones = override_record.tones;
+            record.alternatives = override_record.alternatives;
+        }
+        if let Some(gloss) = get_reading_gloss(entries, &record.pinyin) {
+            record.glosses.insert("en".to_string(), gloss);
+        }
+        if let Err(e) = parse_hanzi_pair(&record.to_text_line()) {
+            errors.push(BuildError::InvalidLine(record.to_text_line(), e));
+        }
+        records.push(record);
+    }
+    add_hsk_levels(&mut records, hsk);
+    let mut unused_overrides: Vec<String> = overrides.into_keys().collect();
+    unused_overrides.sort();
+    errors.extend(unused_overrides.into_iter().map(BuildError::UnusedOverride));
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Formats the share of each tone pair in the corpus, in order of first appearance, as in
+/// `hanzi_pairs_stats.txt`
+pub fn format_stats(hanzi_pairs: &[HanziPair]) -> String {
+    let mut counts: Vec<(String, u32)> = vec![];
+    for hanzi_pair in hanzi_pairs {
+        let tones = format!("{}{}", hanzi_pair.tone_pair.0, hanzi_pair.tone_pair.1);
+        match counts.iter_mut().find(|(key, _)| *key == tones) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((tones, 1)),
+        }
+    }
+    let total = hanzi_pairs.len().max(1) as f32;
+    counts
+        .iter()
+        .map(|(tones, count)| format!("{tones}: {count} ({:.2}%)\n", *count as f32 / total * 100.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HSK: &str = "HSK1\n我们\n你\n学校\n好处\n一丝不苟\n出租车\n";
+    const MISSING_AUDIO: &str = "一丝不苟\n出租车\n";
+    const CEDICT: &str = "我們 我们 [wo3 men5] /we/\n\
+        學校 学校 [xue2 xiao4] /school/\n\
+        好處 好处 [hao3 chu3] /benefit/\n\
+        好處 好处 [hao4 chu4] /a different reading/\n\
+        學校 学校 [Xue2 xiao4] /a proper noun/\n";
+
+    #[test]
+    fn build_corpus_applies_overrides() {
+        let lines = build_corpus(HSK, MISSING_AUDIO, CEDICT, "好处 hao3chu 35 # recording\n")
+            .expect("Corpus should build");
+        let lines: Vec<String> = lines.iter().map(HanziRecord::to_text_line).collect();
+        assert_eq!(
+            lines,
+            vec!["我们 wo3men 35", "学校 xue2xiao4 24", "好处 hao3chu 35"]
+        );
+    }
+
+    #[test]
+    fn build_corpus_keeps_alternative_readings() {
+        let lines = build_corpus(HSK, MISSING_AUDIO, CEDICT, "").expect("Corpus should build");
+        let lines: Vec<String> = lines.iter().map(HanziRecord::to_text_line).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "我们 wo3men 35",
+                "学校 xue2xiao4 24",
+                "好处 hao3chu3 33 hao4chu4"
+            ]
+        );
+    }
+
+    #[test]
+    fn build_corpus_adds_metadata() {
+        let records = build_corpus(HSK, MISSING_AUDIO, CEDICT, "").expect("Corpus should build");
+        assert_eq!(records[0].traditional.as_deref(), Some("我們"));
+        assert_eq!(records[0].glosses["en"], "we");
+        assert_eq!(records[0].hsk_level, Some(1));
+        assert_eq!(
+            parse_hsk_levels("HSK1\n我们\nHSK2\n学校\n我们\n"),
+            HashMap::from([("我们".to_string(), 1), ("学校".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn add_glosses_prefers_the_reading_of_the_record() {
+        let mut records =
+            build_corpus(HSK, MISSING_AUDIO, CEDICT, "").expect("Corpus should build");
+        let cfdict = "好處 好处 [hao4 chu4] /autre lecture/\n\
+            好處 好处 [hao3 chu3] /CL:個|个[ge4]/avantage/bénéfice/profit/\n\
+            學校 学校 [xue2 xiao4] /variant of 學堂|学堂[xue2 tang2]/\n";
+        assert_eq!(
+            add_glosses(&mut records, "fr", cfdict),
+            vec!["我们", "学校"]
+        );
+        assert_eq!(records[2].glosses["fr"], "avantage; bénéfice");
+        assert_eq!(records[2].glosses["en"], "benefit");
+        assert!(!records[1].glosses.contains_key("fr"));
+    }
+
+    #[test]
+    fn build_corpus_reports_every_error() {
+        let errors = build_corpus(
+            &format!("{HSK}把手\n"),
+            MISSING_AUDIO,
+            "我們 我们 [wo3 men5] /we/\n學校 学校 [xue2 xiao4] /school/\n好處 好处 [hao3 chu3 4] /x/\n",
+            "# comment only\n因为 yin1wei2 12\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                BuildError::InvalidLine(
+                    "好处 hao3chu34 334".to_string(),
+                    CorpusError::InvalidToneNumbers("334".to_string())
+                ),
+                BuildError::MissingFromCedict("把手".to_string()),
+                BuildError::UnusedOverride("因为".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_overrides("好处 hao3chu 33\n").unwrap_err(),
+            vec![BuildError::InvalidOverride(
+                1,
+                CorpusError::PinyinToneNumbersUnmatch("hao3chu".to_string(), "33".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn format_stats_works() {
+        let hanzi_pairs: Vec<HanziPair> = ["我们 wo3men 35", "学校 xue2xiao4 24", "怎么 zen3me 35"]
+            .iter()
+            .map(|line| parse_hanzi_pair(line).unwrap())
+            .collect();
+        assert_eq!(
+            format_stats(&hanzi_pairs),
+            "35: 2 (66.67%)\n24: 1 (33.33%)\n"
+        );
+    }
+}
@@ -1,39 +1,164 @@
 use gloo_net::http::Request;
+use leptos::leptos_dom::logging::console_log;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AudioBuffer, BaseAudioContext, OfflineAudioContext};
 
-use crate::{
-    exercise::{HanziPair, Tone},
-    utils::{get_pronounced_pinyin, get_tones_from_pinyin},
+use crate::binary_corpus::BinaryCorpus;
+use crate::corpus::{
+    apply_pronunciation_overrides, parse_corpus, parse_pronunciation_overrides,
+    PronunciationOverride,
 };
+use crate::exercise::HanziPair;
 
-pub async fn fetch_hanzi_pairs() -> Vec<HanziPair> {
-    let mut hanzi_pairs: Vec<HanziPair> = vec![];
-    let resp = Request::get(
-        "https://raw.githubusercontent.com/matturche/Shuangshuang/refs/heads/main/data/hanzi_pairs.txt"
-    )
-    .send()
-    .await.expect("Failed send request for hanzi pairs");
-    let text = resp.text().await.expect("Failed to get text from response");
-    let lines: Vec<String> = text.lines().map(str::to_owned).collect();
-    for line in lines.iter() {
-        let splits: Vec<&str> = line.split(' ').collect();
-        let tones = splits[2];
-        // We know from the preprocessing that [tones] cannot be less than two chars, hence the
-        // unwrap
-        let tone_pair: (Tone, Tone) = (
-            Tone::from(tones.chars().nth(0).unwrap()),
-            Tone::from(tones.chars().nth(1).unwrap()),
-        );
-        let characters = splits[0].to_string();
-        let pinyin = splits[1].to_string();
-        let pronounced_pinyin = get_pronounced_pinyin(&characters, &pinyin, &tone_pair);
-        let pronounced_tone_pair = get_tones_from_pinyin(&pronounced_pinyin);
-        hanzi_pairs.push(HanziPair {
-            characters,
-            pinyin,
-            pronounced_pinyin,
-            tone_pair,
-            pronounced_tone_pair: (pronounced_tone_pair[0], pronounced_tone_pair[1]),
-        });
-    }
-    hanzi_pairs
+const DATA_URL: &str =
+    "https://raw.githubusercontent.com/matturche/Shuangshuang/refs/heads/main/data";
+
+// The app works without overrides, the dictionary pronunciation is used then
+async fn fetch_pronunciation_overrides() -> Vec<PronunciationOverride> {
+    let Ok(resp) = Request::get(&format!("{DATA_URL}/pronunciation_overrides.txt"))
+        .send()
+        .await
+    else {
+        return vec![];
+    };
+    if !resp.ok() {
+        return vec![];
+    }
+    let text = resp.text().await.unwrap_or_default();
+    parse_pronunciation_overrides(&text)
+        .into_iter()
+        .filter_map(
+            |(line_number, pronunciation_override)| match pronunciation_override {
+                Ok(pronunciation_override) => Some(pronunciation_override),
+                Err(e) => {
+                    console_log(&format!(
+                        "Skipping pronunciation_overrides.txt line {line_number}: {e}"
+                    ));
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+// The binary corpus already has the pronunciation overrides applied
+async fn fetch_binary_corpus() -> Option<BinaryCorpus> {
+    let resp = Request::get(&format!("{DATA_URL}/hanzi_pairs.bin"))
+        .send()
+        .await
+        .ok()?;
+    if !resp.ok() {
+        return None;
+    }
+    let bytes = resp.binary().await.ok()?;
+    BinaryCorpus::decode(&bytes)
+        .inspect_err(|e| console_log(&format!("Failed to decode hanzi_pairs.bin: {e}")))
+        .ok()
+}
+
+// The structured corpus is preferred, the text one is kept for older deployments
+async fn fetch_corpus() -> (&'static str, String) {
+    for file_name in ["hanzi_pairs.jsonl", "hanzi_pairs.txt"] {
+        if let Ok(resp) = Request::get(&format!("{DATA_URL}/{file_name}"))
+            .send()
+            .await
+        {
+            if resp.ok() {
+                if let Ok(text) = resp.text().await {
+                    return (file_name, text);
+                }
+            }
+        }
+    }
+    panic!("Failed to fetch the hanzi pairs corpus");
+}
+
+pub async fn fetch_hanzi_pairs() -> BinaryCorpus {
+    if let Some(corpus) = fetch_binary_corpus().await {
+        for hanzi_pair in corpus.iter() {
+            log_pronunciation_override(&hanzi_pair.to_hanzi_pair());
+        }
+        return corpus;
+    }
+    let (file_name, text) = fetch_corpus().await;
+    let mut hanzi_pairs: Vec<HanziPair> = parse_corpus(&text)
+        .into_iter()
+        .filter_map(|(line_number, hanzi_pair)| match hanzi_pair {
+            Ok(hanzi_pair) => Some(hanzi_pair),
+            Err(e) => {
+                console_log(&format!("Skipping {file_name} line {line_number}: {e}"));
+                None
+            }
+        })
+        .collect();
+    let overrides = fetch_pronunciation_overrides().await;
+    for unused_override in apply_pronunciation_overrides(&mut hanzi_pairs, &overrides) {
+        console_log(&format!(
+            "Unused pronunciation override: {} is not in the corpus",
+            unused_override.characters
+        ));
+    }
+    for hanzi_pair in hanzi_pairs.iter() {
+        log_pronunciation_override(hanzi_pair);
+    }
+    BinaryCorpus::from(&hanzi_pairs[..])
+}
+
+fn log_pronunciation_override(hanzi_pair: &HanziPair) {
+    if let Some(reason) = &hanzi_pair.pronunciation_override {
+        console_log(&format!(
+            "Pronunciation override of {}: {} instead of {} ({reason})",
+            hanzi_pair.characters, hanzi_pair.pronounced_pinyin, hanzi_pair.pinyin
+        ));
+    }
+}
+
+/// Fetches an audio file and decodes it at the sample rate of `audio_context`
+pub async fn fetch_audio_buffer(
+    audio_context: &BaseAudioContext,
+    url: &str,
+) -> Option<AudioBuffer> {
+    let resp = Request::get(url).send().await.ok()?;
+    if !resp.ok() {
+        return None;
+    }
+    let bytes = resp.binary().await.ok()?;
+    let array = js_sys::Uint8Array::from(bytes.as_slice());
+    let promise = audio_context.decode_audio_data(&array.buffer()).ok()?;
+    JsFuture::from(promise)
+        .await
+        .ok()?
+        .dyn_into::<AudioBuffer>()
+        .ok()
+}
+
+/// Decodes an encoded audio file and returns its first channel as PCM samples at `sample_rate`
+pub async fn decode_audio_samples(bytes: &[u8], sample_rate: f32) -> Option<Vec<f32>> {
+    // An offline context is enough for decoding, and resamples to the rate we analyze at
+    let offline_context =
+        OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(
+            1,
+            1,
+            sample_rate,
+        )
+        .ok()?;
+    let array = js_sys::Uint8Array::from(bytes);
+    let promise = offline_context.decode_audio_data(&array.buffer()).ok()?;
+    let buffer = JsFuture::from(promise)
+        .await
+        .ok()?
+        .dyn_into::<AudioBuffer>()
+        .ok()?;
+    buffer.get_channel_data(0).ok()
+}
+
+/// Fetches an audio file and returns its first channel as PCM samples at `sample_rate`
+pub async fn fetch_audio_samples(url: &str, sample_rate: f32) -> Option<Vec<f32>> {
+    let resp = Request::get(url).send().await.ok()?;
+    if !resp.ok() {
+        return None;
+    }
+    let bytes = resp.binary().await.ok()?;
+    decode_audio_samples(&bytes, sample_rate).await
 }
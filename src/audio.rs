@@ -0,0 +1,196 @@
+use rand::Rng;
+use thiserror::Error;
+
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum AudioError {
+    #[error("The WAV header is missing or malformed.")]
+    InvalidWavHeader,
+    #[error("Only 16 bits PCM and 32 bits float WAV files are supported.")]
+    UnsupportedWavFormat,
+    #[error("Unknown error")]
+    Unknown,
+}
+
+/// RMS level we assume for the word recordings, roughly -20 dBFS.
+pub const SPEECH_REFERENCE_RMS: f32 = 0.1;
+
+pub fn generate_white_noise<R: Rng>(rng: &mut R, nb_samples: usize) -> Vec<f32> {
+    (0..nb_samples)
+        .map(|_| rng.random_range(-1.0..=1.0))
+        .collect()
+}
+
+// Paul Kellet's refined filter, which gives a -3dB/octave slope from white noise
+pub fn generate_pink_noise<R: Rng>(rng: &mut R, nb_samples: usize) -> Vec<f32> {
+    let mut b = [0.0f32; 7];
+    let mut samples: Vec<f32> = Vec::with_capacity(nb_samples);
+    for white in generate_white_noise(rng, nb_samples) {
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        samples.push(b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362);
+        b[6] = white * 0.115926;
+    }
+    samples
+}
+
+pub fn get_rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Scales the samples so their RMS is 1, silent buffers are left untouched
+pub fn normalize_rms(samples: &mut [f32]) {
+    let rms = get_rms(samples);
+    if rms > 0.0 {
+        for sample in samples.iter_mut() {
+            *sample /= rms;
+        }
+    }
+}
+
+/// Gain to apply to a noise of RMS `noise_rms` so it sits `snr_db` below the speech reference
+pub fn get_noise_gain_from_snr(snr_db: i32, noise_rms: f32) -> f32 {
+    if noise_rms <= 0.0 {
+        return 0.0;
+    }
+    let target_rms = SPEECH_REFERENCE_RMS / 10f32.powf(snr_db as f32 / 20.0);
+    target_rms / noise_rms
+}
+
+/// Decodes a WAV file into mono samples between -1 and 1, along with its sample rate
+#[allow(dead_code)]
+pub fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32), AudioError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(AudioError::InvalidWavHeader);
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let read_u32 =
+        |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+    // (format tag, channels, sample rate, bits per sample)
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = read_u32(offset + 4) as usize;
+        let chunk_start = offset + 8;
+        let chunk_end = (chunk_start + chunk_size).min(bytes.len());
+        if chunk_id == b"fmt " {
+            if chunk_size < 16 || chunk_end < chunk_start + 16 {
+                return Err(AudioError::InvalidWavHeader);
+            }
+            format = Some((
+                read_u16(chunk_start),
+                read_u16(chunk_start + 2),
+                read_u32(chunk_start + 4),
+                read_u16(chunk_start + 14),
+            ));
+        } else if chunk_id == b"data" {
+            let (format_tag, channels, sample_rate, bits) =
+                format.ok_or(AudioError::InvalidWavHeader)?;
+            let channels = channels.max(1) as usize;
+            let data = &bytes[chunk_start..chunk_end];
+            let interleaved: Vec<f32> = match (format_tag, bits) {
+                (1, 16) => data
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                    .collect(),
+                (3, 32) => data
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+                _ => return Err(AudioError::UnsupportedWavFormat),
+            };
+            let samples = interleaved
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                .collect();
+            return Ok((samples, sample_rate));
+        }
+        // Chunks are padded to an even size
+        offset = chunk_start + chunk_size + chunk_size % 2;
+    }
+    Err(AudioError::InvalidWavHeader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const NB_SAMPLES: usize = 44_100;
+
+    // Energy of the first difference over the signal energy, high for bright noises
+    fn get_brightness(samples: &[f32]) -> f32 {
+        let diffs: Vec<f32> = samples.windows(2).map(|w| w[1] - w[0]).collect();
+        get_rms(&diffs) / get_rms(samples)
+    }
+
+    #[test]
+    fn white_noise_stays_in_range() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let noise = generate_white_noise(&mut rng, NB_SAMPLES);
+        assert_eq!(noise.len(), NB_SAMPLES);
+        assert!(noise.iter().all(|s| (-1.0..=1.0).contains(s)));
+        // Uniform noise over [-1, 1] has an RMS of 1/sqrt(3)
+        assert!((get_rms(&noise) - 1.0 / 3f32.sqrt()).abs() < 0.01);
+    }
+
+    #[test]
+    fn pink_noise_is_darker_than_white_noise() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let white = generate_white_noise(&mut rng, NB_SAMPLES);
+        let pink = generate_pink_noise(&mut rng, NB_SAMPLES);
+        assert!(get_brightness(&pink) < get_brightness(&white) / 2.0);
+    }
+
+    #[test]
+    fn normalize_rms_works() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut noise = generate_pink_noise(&mut rng, NB_SAMPLES);
+        normalize_rms(&mut noise);
+        assert!((get_rms(&noise) - 1.0).abs() < 1e-3);
+        let mut silence = vec![0.0; 10];
+        normalize_rms(&mut silence);
+        assert_eq!(silence, vec![0.0; 10]);
+    }
+
+    #[test]
+    fn decode_wav_works() {
+        let mut bytes: Vec<u8> = vec![];
+        bytes.extend(b"RIFF");
+        bytes.extend(36u32.to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        // PCM, stereo, 8kHz, byte rate, block align, 16 bits
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(8000u32.to_le_bytes());
+        bytes.extend(32000u32.to_le_bytes());
+        bytes.extend(4u16.to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend(8u32.to_le_bytes());
+        for sample in [16384i16, 0, -32768, -32768] {
+            bytes.extend(sample.to_le_bytes());
+        }
+        let (samples, sample_rate) = decode_wav(&bytes).expect("WAV should decode");
+        assert_eq!(sample_rate, 8000);
+        assert_eq!(samples, vec![0.25, -1.0]);
+        assert_eq!(decode_wav(b"RIFF"), Err(AudioError::InvalidWavHeader));
+    }
+
+    #[test]
+    fn noise_gain_from_snr_works() {
+        assert!((get_noise_gain_from_snr(0, 1.0) - SPEECH_REFERENCE_RMS).abs() < 1e-6);
+        assert!((get_noise_gain_from_snr(20, 1.0) - SPEECH_REFERENCE_RMS / 10.0).abs() < 1e-6);
+        assert!((get_noise_gain_from_snr(20, 0.5) - SPEECH_REFERENCE_RMS / 5.0).abs() < 1e-6);
+        assert_eq!(get_noise_gain_from_snr(10, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,137 @@
+use leptos::prelude::*;
+
+use crate::components::syllable_replay::SyllableReplay;
+use crate::exercise::{SandhiRule, ShuangElement};
+use crate::i18n::*;
+use crate::utils::get_sandhi_rule;
+
+/// A component shown between two pairs of a session, grading the last answer and/or giving the
+/// meaning of the pair, along with its syllables to replay, until the learner moves on
+#[component]
+pub fn AnswerFeedback(
+    shuang_element: ShuangElement,
+    audio_url: String,
+    show_grading: bool,
+    show_meaning: bool,
+    on_replay: Callback<()>,
+    on_next: Callback<()>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let hanzi_pair = shuang_element.hanzi_pair;
+    let sandhi_rule = get_sandhi_rule(
+        &hanzi_pair.characters,
+        &hanzi_pair.pinyin,
+        &hanzi_pair.tone_pair,
+    );
+    let metadata = hanzi_pair.metadata.clone();
+    let gloss = move || match metadata.gloss(i18n.get_locale().as_ref()) {
+        Some(gloss) => format!("{}{gloss}", t_string!(i18n, exercise.meaning)),
+        None => t_string!(i18n, exercise.no_meaning).to_string(),
+    };
+
+    view! {
+        <div class="card bg-base-100 card-border border-base-300 my-2 p-4 text-center">
+            {show_grading
+                .then(|| {
+                    if shuang_element.is_correct {
+                        view! {
+                            <div class="text-success font-semibold">
+                                {t!(i18n, exercise.correct_feedback)}
+                            </div>
+                        }
+                            .into_any()
+                    } else {
+                        view! {
+                            <div class="text-error font-semibold">
+                                {t!(i18n, exercise.incorrect_feedback)}
+                            </div>
+                        }
+                            .into_any()
+                    }
+                })}
+            <div class="text-2xl">{hanzi_pair.characters.clone()}</div>
+            {show_grading
+                .then(|| {
+                    view! {
+                        <div>
+                            {t!(i18n, exercise.expected_pinyin_answer)}
+                            {hanzi_pair.pronounced_pinyin.clone()}
+                        </div>
+                        <div>
+                            {t!(i18n, exercise.expected_tone_answer)}
+                            {format!(
+                                "{}{}",
+                                hanzi_pair.pronounced_tone_pair.0,
+                                hanzi_pair.pronounced_tone_pair.1,
+                            )}
+                        </div>
+                        {(!shuang_element.is_correct)
+                            .then(|| {
+                                view! {
+                                    <div>
+                                        {t!(i18n, exercise.user_answer)}
+                                        {shuang_element.user_answer.clone()}
+                                    </div>
+                                }
+                            })}
+                        {sandhi_rule
+                            .map(|sandhi_rule| {
+                                let rule_view = match sandhi_rule {
+                                    SandhiRule::ThirdTone => {
+                                        t!(i18n, exercise.sandhi_third_tone).into_any()
+                                    }
+                                    SandhiRule::BuBeforeFourthTone => {
+                                        t!(i18n, exercise.sandhi_bu_before_fourth_tone).into_any()
+                                    }
+                                    SandhiRule::YiBeforeFourthTone => {
+                                        t!(i18n, exercise.sandhi_yi_before_fourth_tone).into_any()
+                                    }
+                                    SandhiRule::YiBeforeOtherTones => {
+                                        t!(i18n, exercise.sandhi_yi_before_other_tones).into_any()
+                                    }
+                                };
+                                view! {
+                                    <div class="text-info">
+                                        {t!(i18n, exercise.sandhi_rule)} {rule_view}
+                                    </div>
+                                }
+                            })}
+                        {hanzi_pair
+                            .pronunciation_override
+                            .clone()
+                            .map(|reason| {
+                                view! {
+                                    <div class="text-warning">
+                                        {t!(i18n, exercise.recording_differs_from_dictionary)}
+                                        {format!("{} ({reason})", hanzi_pair.pinyin)}
+                                    </div>
+                                }
+                            })}
+                    }
+                })}
+            {(!show_grading).then(|| view! { <div>{hanzi_pair.pronounced_pinyin.clone()}</div> })}
+            {show_meaning.then(|| view! { <div class="italic">{gloss}</div> })}
+            <div class="flex justify-center">
+                <SyllableReplay
+                    audio_url
+                    pronounced_pinyin=hanzi_pair.pronounced_pinyin.clone()
+                />
+            </div>
+            <div class="flex justify-center gap-2 pt-2">
+                <button
+                    class="btn btn-neutral rounded-md"
+                    on:click=move |_| on_replay.run(())
+                >
+                    {t!(i18n, exercise.replay_audio)}
+                </button>
+                <button
+                    class="btn btn-primary text-white rounded-md"
+                    autofocus
+                    on:click=move |_| on_next.run(())
+                >
+                    {t!(i18n, exercise.next_pair)}
+                </button>
+            </div>
+        </div>
+    }
+}
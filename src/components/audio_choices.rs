@@ -0,0 +1,49 @@
+use leptos::prelude::*;
+
+use crate::exercise::{AudioQuality, HanziPair};
+use crate::i18n::*;
+use crate::utils::format_word_url;
+
+const CHOICE_LABELS: [&str; 4] = ["A", "B", "C", "D"];
+
+/// A component with a labeled player for each recording of the reverse exercise, picking one
+/// submits its pinyin as the answer
+#[component]
+pub fn AudioChoices(
+    choices: Signal<Vec<HanziPair>>,
+    audio_quality: AudioQuality,
+    set_chosen_answer: WriteSignal<String>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+        <div class="flex flex-col gap-2">
+            {move || {
+                choices()
+                    .into_iter()
+                    .zip(CHOICE_LABELS)
+                    .map(|(choice, label)| {
+                        let audio_url = format_word_url(&choice.characters, audio_quality);
+                        let pronounced_pinyin = choice.pronounced_pinyin;
+                        view! {
+                            <div class="flex items-center gap-2">
+                                <span class="badge badge-neutral font-semibold">{label}</span>
+                                <audio controls preload="none">
+                                    <source type="audio/mpeg" src=audio_url />
+                                </audio>
+                                <button
+                                    type="submit"
+                                    class="btn btn-outline btn-primary rounded-md"
+                                    on:click=move |_| set_chosen_answer(pronounced_pinyin.clone())
+                                >
+                                    {t!(i18n, exercise.pick_recording)}
+                                    {format!(" {label}")}
+                                </button>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+    }
+}
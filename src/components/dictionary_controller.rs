@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use codee::string::JsonSerdeCodec;
+use leptos::html;
+use leptos::prelude::*;
+use leptos::tachys::html::event::Event;
+use leptos_use::storage::use_local_storage;
+
+use crate::dictionary::DictionaryProvider;
+use crate::i18n::*;
+
+const CUSTOM_TEMPLATE_PLACEHOLDER: &str = "https://example.com/search?q={hanzi}";
+
+/// The dictionary chosen by the user, kept in local storage. Until one is chosen, it follows the
+/// language of the app.
+pub fn use_dictionary_provider() -> (
+    Signal<DictionaryProvider>,
+    WriteSignal<Option<DictionaryProvider>>,
+) {
+    let i18n = use_i18n();
+    let (stored_provider, set_stored_provider, _) =
+        use_local_storage::<Option<DictionaryProvider>, JsonSerdeCodec>("dictionaryprovider");
+    let provider = Signal::derive(move || {
+        stored_provider()
+            .unwrap_or_else(|| DictionaryProvider::default_for_language(i18n.get_locale().as_ref()))
+    });
+    (provider, set_stored_provider)
+}
+
+/// A component to choose where the dictionary links lead, custom URL templates are only saved
+/// once valid
+#[component]
+pub fn DictionaryController(
+    provider: Signal<DictionaryProvider>,
+    set_provider: WriteSignal<Option<DictionaryProvider>>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let is_custom = RwSignal::new(matches!(
+        provider.get_untracked(),
+        DictionaryProvider::Custom(_)
+    ));
+    let (template_is_invalid, set_template_is_invalid) = signal(false);
+    let selected_provider: NodeRef<html::Select> = NodeRef::new();
+    let custom_template: NodeRef<html::Input> = NodeRef::new();
+
+    let on_provider_select = move |_ev: Event| {
+        let value = selected_provider
+            .get()
+            .expect("<select> should be mounted for the dictionary provider.")
+            .value();
+        match DictionaryProvider::from_str(&value) {
+            Ok(built_in_provider) => {
+                is_custom.set(false);
+                set_provider(Some(built_in_provider));
+            }
+            Err(_) => is_custom.set(true),
+        }
+    };
+    let on_template_change = move |_ev: Event| {
+        let template = custom_template
+            .get()
+            .expect("<input> should be mounted for the custom dictionary.")
+            .value();
+        match DictionaryProvider::custom(&template) {
+            Ok(custom_provider) => {
+                set_template_is_invalid(false);
+                set_provider(Some(custom_provider));
+            }
+            Err(_) => set_template_is_invalid(true),
+        }
+    };
+    let current_template = move || match provider.get_untracked() {
+        DictionaryProvider::Custom(template) => template,
+        _ => String::new(),
+    };
+
+    view! {
+        <div class="flex flex-wrap justify-center items-center gap-2 py-2">
+            <label class="label text-sm">{t!(i18n, exercise.dictionary_provider)}</label>
+            <select
+                class="select select-sm select-secondary w-fit"
+                node_ref=selected_provider
+                on:change=on_provider_select
+            >
+                {DictionaryProvider::BUILT_IN
+                    .into_iter()
+                    .map(|built_in_provider| {
+                        let value = built_in_provider.to_string();
+                        let is_selected = move || !is_custom() && provider().to_string() == value;
+                        view! {
+                            <option value=built_in_provider.to_string() selected=is_selected>
+                                {built_in_provider.name().to_string()}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+                <option value="custom" selected=is_custom>
+                    {t!(i18n, exercise.custom_dictionary)}
+                </option>
+            </select>
+            <Show when=is_custom>
+                <div class="flex flex-col">
+                    <input
+                        class="input input-sm rounded-md"
+                        type="text"
+                        node_ref=custom_template
+                        value=current_template
+                        placeholder=CUSTOM_TEMPLATE_PLACEHOLDER
+                        on:change=on_template_change
+                    />
+                    <p class="label text-xs" class:text-error=template_is_invalid>
+                        {t!(i18n, exercise.custom_dictionary_help)}
+                    </p>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,288 @@
+use leptos::{html, prelude::*};
+
+use crate::binary_corpus::BinaryCorpus;
+use crate::exercise::ExerciseParams;
+use crate::i18n::*;
+use crate::minimal_pairs::{
+    get_contrast_scores, get_random_minimal_pair_questions, MinimalPairAnswer, MinimalPairIndex,
+    MinimalPairQuestion,
+};
+use crate::utils::{format_word_url, get_syllables_from_pinyin};
+
+/// A component handling the minimal pair session for ShuangShuang, where two words differing
+/// only in their tones are played and the learner finds the one with the asked tones
+#[component]
+pub fn MinimalPairSession(
+    hanzi_pairs: ReadSignal<BinaryCorpus>,
+    exercise_params: ReadSignal<Option<ExerciseParams>>,
+    set_exercise_finished: WriteSignal<bool>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let params = exercise_params
+        .get_untracked()
+        .expect("exercise_params is not yet set to Some.");
+    let questions = StoredValue::new({
+        let corpus = &*hanzi_pairs.read_untracked();
+        let index = MinimalPairIndex::new(corpus);
+        get_random_minimal_pair_questions(params.exercise_size, &index, corpus)
+    });
+    let (current_idx, set_current_idx) = signal(0usize);
+    let (answers, set_answers) = signal::<Vec<(MinimalPairQuestion, MinimalPairAnswer)>>(vec![]);
+    let (last_answer, set_last_answer) = signal::<Option<MinimalPairAnswer>>(None);
+    let (show_results, set_show_results) = signal(questions.read_value().is_empty());
+    // Set while the first word plays, so that the second one follows it
+    let (second_word_queued, set_second_word_queued) = signal(false);
+    let audio_element: NodeRef<html::Audio> = NodeRef::new();
+    let current_question = Memo::new(move |_| {
+        questions
+            .read_value()
+            .get(current_idx())
+            .cloned()
+            .unwrap_or_default()
+    });
+
+    let play_word = move |word: MinimalPairAnswer| {
+        let question = current_question.get_untracked();
+        let hanzi_pair = match word {
+            MinimalPairAnswer::First => question.first,
+            MinimalPairAnswer::Second => question.second,
+        };
+        if let Some(audio) = audio_element.get_untracked() {
+            audio.set_src(&format_word_url(
+                &hanzi_pair.characters,
+                params.audio_quality,
+            ));
+            let _promise = audio.play().expect("Failed to play audio element.");
+        }
+    };
+    let play_both = move || {
+        set_second_word_queued(true);
+        play_word(MinimalPairAnswer::First);
+    };
+    let on_audio_ended = move |_| {
+        if second_word_queued.get_untracked() {
+            set_second_word_queued(false);
+            play_word(MinimalPairAnswer::Second);
+        }
+    };
+    // Each question starts by playing both words
+    Effect::new(move |_| {
+        current_idx.track();
+        if !show_results.get_untracked() {
+            play_both();
+        }
+    });
+
+    let on_answer = move |answer: MinimalPairAnswer| {
+        set_second_word_queued(false);
+        set_answers.update(|answers| answers.push((current_question.get_untracked(), answer)));
+        set_last_answer(Some(answer));
+    };
+    let on_next = move |_| {
+        set_last_answer(None);
+        if current_idx.get_untracked() + 1 < questions.read_value().len() {
+            set_current_idx.update(|idx| *idx += 1);
+        } else {
+            set_show_results(true);
+        }
+    };
+
+    view! {
+        {move || {
+            if show_results() {
+                let answers = answers();
+                let correct_answers = answers
+                    .iter()
+                    .filter(|(question, answer)| question.expected_answer() == *answer)
+                    .count();
+                view! {
+                    <div class="flex justify-center">
+                        <div class="flex flex-col">
+                            <div class="card h-full md:h-160 md:mt-10 bg-base-100 card-border border-base-300 card-md overflow-auto px-10 py-4">
+                                <div class="flex justify-center">
+                                    {t!(i18n, exercise.correct_answers)}
+                                    {format!("{correct_answers}/{}", answers.len())}
+                                </div>
+                                <div class="text-center pt-4 font-semibold">
+                                    {t!(i18n, exercise.tone_contrast_scores)}
+                                </div>
+                                <ul class="py-2">
+                                    {get_contrast_scores(&answers)
+                                        .into_iter()
+                                        .map(|(tone_contrast, score)| {
+                                            let text_class = if score.correct_answers
+                                                == score.total_answers
+                                            {
+                                                "text-success"
+                                            } else {
+                                                "text-error"
+                                            };
+                                            view! {
+                                                <li class=text_class>
+                                                    {format!(
+                                                        "{tone_contrast} => {}/{}",
+                                                        score.correct_answers,
+                                                        score.total_answers,
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                                <ul class="py-2 text-sm">
+                                    {answers
+                                        .iter()
+                                        .map(|(question, answer)| {
+                                            let text_class = if question.expected_answer() == *answer {
+                                                "text-success"
+                                            } else {
+                                                "text-error"
+                                            };
+                                            view! {
+                                                <li class=text_class>
+                                                    {format!(
+                                                        "{} ({}) / {} ({})",
+                                                        question.first.characters,
+                                                        question.first.pronounced_pinyin,
+                                                        question.second.characters,
+                                                        question.second.pronounced_pinyin,
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            </div>
+                            <div class="flex fit justify-center py-2">
+                                <button
+                                    class="btn rounded-md btn-secondary text-white"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            } else {
+                let question = current_question();
+                let asked_tones = format!(
+                    "{}-{}",
+                    question.asked_tone_pair.0,
+                    question.asked_tone_pair.1,
+                );
+                let syllables = get_syllables_from_pinyin(&question.first.pronounced_pinyin)
+                    .join(" ");
+                view! {
+                    <div class="flex h-full md:h-120 justify-center place-items-center">
+                        <div class="flex flex-col justify-center items-center gap-2">
+                            <div>
+                                <a class="badge badge-accent text-white font-semibold">
+                                    {move || questions.read_value().len() - current_idx()}
+                                </a>
+                                {t!(i18n, exercise.remaining_pairs)}
+                            </div>
+                            <p class="text-2xl pt-4">{syllables}</p>
+                            <p class="text-lg">
+                                {t!(
+                                    i18n, exercise.minimal_pair_question, tones = move || asked_tones.clone()
+                                )}
+                            </p>
+                            <audio node_ref=audio_element on:ended=on_audio_ended></audio>
+                            <div class="flex gap-2">
+                                <button
+                                    class="btn btn-neutral rounded-md"
+                                    on:click=move |_| play_word(MinimalPairAnswer::First)
+                                >
+                                    {t!(i18n, exercise.play_first_word)}
+                                </button>
+                                <button
+                                    class="btn btn-neutral rounded-md"
+                                    on:click=move |_| play_both()
+                                >
+                                    {t!(i18n, exercise.play_both_words)}
+                                </button>
+                                <button
+                                    class="btn btn-neutral rounded-md"
+                                    on:click=move |_| play_word(MinimalPairAnswer::Second)
+                                >
+                                    {t!(i18n, exercise.play_second_word)}
+                                </button>
+                            </div>
+                            {move || match last_answer() {
+                                None => {
+                                    view! {
+                                        <div class="flex gap-2 pt-2">
+                                            <button
+                                                class="btn btn-primary text-white rounded-md"
+                                                on:click=move |_| on_answer(MinimalPairAnswer::First)
+                                            >
+                                                {t!(i18n, exercise.first_word)}
+                                            </button>
+                                            <button
+                                                class="btn btn-primary text-white rounded-md"
+                                                on:click=move |_| on_answer(MinimalPairAnswer::Second)
+                                            >
+                                                {t!(i18n, exercise.second_word)}
+                                            </button>
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                                Some(answer) => {
+                                    let question = current_question();
+                                    let is_correct = question.expected_answer() == answer;
+                                    view! {
+                                        <div class="flex flex-col items-center pt-2">
+                                            {if is_correct {
+                                                view! {
+                                                    <div class="text-success font-semibold">
+                                                        {t!(i18n, exercise.correct_feedback)}
+                                                    </div>
+                                                }
+                                                    .into_any()
+                                            } else {
+                                                view! {
+                                                    <div class="text-error font-semibold">
+                                                        {t!(i18n, exercise.incorrect_feedback)}
+                                                    </div>
+                                                }
+                                                    .into_any()
+                                            }}
+                                            <div>
+                                                {format!(
+                                                    "1. {} {} · 2. {} {}",
+                                                    question.first.characters,
+                                                    question.first.pronounced_pinyin,
+                                                    question.second.characters,
+                                                    question.second.pronounced_pinyin,
+                                                )}
+                                            </div>
+                                            <button
+                                                class="btn btn-accent rounded-md text-white mt-2"
+                                                on:click=on_next
+                                            >
+                                                {t!(i18n, exercise.next_pair)}
+                                            </button>
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                            }}
+                            <div class="flex justify-center">
+                                <button
+                                    class="link text-xs mt-6"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            }
+        }}
+    }
+}
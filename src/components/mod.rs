@@ -1,7 +1,17 @@
+pub mod answer_feedback;
+pub mod audio_choices;
 pub mod context;
+pub mod dictionary_controller;
 pub mod language_controller;
+pub mod minimal_pair_session;
+pub mod noise_layer;
+pub mod pinyin_drill_session;
+pub mod pitch_contour;
+pub mod production_session;
 pub mod subtitle_with_helper;
+pub mod syllable_replay;
 pub mod test_form;
 pub mod test_session;
 pub mod theme_controller;
 pub mod touch_tone_buttons;
+pub mod voice_recorder;
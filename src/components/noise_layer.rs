@@ -0,0 +1,108 @@
+use leptos::leptos_dom::logging::console_log;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use send_wrapper::SendWrapper;
+use web_sys::{AudioBuffer, AudioContext};
+
+use crate::api::fetch_audio_buffer;
+use crate::audio::{
+    generate_pink_noise, generate_white_noise, get_noise_gain_from_snr, get_rms, normalize_rms,
+};
+use crate::exercise::{BackgroundNoise, NoiseType};
+use crate::utils::format_noise_loop_url;
+
+const NOISE_BUFFER_SECONDS: f32 = 2.0;
+// Time constant of the gain ramps, avoids clicks when the noise is gated
+const GAIN_TIME_CONSTANT: f64 = 0.05;
+
+// A component mixing background noise through WebAudio while the word audio is playing
+#[component]
+pub fn NoiseLayer(
+    background_noise: BackgroundNoise,
+    audio_playing: ReadSignal<bool>,
+) -> impl IntoView {
+    let audio_context =
+        SendWrapper::new(AudioContext::new().expect("Failed to create AudioContext."));
+    let gain_node = SendWrapper::new(
+        audio_context
+            .create_gain()
+            .expect("Failed to create noise GainNode."),
+    );
+    gain_node.gain().set_value(0.0);
+    gain_node
+        .connect_with_audio_node(&audio_context.destination())
+        .expect("Failed to connect noise GainNode to destination.");
+    // Stays at 0 until the noise buffer is ready
+    let noise_gain = RwSignal::new(0.0f32);
+
+    {
+        let audio_context = audio_context.clone();
+        let gain_node = gain_node.clone();
+        spawn_local(async move {
+            let (buffer, noise_rms) =
+                get_noise_buffer(&audio_context, background_noise.noise_type).await;
+            let source = audio_context
+                .create_buffer_source()
+                .expect("Failed to create noise AudioBufferSourceNode.");
+            source.set_buffer(Some(&buffer));
+            source.set_loop(true);
+            source
+                .connect_with_audio_node(&gain_node)
+                .expect("Failed to connect noise source to GainNode.");
+            source.start().expect("Failed to start noise source.");
+            let _promise = audio_context.resume();
+            // The layer may be gone once the loop is fetched
+            let _ = noise_gain.try_set(get_noise_gain_from_snr(background_noise.snr_db, noise_rms));
+        });
+    }
+
+    {
+        let audio_context = audio_context.clone();
+        Effect::new(move |_| {
+            let target = if audio_playing() { noise_gain() } else { 0.0 };
+            let _ = gain_node.gain().set_target_at_time(
+                target,
+                audio_context.current_time(),
+                GAIN_TIME_CONSTANT,
+            );
+        });
+    }
+
+    on_cleanup(move || {
+        let _promise = audio_context.close();
+    });
+}
+
+// Returns the buffer to loop along with its RMS, falling back to pink noise when a bundled
+// loop cannot be fetched or decoded
+async fn get_noise_buffer(
+    audio_context: &AudioContext,
+    noise_type: NoiseType,
+) -> (AudioBuffer, f32) {
+    if let Some(url) = format_noise_loop_url(noise_type) {
+        match fetch_audio_buffer(audio_context, &url).await {
+            Some(buffer) => {
+                let noise_rms = get_rms(&buffer.get_channel_data(0).unwrap_or_default());
+                return (buffer, noise_rms);
+            }
+            None => console_log(&format!(
+                "Failed to load noise loop {url}, using pink noise."
+            )),
+        }
+    }
+    let sample_rate = audio_context.sample_rate();
+    let nb_samples = (sample_rate * NOISE_BUFFER_SECONDS) as usize;
+    let mut rng = rand::rng();
+    let mut samples = match noise_type {
+        NoiseType::WhiteNoise => generate_white_noise(&mut rng, nb_samples),
+        _ => generate_pink_noise(&mut rng, nb_samples),
+    };
+    normalize_rms(&mut samples);
+    let buffer = audio_context
+        .create_buffer(1, nb_samples as u32, sample_rate)
+        .expect("Failed to create noise AudioBuffer.");
+    buffer
+        .copy_to_channel(&samples, 0)
+        .expect("Failed to copy noise samples to AudioBuffer.");
+    (buffer, 1.0)
+}
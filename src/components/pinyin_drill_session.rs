@@ -0,0 +1,238 @@
+use leptos::{html, prelude::*};
+
+use crate::binary_corpus::BinaryCorpus;
+use crate::exercise::ExerciseParams;
+use crate::i18n::*;
+use crate::pinyin_drills::{get_drill_scores, get_random_drill_questions, PinyinDrillQuestion};
+use crate::utils::format_word_url;
+
+/// A component handling the initial and final drill session for ShuangShuang, where a word is
+/// played and the learner picks the segment of one of its syllables, like zh or z
+#[component]
+pub fn PinyinDrillSession(
+    hanzi_pairs: ReadSignal<BinaryCorpus>,
+    exercise_params: ReadSignal<Option<ExerciseParams>>,
+    set_exercise_finished: WriteSignal<bool>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let params = exercise_params
+        .get_untracked()
+        .expect("exercise_params is not yet set to Some.");
+    let questions = StoredValue::new(get_random_drill_questions(
+        params.exercise_size,
+        params.drill_contrast,
+        &*hanzi_pairs.read_untracked(),
+    ));
+    let (current_idx, set_current_idx) = signal(0usize);
+    let (answers, set_answers) = signal::<Vec<(PinyinDrillQuestion, String)>>(vec![]);
+    let (last_answer, set_last_answer) = signal::<Option<String>>(None);
+    let (show_results, set_show_results) = signal(questions.read_value().is_empty());
+    let audio_element: NodeRef<html::Audio> = NodeRef::new();
+    let current_question = Memo::new(move |_| questions.read_value().get(current_idx()).cloned());
+
+    let play_word = move || {
+        let Some(question) = current_question.get_untracked() else {
+            return;
+        };
+        if let Some(audio) = audio_element.get_untracked() {
+            audio.set_src(&format_word_url(
+                &question.hanzi_pair.characters,
+                params.audio_quality,
+            ));
+            let _promise = audio.play().expect("Failed to play audio element.");
+        }
+    };
+    // Each question starts by playing its word
+    Effect::new(move |_| {
+        current_idx.track();
+        if !show_results.get_untracked() {
+            play_word();
+        }
+    });
+
+    let on_answer = move |answer: String| {
+        if let Some(question) = current_question.get_untracked() {
+            set_answers.update(|answers| answers.push((question, answer.clone())));
+            set_last_answer(Some(answer));
+        }
+    };
+    let on_next = move |_| {
+        set_last_answer(None);
+        if current_idx.get_untracked() + 1 < questions.read_value().len() {
+            set_current_idx.update(|idx| *idx += 1);
+        } else {
+            set_show_results(true);
+        }
+    };
+
+    view! {
+        {move || {
+            if show_results() {
+                let answers = answers();
+                let correct_answers = answers
+                    .iter()
+                    .filter(|(question, answer)| question.segment == answer)
+                    .count();
+                view! {
+                    <div class="flex justify-center">
+                        <div class="flex flex-col">
+                            <div class="card h-full md:h-160 md:mt-10 bg-base-100 card-border border-base-300 card-md overflow-auto px-10 py-4">
+                                <div class="flex justify-center">
+                                    {t!(i18n, exercise.correct_answers)}
+                                    {format!("{correct_answers}/{}", answers.len())}
+                                </div>
+                                <div class="text-center pt-4 font-semibold">
+                                    {t!(i18n, exercise.pinyin_contrast_scores)}
+                                </div>
+                                <ul class="py-2">
+                                    {get_drill_scores(&answers)
+                                        .into_iter()
+                                        .map(|(contrast, score)| {
+                                            let text_class = if score.correct_answers
+                                                == score.total_answers
+                                            {
+                                                "text-success"
+                                            } else {
+                                                "text-error"
+                                            };
+                                            view! {
+                                                <li class=text_class>
+                                                    {format!(
+                                                        "{contrast} => {}/{}",
+                                                        score.correct_answers,
+                                                        score.total_answers,
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                                <ul class="py-2 text-sm">
+                                    {answers
+                                        .iter()
+                                        .map(|(question, answer)| {
+                                            let text_class = if question.segment == answer {
+                                                "text-success"
+                                            } else {
+                                                "text-error"
+                                            };
+                                            view! {
+                                                <li class=text_class>
+                                                    {format!(
+                                                        "{} ({}) {} => {answer}",
+                                                        question.hanzi_pair.characters,
+                                                        question.hanzi_pair.pronounced_pinyin,
+                                                        question.blanked_pinyin(),
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            </div>
+                            <div class="flex fit justify-center py-2">
+                                <button
+                                    class="btn rounded-md btn-secondary text-white"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            } else {
+                let Some(question) = current_question() else {
+                    return ().into_any();
+                };
+                let segments = question.contrast.segments();
+                view! {
+                    <div class="flex h-full md:h-120 justify-center place-items-center">
+                        <div class="flex flex-col justify-center items-center gap-2">
+                            <div>
+                                <a class="badge badge-accent text-white font-semibold">
+                                    {move || questions.read_value().len() - current_idx()}
+                                </a>
+                                {t!(i18n, exercise.remaining_pairs)}
+                            </div>
+                            <p class="text-2xl pt-4">{question.blanked_pinyin()}</p>
+                            <p class="text-lg">{t!(i18n, exercise.pinyin_drill_question)}</p>
+                            <audio node_ref=audio_element></audio>
+                            <button class="btn btn-neutral rounded-md" on:click=move |_| play_word()>
+                                {t!(i18n, exercise.replay_audio)}
+                            </button>
+                            {move || match last_answer() {
+                                None => {
+                                    view! {
+                                        <div class="flex gap-2 pt-2">
+                                            {segments
+                                                .iter()
+                                                .map(|&segment| {
+                                                    view! {
+                                                        <button
+                                                            class="btn btn-primary text-white rounded-md"
+                                                            on:click=move |_| on_answer(segment.to_string())
+                                                        >
+                                                            {segment}
+                                                        </button>
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                                Some(answer) => {
+                                    let is_correct = question.segment == answer;
+                                    view! {
+                                        <div class="flex flex-col items-center pt-2">
+                                            {if is_correct {
+                                                view! {
+                                                    <div class="text-success font-semibold">
+                                                        {t!(i18n, exercise.correct_feedback)}
+                                                    </div>
+                                                }
+                                                    .into_any()
+                                            } else {
+                                                view! {
+                                                    <div class="text-error font-semibold">
+                                                        {t!(i18n, exercise.incorrect_feedback)}
+                                                    </div>
+                                                }
+                                                    .into_any()
+                                            }}
+                                            <div>
+                                                {format!(
+                                                    "{} {}",
+                                                    question.hanzi_pair.characters,
+                                                    question.hanzi_pair.pronounced_pinyin,
+                                                )}
+                                            </div>
+                                            <button
+                                                class="btn btn-accent rounded-md text-white mt-2"
+                                                on:click=on_next
+                                            >
+                                                {t!(i18n, exercise.next_pair)}
+                                            </button>
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                            }}
+                            <div class="flex justify-center">
+                                <button
+                                    class="link text-xs mt-6"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            }
+        }}
+    }
+}
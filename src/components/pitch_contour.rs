@@ -0,0 +1,120 @@
+use leptos::prelude::*;
+
+use crate::api::fetch_audio_samples;
+use crate::exercise::Tone;
+use crate::i18n::*;
+use crate::pitch::{
+    get_chao_contour, get_ideal_tone_pair_contour, get_pitch_contour, ContourPoint,
+    ANALYSIS_SAMPLE_RATE,
+};
+
+const SVG_WIDTH: f32 = 200.0;
+const SVG_HEIGHT: f32 = 100.0;
+const SVG_PADDING: f32 = 10.0;
+
+fn get_svg_x(time: f32) -> f32 {
+    SVG_PADDING + time * (SVG_WIDTH - 2.0 * SVG_PADDING)
+}
+
+// Chao level 5 is at the top of the drawing, level 1 at the bottom
+fn get_svg_y(level: f32) -> f32 {
+    SVG_HEIGHT - SVG_PADDING - (level - 1.0) / 4.0 * (SVG_HEIGHT - 2.0 * SVG_PADDING)
+}
+
+fn format_svg_points(points: &[ContourPoint]) -> String {
+    points
+        .iter()
+        .map(|point| format!("{:.1},{:.1}", get_svg_x(point.time), get_svg_y(point.level)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// A component fetching a word recording and drawing its pitch contour over its ideal tone shapes
+#[component]
+pub fn PitchContour(audio_url: String, tone_pair: (Tone, Tone)) -> impl IntoView {
+    let chao_contour = LocalResource::new(move || {
+        let audio_url = audio_url.clone();
+        async move {
+            fetch_audio_samples(&audio_url, ANALYSIS_SAMPLE_RATE)
+                .await
+                .map(|samples| get_chao_contour(&get_pitch_contour(&samples, ANALYSIS_SAMPLE_RATE)))
+                .unwrap_or_default()
+        }
+    });
+
+    view! {
+        <ContourChart
+            chao_contour=Signal::derive(move || chao_contour.get().unwrap_or_default())
+            tone_pair
+        />
+    }
+}
+
+// A component drawing a pitch contour over the ideal shapes of a tone pair
+#[component]
+pub fn ContourChart(
+    chao_contour: Signal<Vec<Vec<ContourPoint>>>,
+    tone_pair: (Tone, Tone),
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let view_box = format!("0 0 {SVG_WIDTH} {SVG_HEIGHT}");
+
+    view! {
+        <figure class="py-2">
+            <svg viewBox=view_box class="w-full max-w-xs">
+                {(1..=5)
+                    .map(|level| {
+                        let y = get_svg_y(level as f32);
+                        view! {
+                            <line
+                                x1=SVG_PADDING
+                                x2=SVG_WIDTH - SVG_PADDING
+                                y1=y
+                                y2=y
+                                class="stroke-base-300"
+                                stroke-width="0.5"
+                            />
+                        }
+                    })
+                    .collect_view()}
+                {get_ideal_tone_pair_contour(tone_pair)
+                    .iter()
+                    .map(|points| {
+                        view! {
+                            <polyline
+                                points=format_svg_points(points)
+                                fill="none"
+                                class="stroke-secondary"
+                                stroke-width="3"
+                                stroke-dasharray="4 3"
+                                stroke-linecap="round"
+                            />
+                        }
+                    })
+                    .collect_view()}
+                {move || {
+                    chao_contour
+                        .get()
+                        .iter()
+                        .map(|points| {
+                            view! {
+                                <polyline
+                                    points=format_svg_points(points)
+                                    fill="none"
+                                    class="stroke-primary"
+                                    stroke-width="2"
+                                    stroke-linejoin="round"
+                                />
+                            }
+                        })
+                        .collect_view()
+                }}
+            </svg>
+            <figcaption class="text-xs">
+                <span class="text-secondary">{t!(i18n, exercise.pitch_contour_expected)}</span>
+                " / "
+                <span class="text-primary">{t!(i18n, exercise.pitch_contour_recording)}</span>
+            </figcaption>
+        </figure>
+    }
+}
@@ -0,0 +1,230 @@
+use leptos::prelude::*;
+
+use crate::binary_corpus::BinaryCorpus;
+use crate::components::pitch_contour::ContourChart;
+use crate::components::syllable_replay::SyllableReplay;
+use crate::components::voice_recorder::VoiceRecorder;
+use crate::corpus::HanziPairSource;
+use crate::exercise::{ExerciseParams, ExerciseSummary, ExerciseType, ShuangElement, Tone};
+use crate::i18n::*;
+use crate::pitch::{get_chao_contour, get_pitch_contour, ContourPoint, ANALYSIS_SAMPLE_RATE};
+use crate::tone_analysis::{analyze_contour_tones, grade_produced_tones};
+use crate::utils::{format_word_url, get_random_hanzi_pairs_idxs};
+
+fn format_tones(tones: &[Tone]) -> String {
+    tones.iter().map(|tone| tone.to_string()).collect()
+}
+
+/// A component handling the pronunciation session for ShuangShuang, where the learner records
+/// each pair and the detected tones are graded
+#[component]
+pub fn ProductionSession(
+    hanzi_pairs: ReadSignal<BinaryCorpus>,
+    exercise_params: ReadSignal<Option<ExerciseParams>>,
+    set_exercise_finished: WriteSignal<bool>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let params = exercise_params
+        .get_untracked()
+        .expect("exercise_params is not yet set to Some.");
+    let random_idxs = StoredValue::new(get_random_hanzi_pairs_idxs(
+        params.exercise_size,
+        &*hanzi_pairs.read_untracked(),
+        params.shuffle_mode,
+    ));
+    let (current_random_idx, set_current_random_idx) = signal(0usize);
+    let (shuang_elements, set_shuang_elements) = signal::<Vec<ShuangElement>>(vec![]);
+    let (produced_tones, set_produced_tones) = signal::<Option<Vec<Tone>>>(None);
+    let (produced_contour, set_produced_contour) = signal::<Vec<Vec<ContourPoint>>>(vec![]);
+    let (show_results, set_show_results) = signal(false);
+    let current_hanzi_pair = Memo::new(move |_| {
+        let c_idx = random_idxs.read_value()[current_random_idx()];
+        hanzi_pairs.read().hanzi_pair(c_idx)
+    });
+
+    let on_recorded = Callback::new(move |samples: Vec<f32>| {
+        let contour = get_pitch_contour(&samples, ANALYSIS_SAMPLE_RATE);
+        set_produced_tones(Some(analyze_contour_tones(&contour, 2)));
+        set_produced_contour(get_chao_contour(&contour));
+    });
+
+    let on_next = move |_| {
+        let tones = produced_tones().unwrap_or_default();
+        let hanzi_pair = current_hanzi_pair();
+        let is_correct = grade_produced_tones(&tones, hanzi_pair.pronounced_tone_pair)
+            .iter()
+            .all(|correct| *correct);
+        set_shuang_elements.update(|v| {
+            v.push(ShuangElement {
+                hanzi_pair,
+                user_answer: format_tones(&tones),
+                is_correct,
+                first_answer: None,
+                attempts: 1,
+                exercise_type: ExerciseType::Production,
+            })
+        });
+        set_produced_tones(None);
+        set_produced_contour(vec![]);
+        if current_random_idx() < random_idxs.read_value().len() - 1 {
+            set_current_random_idx.update(|idx| *idx += 1);
+        } else {
+            set_show_results(true);
+        }
+    };
+
+    view! {
+        {move || {
+            if show_results() {
+                let exercise_summary = ExerciseSummary::from(shuang_elements());
+                let rounded_percentage = (exercise_summary.get_correct_percentage() * 100.0)
+                    .round() / 100.0;
+                view! {
+                    <div class="flex justify-center">
+                        <div class="flex flex-col">
+                            <div class="card h-full md:h-160 md:mt-10 bg-base-100 card-border border-base-300 card-md overflow-auto px-10 py-4">
+                                <div class="flex justify-center">
+                                    {t!(i18n, exercise.correct_answers)}
+                                    {exercise_summary.correct_answers}
+                                </div>
+                                <div class="flex justify-center">
+                                    {t!(i18n, exercise.correct_percentage)}
+                                    {rounded_percentage}
+                                    "%"
+                                </div>
+                                <ul class="py-4">
+                                    {exercise_summary
+                                        .test_elements
+                                        .iter()
+                                        .map(|elem| {
+                                            let text_class = if elem.is_correct {
+                                                "text-success"
+                                            } else {
+                                                "text-error"
+                                            };
+                                            view! {
+                                                <li class=text_class>
+                                                    {format!(
+                                                        "{} ({}): {} / {}",
+                                                        elem.hanzi_pair.characters,
+                                                        elem.hanzi_pair.pronounced_pinyin,
+                                                        format_tones(
+                                                            &[
+                                                                elem.hanzi_pair.pronounced_tone_pair.0,
+                                                                elem.hanzi_pair.pronounced_tone_pair.1,
+                                                            ],
+                                                        ),
+                                                        elem.user_answer,
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                                <p class="text-xs">{t!(i18n, exercise.production_results_legend)}</p>
+                            </div>
+                            <div class="flex fit justify-center py-2">
+                                <button
+                                    class="btn rounded-md btn-secondary text-white"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            } else {
+                let hanzi_pair = current_hanzi_pair();
+                let tone_pair = hanzi_pair.pronounced_tone_pair;
+                view! {
+                    <div class="flex h-full md:h-120 justify-center place-items-center">
+                        <div class="flex flex-col justify-center items-center">
+                            <div>
+                                <a class="badge badge-accent text-white font-semibold">
+                                    {random_idxs.read_value().len() - current_random_idx()}
+                                </a>
+                                {t!(i18n, exercise.remaining_pairs)}
+                            </div>
+                            <p class="text-4xl pt-4">{hanzi_pair.characters.clone()}</p>
+                            <p class="text-lg pb-2">{hanzi_pair.pronounced_pinyin.clone()}</p>
+                            <p class="label pb-2">{t!(i18n, exercise.production_instructions)}</p>
+                            <VoiceRecorder on_recorded />
+                            {move || {
+                                produced_tones()
+                                    .map(|tones| {
+                                        let grades = grade_produced_tones(&tones, tone_pair);
+                                        let audio_url = format_word_url(
+                                            &current_hanzi_pair().characters,
+                                            params.audio_quality,
+                                        );
+                                        view! {
+                                            <div class="flex flex-col items-center pt-4">
+                                                <div>
+                                                    {t!(i18n, exercise.detected_tones)}
+                                                    {[tone_pair.0, tone_pair.1]
+                                                        .iter()
+                                                        .zip(grades.iter())
+                                                        .enumerate()
+                                                        .map(|(i, (expected, correct))| {
+                                                            let detected = tones
+                                                                .get(i)
+                                                                .map(|tone| tone.to_string())
+                                                                .unwrap_or("?".to_string());
+                                                            if *correct {
+                                                                view! {
+                                                                    <span class="text-success px-1">{detected}</span>
+                                                                }
+                                                                    .into_any()
+                                                            } else {
+                                                                view! {
+                                                                    <span class="text-error px-1">
+                                                                        {format!("{detected} ≠ {expected}")}
+                                                                    </span>
+                                                                }
+                                                                    .into_any()
+                                                            }
+                                                        })
+                                                        .collect_view()}
+                                                </div>
+                                                <ContourChart
+                                                    chao_contour=produced_contour.into()
+                                                    tone_pair
+                                                />
+                                                <label class="label">
+                                                    {t!(i18n, exercise.model_recording)}
+                                                </label>
+                                                <audio controls>
+                                                    <source type="audio/mpeg" src=audio_url.clone() />
+                                                </audio>
+                                                <SyllableReplay
+                                                    audio_url
+                                                    pronounced_pinyin=current_hanzi_pair().pronounced_pinyin
+                                                />
+                                                <button
+                                                    class="btn btn-accent rounded-md text-white mt-2"
+                                                    on:click=on_next
+                                                >
+                                                    {t!(i18n, exercise.next_pair)}
+                                                </button>
+                                            </div>
+                                        }
+                                    })
+                            }}
+                            <div class="flex justify-center">
+                                <button
+                                    class="link text-xs mt-6"
+                                    on:click=move |_| { set_exercise_finished(true) }
+                                >
+                                    {t!(i18n, exercise.return_home)}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                }
+                    .into_any()
+            }
+        }}
+    }
+}
@@ -0,0 +1,131 @@
+use leptos::leptos_dom::logging::console_log;
+use leptos::prelude::*;
+use send_wrapper::SendWrapper;
+use web_sys::{AudioContext, HtmlAudioElement};
+
+use crate::api::fetch_audio_samples;
+use crate::components::touch_tone_buttons::get_tone_preview_url;
+use crate::i18n::*;
+use crate::pitch::{get_pitch_contour, ANALYSIS_SAMPLE_RATE};
+use crate::tone_analysis::{find_syllable_boundary, MIN_BOUNDARY_CONFIDENCE};
+use crate::utils::{get_syllables_from_pinyin, get_tones_from_pinyin};
+
+// A word recording along with the time its second syllable starts at
+#[derive(Clone)]
+struct SegmentedRecording {
+    samples: Vec<f32>,
+    boundary: f32,
+}
+
+fn play_slice(audio_context: &AudioContext, samples: &[f32], start: f32, end: f32) {
+    let buffer = audio_context
+        .create_buffer(1, samples.len() as u32, ANALYSIS_SAMPLE_RATE)
+        .expect("Failed to create syllable AudioBuffer.");
+    buffer
+        .copy_to_channel(samples, 0)
+        .expect("Failed to copy syllable samples to AudioBuffer.");
+    let source = audio_context
+        .create_buffer_source()
+        .expect("Failed to create syllable AudioBufferSourceNode.");
+    source.set_buffer(Some(&buffer));
+    source
+        .connect_with_audio_node(&audio_context.destination())
+        .expect("Failed to connect syllable source to destination.");
+    let _promise = audio_context.resume();
+    source
+        .start_with_when_and_grain_offset_and_grain_duration(
+            0.0,
+            start as f64,
+            (end - start) as f64,
+        )
+        .expect("Failed to start syllable source.");
+}
+
+// A component playing each syllable of a word on its own, slicing the word recording at the
+// detected boundary, or using the isolated syllable recordings when the boundary is uncertain.
+// There are none for the neutral tone, so its syllable cannot be played without a boundary.
+#[component]
+pub fn SyllableReplay(audio_url: String, pronounced_pinyin: String) -> impl IntoView {
+    let i18n = use_i18n();
+    let syllables = get_syllables_from_pinyin(&pronounced_pinyin);
+    let tones = get_tones_from_pinyin(&pronounced_pinyin);
+    let audio_context = StoredValue::new(None::<SendWrapper<AudioContext>>);
+    let segmented_recording = LocalResource::new(move || {
+        let audio_url = audio_url.clone();
+        async move {
+            let samples = fetch_audio_samples(&audio_url, ANALYSIS_SAMPLE_RATE).await?;
+            let contour = get_pitch_contour(&samples, ANALYSIS_SAMPLE_RATE);
+            match find_syllable_boundary(&contour) {
+                Some(boundary) if boundary.confidence >= MIN_BOUNDARY_CONFIDENCE => {
+                    Some(SegmentedRecording {
+                        samples,
+                        boundary: boundary.time,
+                    })
+                }
+                boundary => {
+                    console_log(&format!(
+                        "Uncertain syllable boundary for {audio_url}: {boundary:?}"
+                    ));
+                    None
+                }
+            }
+        }
+    });
+
+    let is_segmented = move || matches!(segmented_recording.get(), Some(Some(_)));
+    let play_syllable = move |syllable_idx: usize, fallback_url: Option<String>| {
+        if let Some(Some(recording)) = segmented_recording.get() {
+            if audio_context.with_value(Option::is_none) {
+                let context = AudioContext::new().expect("Failed to create AudioContext.");
+                audio_context.set_value(Some(SendWrapper::new(context)));
+            }
+            let duration = recording.samples.len() as f32 / ANALYSIS_SAMPLE_RATE;
+            let (start, end) = if syllable_idx == 0 {
+                (0.0, recording.boundary)
+            } else {
+                (recording.boundary, duration)
+            };
+            audio_context.with_value(|context| {
+                if let Some(context) = context {
+                    play_slice(context, &recording.samples, start, end);
+                }
+            });
+        } else if let Some(fallback_url) = fallback_url {
+            let audio =
+                HtmlAudioElement::new_with_src(&fallback_url).expect("Failed to create <audio>.");
+            let _promise = audio.play().expect("Failed to play syllable <audio>.");
+        }
+    };
+
+    on_cleanup(move || {
+        if let Some(context) = audio_context.get_value() {
+            let _promise = context.close();
+        }
+    });
+
+    view! {
+        <div class="flex flex-wrap gap-2 py-2">
+            {syllables
+                .into_iter()
+                .zip(tones)
+                .take(2)
+                .enumerate()
+                .map(|(syllable_idx, (syllable, tone))| {
+                    let fallback_url = get_tone_preview_url(&syllable, tone);
+                    let has_fallback = fallback_url.is_some();
+                    view! {
+                        <button
+                            class="btn btn-sm btn-outline rounded-md"
+                            disabled=move || !has_fallback && !is_segmented()
+                            on:click=move |_| play_syllable(syllable_idx, fallback_url.clone())
+                        >
+                            {t!(i18n, exercise.play_syllable)}
+                            " "
+                            {syllable}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
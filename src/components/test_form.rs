@@ -6,9 +6,17 @@ use std::str::FromStr;
 
 use crate::components::subtitle_with_helper::SubtitleWithHelper;
 use crate::exercise::{
-    AudioQuality, ExerciseDifficulty, ExerciseParams, ExerciseType, InputStyle, ShuffleMode,
+    AudioQuality, BackgroundNoise, ExerciseDifficulty, ExerciseMix, ExerciseParams, ExerciseType,
+    GameMode, InputStyle, NoiseType, ShuffleMode,
 };
 use crate::i18n::*;
+use crate::pinyin_drills::PinyinContrast;
+
+/// Value of the drill contrast radio asking every contrast
+const ALL_DRILL_CONTRASTS: &str = "all";
+/// Values of the game mode radios whose limit is picked apart
+const SURVIVAL_GAME_MODE: &str = "survival";
+const TIME_ATTACK_GAME_MODE: &str = "time_attack";
 
 /// A component to enter exercise parameters for ShuangShuang
 #[component]
@@ -20,12 +28,96 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
     let exercise_type = RwSignal::new(ExerciseType::default().to_string());
     let input_style = RwSignal::new(InputStyle::default().to_string());
     let timer_on = RwSignal::new(false);
+    let accept_alternative_readings = RwSignal::new(false);
+    let exact_hanzi_match = RwSignal::new(false);
+    let drill_contrast = RwSignal::new(ALL_DRILL_CONTRASTS.to_string());
+    let exercise_mix = ExerciseMix::default();
+    let mix_tone_only = RwSignal::new(exercise_mix.tone_only.to_string());
+    let mix_no_tone_pinyin = RwSignal::new(exercise_mix.no_tone_pinyin.to_string());
+    let mix_pinyin = RwSignal::new(exercise_mix.pinyin.to_string());
+    let show_meaning_after_answer = RwSignal::new(false);
+    let immediate_feedback = RwSignal::new(false);
+    let max_attempts = RwSignal::new(1.to_string());
+    let game_mode = RwSignal::new(GameMode::default().to_string());
+    let max_mistakes = RwSignal::new(3.to_string());
+    let time_limit_secs = RwSignal::new(60.to_string());
     let audio_quality = RwSignal::new(AudioQuality::default().to_string());
+    let noise_type = RwSignal::new(NoiseType::default().to_string());
+    let (noise_snr, set_noise_snr) = signal(BackgroundNoise::default().snr_db);
     let nb_elements_element: NodeRef<html::Input> = NodeRef::new();
+    let noise_snr_element: NodeRef<html::Input> = NodeRef::new();
     let nb_elements_min = 5;
     let nb_elements_max = 40;
     let nb_elements_step = 5;
     let nb_audio_retries = 3;
+    let max_attempts_choices: [u32; 4] = [1, 2, 3, 5];
+    let max_mistakes_choices: [u32; 3] = [1, 3, 5];
+    let time_limit_choices: [u32; 2] = [60, 120];
+    let mix_weight_max = 10;
+    let noise_snr_min = 0;
+    let noise_snr_max = 30;
+    let noise_snr_step = 5;
+
+    // The production, minimal pair and drill exercises have their own session, of a fixed size
+    let game_mode_hidden = move || {
+        ExerciseDifficulty::from_str(&exercise_difficulty()).unwrap() == ExerciseDifficulty::Custom
+            && matches!(
+                ExerciseType::from_str(&exercise_type()).unwrap(),
+                ExerciseType::Production | ExerciseType::MinimalPair | ExerciseType::PinyinDrill
+            )
+    };
+    // Buttons only give tones, the production, minimal pair and drill exercises have their own
+    // input, recordings can only be picked and characters only typed
+    let allowed_input_styles = Memo::new(move |_| {
+        let exercise_type = ExerciseType::from_str(&exercise_type()).unwrap();
+        let mut input_styles: Vec<InputStyle> = vec![];
+        if !matches!(
+            exercise_type,
+            ExerciseType::Production
+                | ExerciseType::MinimalPair
+                | ExerciseType::ReverseAudio
+                | ExerciseType::PinyinDrill
+        ) {
+            input_styles.push(InputStyle::Keyboard);
+        }
+        if matches!(exercise_type, ExerciseType::ToneOnly | ExerciseType::Sandhi) {
+            input_styles.push(InputStyle::Touch);
+        }
+        if !matches!(
+            exercise_type,
+            ExerciseType::Production
+                | ExerciseType::MinimalPair
+                | ExerciseType::Hanzi
+                | ExerciseType::PinyinDrill
+        ) {
+            input_styles.push(InputStyle::MultipleChoice);
+        }
+        input_styles
+    });
+    let is_input_style_disabled =
+        move |input_style: InputStyle| !allowed_input_styles.read().contains(&input_style);
+    // The reverse exercise is only answered by picking a recording, and a style the exercise
+    // does not allow falls back to the keyboard
+    Effect::new(move |_| {
+        let current_style = InputStyle::from_str(&input_style.get_untracked()).unwrap();
+        if ExerciseType::from_str(&exercise_type()).unwrap() == ExerciseType::ReverseAudio {
+            input_style.set(InputStyle::MultipleChoice.to_string());
+        } else if current_style != InputStyle::Keyboard && is_input_style_disabled(current_style) {
+            input_style.set(InputStyle::Keyboard.to_string());
+        }
+    });
+    let selected_game_mode = move || {
+        if game_mode_hidden() {
+            return GameMode::Standard;
+        }
+        let game_mode = game_mode.get_untracked();
+        let limit = match game_mode.as_str() {
+            SURVIVAL_GAME_MODE => max_mistakes.get_untracked(),
+            TIME_ATTACK_GAME_MODE => time_limit_secs.get_untracked(),
+            _ => return GameMode::Standard,
+        };
+        GameMode::from_str(&format!("{game_mode}_{limit}")).unwrap()
+    };
 
     let on_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
@@ -38,6 +130,13 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
                 .parse()
                 .unwrap();
             set_nb_elements(exercise_size);
+            let snr_db: i32 = noise_snr_element
+                .get()
+                .expect("<noise_snr_element> input should be mounted.")
+                .value()
+                .parse()
+                .unwrap();
+            set_noise_snr(snr_db);
             set_exercise_params(Some(ExerciseParams {
                 exercise_size,
                 exercise_type: ExerciseType::from_str(&exercise_type()).unwrap(),
@@ -46,9 +145,33 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
                 timer_on: timer_on(),
                 audio_quality: AudioQuality::from_str(&audio_quality()).unwrap(),
                 audio_retries: nb_audio_retries,
+                background_noise: BackgroundNoise {
+                    noise_type: NoiseType::from_str(&noise_type()).unwrap(),
+                    snr_db,
+                },
+                accept_alternative_readings: accept_alternative_readings(),
+                exact_hanzi_match: exact_hanzi_match(),
+                drill_contrast: PinyinContrast::from_str(&drill_contrast()).ok(),
+                // Cleared or invalid proportions count as 0
+                exercise_mix: ExerciseMix {
+                    tone_only: mix_tone_only().parse().unwrap_or_default(),
+                    no_tone_pinyin: mix_no_tone_pinyin().parse().unwrap_or_default(),
+                    pinyin: mix_pinyin().parse().unwrap_or_default(),
+                },
+                show_meaning_after_answer: show_meaning_after_answer(),
+                immediate_feedback: immediate_feedback(),
+                max_attempts: max_attempts().parse().unwrap(),
+                game_mode: selected_game_mode(),
+                difficulty,
             }));
         } else {
-            let exercise_params = ExerciseParams::from(difficulty);
+            let exercise_params = ExerciseParams {
+                show_meaning_after_answer: show_meaning_after_answer(),
+                immediate_feedback: immediate_feedback(),
+                max_attempts: max_attempts().parse().unwrap(),
+                game_mode: selected_game_mode(),
+                ..ExerciseParams::from(difficulty)
+            };
             set_exercise_params(Some(exercise_params));
         }
     };
@@ -252,70 +375,187 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
                                                     bind:group=exercise_type
                                                 />
                                             </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_production)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::Production.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_minimal_pair)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::MinimalPair.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_reverse_audio)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::ReverseAudio.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_hanzi)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::Hanzi.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_sandhi)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::Sandhi.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_pinyin_drill)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::PinyinDrill.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.exercise_type_mixed)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ExerciseType::Mixed.to_string()
+                                                    bind:group=exercise_type
+                                                />
+                                            </label>
                                         </div>
                                     </fieldset>
                                 </div>
-                                <div>
+                                <div class:hidden=move || {
+                                    exercise_type() != ExerciseType::Mixed.to_string()
+                                }>
                                     <fieldset>
                                         <legend class=subtitle_font_class>
-                                            {t!(i18n, form.select_input_type)}
+                                            {t!(i18n, form.select_exercise_mix)}
                                         </legend>
                                         <div class=fieldset_class>
-
-                                            {move || {
-                                                if let ExerciseType::ToneOnly = ExerciseType::from_str(
-                                                        &exercise_type(),
-                                                    )
-                                                    .unwrap()
-                                                {
+                                            {[
+                                                (t!(i18n, form.exercise_type_tone_only).into_any(), mix_tone_only),
+                                                (
+                                                    t!(i18n, form.exercise_type_no_tone_pinyin).into_any(),
+                                                    mix_no_tone_pinyin,
+                                                ),
+                                                (t!(i18n, form.exercise_type_pinyin).into_any(), mix_pinyin),
+                                            ]
+                                                .into_iter()
+                                                .map(|(type_label, weight)| {
                                                     view! {
                                                         <label class=label_class>
-                                                            {t!(i18n, form.input_type_keyboard)}
-                                                            <input
-                                                                type="radio"
-                                                                class=radio_class
-                                                                value=InputStyle::Keyboard.to_string()
-                                                                bind:group=input_style
-                                                            />
-                                                        </label>
-                                                        <label class=label_class>
-                                                            {t!(i18n, form.input_type_buttons)}
+                                                            {type_label}
                                                             <input
-                                                                type="radio"
-                                                                class=radio_class
-                                                                value=InputStyle::Touch.to_string()
-                                                                bind:group=input_style
+                                                                type="number"
+                                                                class="input input-sm w-16 ml-2"
+                                                                min=0
+                                                                max=mix_weight_max
+                                                                bind:value=weight
                                                             />
                                                         </label>
                                                     }
-                                                        .into_any()
-                                                } else {
-                                                    input_style.set(InputStyle::Keyboard.to_string());
+                                                })
+                                                .collect_view()}
+                                        </div>
+                                    </fieldset>
+                                </div>
+                                <div class:hidden=move || {
+                                    exercise_type() != ExerciseType::PinyinDrill.to_string()
+                                }>
+                                    <fieldset>
+                                        <legend class=subtitle_font_class>
+                                            {t!(i18n, form.select_drill_contrast)}
+                                        </legend>
+                                        <div class=fieldset_class>
+                                            <label class=label_class>
+                                                {t!(i18n, form.all_drill_contrasts)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=ALL_DRILL_CONTRASTS
+                                                    bind:group=drill_contrast
+                                                />
+                                            </label>
+                                            {PinyinContrast::ALL
+                                                .into_iter()
+                                                .map(|contrast| {
                                                     view! {
                                                         <label class=label_class>
-                                                            {t!(i18n, form.input_type_keyboard)}
+                                                            {contrast.label()}
                                                             <input
-                                                                disabled
                                                                 type="radio"
                                                                 class=radio_class
-                                                                value=InputStyle::Keyboard.to_string()
-                                                                bind:group=input_style
-                                                            />
-                                                        </label>
-                                                        <label class=label_class>
-                                                            {t!(i18n, form.input_type_buttons)}
-                                                            <input
-                                                                disabled
-                                                                type="radio"
-                                                                class=radio_class
-                                                                value=InputStyle::Touch.to_string()
-                                                                bind:group=input_style
+                                                                value=contrast.to_string()
+                                                                bind:group=drill_contrast
                                                             />
                                                         </label>
                                                     }
-                                                        .into_any()
-                                                }
-                                            }}
+                                                })
+                                                .collect_view()}
+                                        </div>
+                                    </fieldset>
+                                </div>
+                                <div>
+                                    <fieldset>
+                                        <legend class=subtitle_font_class>
+                                            {t!(i18n, form.select_input_type)}
+                                        </legend>
+                                        <div class=fieldset_class>
+                                            <label class=label_class>
+                                                {t!(i18n, form.input_type_keyboard)}
+                                                <input
+                                                    disabled=move || {
+                                                        is_input_style_disabled(InputStyle::Keyboard)
+                                                    }
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=InputStyle::Keyboard.to_string()
+                                                    bind:group=input_style
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.input_type_buttons)}
+                                                <input
+                                                    disabled=move || {
+                                                        is_input_style_disabled(InputStyle::Touch)
+                                                    }
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=InputStyle::Touch.to_string()
+                                                    bind:group=input_style
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.input_type_multiple_choice)}
+                                                <input
+                                                    disabled=move || {
+                                                        is_input_style_disabled(InputStyle::MultipleChoice)
+                                                            || ExerciseType::from_str(&exercise_type())
+                                                                .unwrap() == ExerciseType::ReverseAudio
+                                                    }
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=InputStyle::MultipleChoice.to_string()
+                                                    bind:group=input_style
+                                                />
+                                            </label>
                                         </div>
                                     </fieldset>
                                 </div>
@@ -399,6 +639,90 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
                                         </div>
                                     </fieldset>
                                 </div>
+                                <div>
+                                    <fieldset>
+                                        <SubtitleWithHelper
+                                            subtitle=t_string!(i18n, form.select_background_noise)
+                                                .to_string()
+                                            helper_title=t_string!(
+                                                i18n, form.select_background_noise_helper
+                                            )
+                                                .to_string()
+                                            helper_desc=move || {
+                                                view! {
+                                                    {t!(i18n, form.select_background_noise_helper_desc)}
+                                                }
+                                            }
+                                        />
+                                        <div class=fieldset_class>
+                                            <label class=label_class>
+                                                {t!(i18n, form.noise_silence)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=NoiseType::Silence.to_string()
+                                                    bind:group=noise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.noise_white)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=NoiseType::WhiteNoise.to_string()
+                                                    bind:group=noise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.noise_pink)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=NoiseType::PinkNoise.to_string()
+                                                    bind:group=noise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.noise_cafe)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=NoiseType::CafeLoop.to_string()
+                                                    bind:group=noise_type
+                                                />
+                                            </label>
+                                            <label class=label_class>
+                                                {t!(i18n, form.noise_street)}
+                                                <input
+                                                    type="radio"
+                                                    class=radio_class
+                                                    value=NoiseType::StreetLoop.to_string()
+                                                    bind:group=noise_type
+                                                />
+                                            </label>
+                                        </div>
+                                    </fieldset>
+                                    <div class="w-full justify-items-center pb-2">
+                                        <label for="noise_snr" class="text-xs">
+                                            {t!(i18n, form.noise_snr)}
+                                        </label>
+                                        <input
+                                            type="range"
+                                            class="w-full range range-sm range-primary [--range-thumb:white]"
+                                            min=noise_snr_min
+                                            max=noise_snr_max
+                                            step=noise_snr_step
+                                            value=noise_snr
+                                            node_ref=noise_snr_element
+                                        />
+                                        <div class="flex justify-between px-2.5 mt-1 text-xs">
+                                            {(noise_snr_min..noise_snr_max + 1)
+                                                .step_by(noise_snr_step)
+                                                .map(|snr_step| view! { <span>{snr_step}</span> })
+                                                .collect_view()}
+                                        </div>
+                                    </div>
+                                </div>
                                 <div>
                                     <fieldset>
                                         <legend class=subtitle_font_class>
@@ -413,6 +737,174 @@ pub fn TestForm(set_exercise_params: WriteSignal<Option<ExerciseParams>>) -> imp
                                         </div>
                                     </fieldset>
                                 </div>
+                                <div>
+                                    <fieldset>
+                                        <legend class=subtitle_font_class>
+                                            {t!(i18n, form.toggle_alternative_readings_option)}
+                                        </legend>
+                                        <div class=fieldset_class>
+                                            <input
+                                                type="checkbox"
+                                                class="toggle toggle-primary toggle-md"
+                                                bind:checked=accept_alternative_readings
+                                            />
+                                        </div>
+                                    </fieldset>
+                                </div>
+                                <div>
+                                    <fieldset>
+                                        <legend class=subtitle_font_class>
+                                            {t!(i18n, form.toggle_exact_hanzi_match_option)}
+                                        </legend>
+                                        <div class=fieldset_class>
+                                            <input
+                                                type="checkbox"
+                                                class="toggle toggle-primary toggle-md"
+                                                bind:checked=exact_hanzi_match
+                                            />
+                                        </div>
+                                    </fieldset>
+                                </div>
+                            </div>
+                            // Not difficulty settings, so they are offered for every difficulty
+                            <div class:hidden=game_mode_hidden>
+                                <fieldset>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.select_game_mode)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        <label class=label_class>
+                                            {t!(i18n, form.game_mode_standard)}
+                                            <input
+                                                type="radio"
+                                                class=radio_class
+                                                value=GameMode::Standard.to_string()
+                                                bind:group=game_mode
+                                            />
+                                        </label>
+                                        <label class=label_class>
+                                            {t!(i18n, form.game_mode_survival)}
+                                            <input
+                                                type="radio"
+                                                class=radio_class
+                                                value=SURVIVAL_GAME_MODE
+                                                bind:group=game_mode
+                                            />
+                                        </label>
+                                        <label class=label_class>
+                                            {t!(i18n, form.game_mode_time_attack)}
+                                            <input
+                                                type="radio"
+                                                class=radio_class
+                                                value=TIME_ATTACK_GAME_MODE
+                                                bind:group=game_mode
+                                            />
+                                        </label>
+                                    </div>
+                                </fieldset>
+                                <fieldset class:hidden=move || game_mode() != SURVIVAL_GAME_MODE>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.select_max_mistakes)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        {max_mistakes_choices
+                                            .into_iter()
+                                            .map(|mistakes| {
+                                                view! {
+                                                    <label class=label_class>
+                                                        {mistakes}
+                                                        <input
+                                                            type="radio"
+                                                            class=radio_class
+                                                            value=mistakes.to_string()
+                                                            bind:group=max_mistakes
+                                                        />
+                                                    </label>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </fieldset>
+                                <fieldset class:hidden=move || game_mode() != TIME_ATTACK_GAME_MODE>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.select_time_limit)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        {time_limit_choices
+                                            .into_iter()
+                                            .map(|secs| {
+                                                view! {
+                                                    <label class=label_class>
+                                                        {format!("{secs} s")}
+                                                        <input
+                                                            type="radio"
+                                                            class=radio_class
+                                                            value=secs.to_string()
+                                                            bind:group=time_limit_secs
+                                                        />
+                                                    </label>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </fieldset>
+                            </div>
+                            <div>
+                                <fieldset>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.toggle_immediate_feedback_option)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        <input
+                                            type="checkbox"
+                                            class="toggle toggle-primary toggle-md"
+                                            bind:checked=immediate_feedback
+                                        />
+                                    </div>
+                                </fieldset>
+                            </div>
+                            <div>
+                                <fieldset>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.select_max_attempts)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        {max_attempts_choices
+                                            .into_iter()
+                                            .map(|attempts| {
+                                                view! {
+                                                    <label class=label_class>
+                                                        {if attempts == 1 {
+                                                            t!(i18n, form.no_retry).into_any()
+                                                        } else {
+                                                            attempts.into_any()
+                                                        }}
+                                                        <input
+                                                            type="radio"
+                                                            class=radio_class
+                                                            value=attempts.to_string()
+                                                            bind:group=max_attempts
+                                                        />
+                                                    </label>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </fieldset>
+                            </div>
+                            <div>
+                                <fieldset>
+                                    <legend class=subtitle_font_class>
+                                        {t!(i18n, form.toggle_show_meaning_option)}
+                                    </legend>
+                                    <div class=fieldset_class>
+                                        <input
+                                            type="checkbox"
+                                            class="toggle toggle-primary toggle-md"
+                                            bind:checked=show_meaning_after_answer
+                                        />
+                                    </div>
+                                </fieldset>
                             </div>
                             <div class="flex justify-center m-2 pb-2">
                                 <input
@@ -1,86 +1,168 @@
-use leptos::ev::SubmitEvent;
+use codee::string::JsonSerdeCodec;
+use leptos::ev::{KeyboardEvent, SubmitEvent};
 use leptos::leptos_dom::logging::console_log;
 use leptos::{html, prelude::*};
+use leptos_use::storage::use_local_storage;
 use leptos_use::use_interval_fn;
 use leptos_use::utils::Pausable;
-use urlencoding::encode;
 
+use crate::binary_corpus::BinaryCorpus;
+use crate::choices::{get_answer_choices, get_audio_choices, get_nb_choices};
+use crate::components::answer_feedback::AnswerFeedback;
+use crate::components::audio_choices::AudioChoices;
+use crate::components::dictionary_controller::{use_dictionary_provider, DictionaryController};
+use crate::components::noise_layer::NoiseLayer;
+use crate::components::pitch_contour::PitchContour;
+use crate::components::syllable_replay::SyllableReplay;
 use crate::components::touch_tone_buttons::TouchToneButtons;
-use crate::exercise::{ExerciseSummary, HanziPair, InputStyle};
+use crate::exercise::{ExerciseParams, ExerciseType, GameMode, InputStyle, NoiseType};
+use crate::high_scores::HighScores;
 use crate::i18n::*;
-use crate::{
-    exercise::{ExerciseParams, ExerciseType, ShuangElement},
-    utils::{format_word_url, get_random_hanzi_pairs_idxs, get_tones_only_from_pronounced_pinyin},
-};
+use crate::minimal_pairs::MinimalPairIndex;
+use crate::session::{SessionEffect, SessionEngine, SessionEvent, SessionState};
+use crate::utils::format_word_url;
 
-const DEFAULT_LISTENINGS_TRIES: u32 = 3;
 const DEFAULT_TIMER_VALUE: u32 = 5;
+/// Key code of the key events handled by an input method
+const IME_KEY_CODE: u32 = 229;
 
 /// A component handling the exercise session for ShuangShuang
 #[component]
 pub fn TestSession(
-    hanzi_pairs: ReadSignal<Vec<HanziPair>>,
+    hanzi_pairs: ReadSignal<BinaryCorpus>,
     exercise_params: ReadSignal<Option<ExerciseParams>>,
     set_exercise_finished: WriteSignal<bool>,
 ) -> impl IntoView {
     let i18n = use_i18n();
-    let (current_random_idx, set_current_random_idx) = signal(0);
-    let (current_hanzi_pair, set_current_hanzi_pair) = signal(HanziPair::default());
-    let (current_pronounced_pinyin, set_current_pronounced_pinyin) = signal("".to_string());
+    let params = exercise_params
+        .get_untracked()
+        .expect("exercise_params is not yet set to Some.");
+    let engine = RwSignal::new(SessionEngine::from_corpus(
+        &*hanzi_pairs.read_untracked(),
+        &params,
+    ));
     let (user_answer, set_user_answer) = signal("".to_string());
-    let (random_idxs, set_random_idxs) = signal(vec![]);
-    let (shuang_elements, set_shuang_elements) = signal::<Vec<ShuangElement>>(vec![]);
+    let (chosen_answer, set_chosen_answer) = signal("".to_string());
     let (audio_playing, set_audio_playing) = signal(false);
-    let (show_results, set_show_results) = signal(false);
+    // Set while an input method composes characters, its Enter must not submit the answer
+    let (is_composing, set_is_composing) = signal(false);
     let timer = RwSignal::new(DEFAULT_TIMER_VALUE);
-    let first_tone_value = RwSignal::new("".to_string());
-    let second_tone_value = RwSignal::new("".to_string());
+    // Seconds left in a time attack session, counted down by the same interval as `timer`
+    let game_clock = RwSignal::new(match params.game_mode {
+        GameMode::TimeAttack { time_limit_secs } => time_limit_secs,
+        _ => 0,
+    });
+    let (high_scores, set_high_scores, _) =
+        use_local_storage::<HighScores, JsonSerdeCodec>("highscores");
+    // The high score to beat when the session started, and whether it was beaten
+    let (previous_high_score, set_previous_high_score) = signal::<Option<u32>>(None);
+    let (is_new_high_score, set_is_new_high_score) = signal(false);
+    let tone_values = RwSignal::new(Vec::<String>::new());
     let user_answer_element: NodeRef<html::Input> = NodeRef::new();
     let audio_element: NodeRef<html::Audio> = NodeRef::new();
-    let (remaining_listenings, set_remaining_listenings) = signal(DEFAULT_LISTENINGS_TRIES);
-    let (audio_url, set_audio_url) = signal("".to_string());
-    let params = move || exercise_params().expect("exercise_params is not yet set to Some.");
-    let go_to_next_hanzi_pair = move || {
-        let c_r_idx: usize = current_random_idx();
-        let c_idx: usize = random_idxs.read()[c_r_idx];
-        let hp = hanzi_pairs.read()[c_idx].clone();
-        set_current_pronounced_pinyin(hp.pronounced_pinyin.clone());
-        set_current_hanzi_pair(hp);
-        set_audio_url(format_word_url(
-            &current_hanzi_pair().characters,
-            params().audio_quality,
-        ));
-        set_remaining_listenings(params().audio_retries);
-        console_log(&format!("Current idx: {}", c_r_idx));
-        console_log(&format!("Current url: {}", audio_url()));
-    };
-    let play_hanzi_pair_audio = move |set_new_audio: bool| {
-        let audio = audio_element
-            .get()
-            .expect("<audio> element should be mounted");
-        if set_new_audio {
-            audio.set_src(&audio_url());
+    let background_noise = params.background_noise;
+    let is_finished = Memo::new(move |_| engine.read().state() == SessionState::Finished);
+    let (dictionary_provider, set_dictionary_provider) = use_dictionary_provider();
+    let is_answered = Memo::new(move |_| engine.read().state() == SessionState::Answered);
+    let audio_url = move || engine.read().current_audio_url().unwrap_or_default();
+    let current_pronounced_pinyin = Signal::derive(move || {
+        engine
+            .read()
+            .current_hanzi_pair()
+            .map(|hanzi_pair| hanzi_pair.pronounced_pinyin.clone())
+            .unwrap_or_default()
+    });
+    let is_reverse_audio = params.exercise_type == ExerciseType::ReverseAudio;
+    let is_sandhi = params.exercise_type == ExerciseType::Sandhi;
+    // Varies from a pair to the next in a mixed session
+    let current_exercise_type = Memo::new(move |_| engine.read().current_exercise_type());
+    let minimal_pair_index = StoredValue::new(if is_reverse_audio {
+        MinimalPairIndex::new(&*hanzi_pairs.read_untracked())
+    } else {
+        MinimalPairIndex::default()
+    });
+    let audio_choices = Memo::new(move |_| {
+        if !is_reverse_audio {
+            return vec![];
         }
-        let _promise = audio.play().expect("Failed to play audio element.");
-        set_audio_playing(true);
-    };
+        engine
+            .read()
+            .current_hanzi_pair()
+            .map(|hanzi_pair| {
+                get_audio_choices(
+                    hanzi_pair,
+                    &minimal_pair_index.read_value(),
+                    &*hanzi_pairs.read_untracked(),
+                    get_nb_choices(params.exercise_type),
+                )
+            })
+            .unwrap_or_default()
+    });
+    let answer_choices = Memo::new(move |_| {
+        if params.input_style != InputStyle::MultipleChoice || is_reverse_audio {
+            return vec![];
+        }
+        engine
+            .read()
+            .current_hanzi_pair()
+            .map(|hanzi_pair| {
+                get_answer_choices(
+                    hanzi_pair,
+                    current_exercise_type(),
+                    &*hanzi_pairs.read_untracked(),
+                    get_nb_choices(current_exercise_type()),
+                )
+            })
+            .unwrap_or_default()
+    });
 
-    let input_label = move || match params().exercise_type {
-        ExerciseType::ToneOnly => t_string!(i18n, exercise.input_label_tone_only),
-        ExerciseType::NoTonePinyin => t_string!(i18n, exercise.input_label_no_tone_pinyin),
-        ExerciseType::Pinyin => t_string!(i18n, exercise.input_label_pinyin),
+    let dispatch = move |event: SessionEvent| {
+        let effects = engine
+            .try_update(|engine| {
+                // An endless session draws its next pairs before moving on
+                if event == SessionEvent::Next {
+                    engine.refill(&*hanzi_pairs.read_untracked());
+                }
+                engine.handle(event)
+            })
+            .unwrap_or_default();
+        for effect in effects {
+            match effect {
+                SessionEffect::PlayAudio { url, new_audio } => {
+                    // The first pair autoplays, as the <audio> element is not mounted yet
+                    if let Some(audio) = audio_element.get_untracked() {
+                        if new_audio {
+                            audio.set_src(&url);
+                        }
+                        let _promise = audio.play().expect("Failed to play audio element.");
+                        set_audio_playing(true);
+                    }
+                    console_log(&format!("Current url: {url}"));
+                }
+                SessionEffect::ResetTimer => timer.set(DEFAULT_TIMER_VALUE),
+            }
+        }
     };
+    dispatch(SessionEvent::Start);
 
-    let on_click_audio = move |_| {
-        if *remaining_listenings.read() > 0 {
-            set_remaining_listenings.update(|l| *l -= 1);
-            play_hanzi_pair_audio(false);
+    let input_label = move || match current_exercise_type() {
+        ExerciseType::ToneOnly | ExerciseType::Production | ExerciseType::MinimalPair => {
+            t_string!(i18n, exercise.input_label_tone_only)
+        }
+        ExerciseType::Sandhi => t_string!(i18n, exercise.input_label_sandhi),
+        ExerciseType::NoTonePinyin | ExerciseType::PinyinDrill => {
+            t_string!(i18n, exercise.input_label_no_tone_pinyin)
         }
+        ExerciseType::Pinyin | ExerciseType::Mixed => t_string!(i18n, exercise.input_label_pinyin),
+        ExerciseType::ReverseAudio => t_string!(i18n, exercise.input_label_reverse_audio),
+        ExerciseType::Hanzi => t_string!(i18n, exercise.input_label_hanzi),
     };
 
+    let on_click_audio = move |_| dispatch(SessionEvent::ReplayAudio);
+
     let on_submit_answer = move || {
         let answer: String;
-        match params().input_style {
+        match params.input_style {
             InputStyle::Keyboard => {
                 let answer_elem = user_answer_element
                     .get()
@@ -91,44 +173,47 @@ pub fn TestSession(
                 set_user_answer(answer.clone());
             }
             InputStyle::Touch => {
-                answer = first_tone_value() + &second_tone_value();
-                first_tone_value.set("".to_string());
-                second_tone_value.set("".to_string());
+                answer = tone_values().concat();
+                tone_values.set(vec![]);
             }
-        }
-        let hanzi_pair = current_hanzi_pair();
-        let pronounced_pinyin = hanzi_pair.pronounced_pinyin.clone();
-        let expected_answer = match params().exercise_type {
-            ExerciseType::ToneOnly => get_tones_only_from_pronounced_pinyin(&pronounced_pinyin),
-            ExerciseType::NoTonePinyin => pronounced_pinyin.replace(char::is_numeric, ""),
-            ExerciseType::Pinyin => pronounced_pinyin.clone(),
-        };
-        set_shuang_elements.update(|v| {
-            v.push(ShuangElement {
-                hanzi_pair,
-                is_correct: answer == expected_answer,
-                user_answer: answer,
-            })
-        });
-        if current_random_idx() < random_idxs.read().len() - 1 {
-            set_current_random_idx.update(|idx| *idx += 1);
-            go_to_next_hanzi_pair();
-            play_hanzi_pair_audio(true);
-            if params().timer_on {
-                timer.set(DEFAULT_TIMER_VALUE);
+            // Set by the clicked choice, the timer submits an empty answer
+            InputStyle::MultipleChoice => {
+                answer = chosen_answer.get_untracked();
+                set_chosen_answer(String::new());
             }
-        } else {
-            set_show_results(true);
+        }
+        dispatch(SessionEvent::SubmitAnswer(answer));
+        // The feedback stays until the learner moves on, the sandhi quiz always explains the
+        // answer while its recording plays
+        if !params.immediate_feedback && !params.show_meaning_after_answer && !is_sandhi {
+            dispatch(SessionEvent::Next);
         }
     };
+    let on_feedback_replay = Callback::new(move |_: ()| {
+        if let Some(audio) = audio_element.get_untracked() {
+            let _promise = audio.play().expect("Failed to play audio element.");
+            set_audio_playing(true);
+        }
+    });
+    let on_feedback_next = Callback::new(move |_: ()| dispatch(SessionEvent::Next));
 
     // Function to handle countdown
-    let Pausable {
-        pause,
-        resume,
-        is_active,
-    } = use_interval_fn(
+    let Pausable { pause, resume, .. } = use_interval_fn(
         move || {
+            // The time attack clock keeps running while the last pair is shown, unlike the timer
+            if let GameMode::TimeAttack { .. } = engine.read_untracked().game_mode() {
+                if game_clock.get_untracked() > 1 {
+                    game_clock.update(|t| *t -= 1);
+                } else {
+                    game_clock.set(0);
+                    dispatch(SessionEvent::TimeUp);
+                    return;
+                }
+            }
+            // The timer waits while the meaning of the last pair is shown
+            if !params.timer_on || is_answered.get_untracked() {
+                return;
+            }
             if timer.get() > 0 {
                 timer.update(|t| *t -= 1);
             } else {
@@ -138,27 +223,44 @@ pub fn TestSession(
         },
         1000,
     );
-    view! {
-        {move || {
-            if current_random_idx() == 0 && timer() == DEFAULT_TIMER_VALUE {
-                pause();
-                if params().timer_on {
-                    resume();
+    if !params.timer_on && !matches!(params.game_mode, GameMode::TimeAttack { .. }) {
+        pause();
+    }
+    Effect::new(move |_| {
+        if is_finished() {
+            pause();
+            // A review of the mistakes is a standard session, without high score
+            let game_mode = engine.read_untracked().game_mode();
+            if game_mode.is_endless() {
+                let score = engine.read_untracked().get_summary().correct_answers;
+                let mut updated_high_scores = high_scores.get_untracked();
+                set_previous_high_score(updated_high_scores.get(game_mode, params.difficulty));
+                if updated_high_scores.record(game_mode, params.difficulty, score) {
+                    set_is_new_high_score(true);
+                    set_high_scores(updated_high_scores);
                 }
-                set_random_idxs(
-                    get_random_hanzi_pairs_idxs(
-                        params().exercise_size,
-                        &hanzi_pairs(),
-                        params().shuffle_mode,
-                    ),
-                );
-                go_to_next_hanzi_pair();
             }
-            if show_results() {
-                let exercise_summary = ExerciseSummary::from(shuang_elements());
-                if is_active() {
-                    pause();
-                }
+        }
+    });
+    // The review is a new session of the mistakes, its summary keeps their first answer
+    let on_review_mistakes = move |_| {
+        let mistakes = engine.read_untracked().get_summary().test_elements;
+        engine.set(
+            SessionEngine::from_mistakes(&mistakes, &params)
+                .with_homophones(&*hanzi_pairs.read_untracked()),
+        );
+        timer.set(DEFAULT_TIMER_VALUE);
+        if params.timer_on {
+            resume();
+        }
+        dispatch(SessionEvent::Start);
+    };
+    view! {
+        {(background_noise.noise_type != NoiseType::Silence)
+            .then(|| view! { <NoiseLayer background_noise audio_playing /> })}
+        {move || {
+            if is_finished() {
+                let exercise_summary = engine.read_untracked().get_summary();
                 let rounded_percentage = (exercise_summary.get_correct_percentage() * 100.0).round()
                     / 100.0;
                 let result_color_class = match rounded_percentage {
@@ -168,10 +270,45 @@ pub fn TestSession(
                     _ => "text-success",
                 };
 
+                let score = exercise_summary.correct_answers;
+
                 view! {
                     <div class="flex justify-center">
                         <div class="flex flex-col">
                             <div class="card h-full md:h-160 md:mt-10 bg-base-100 card-border border-base-300 card-md overflow-auto px-10">
+                                {engine
+                                    .read_untracked()
+                                    .game_mode()
+                                    .is_endless()
+                                    .then(|| {
+                                        view! {
+                                            <div class="flex justify-center text-xl font-semibold">
+                                                {t!(i18n, exercise.score)}
+                                                {score}
+                                            </div>
+                                            <div class="flex justify-center">
+                                                {move || {
+                                                    if is_new_high_score() {
+                                                        view! {
+                                                            <a class="text-success font-semibold">
+                                                                {t!(i18n, exercise.new_high_score)}
+                                                            </a>
+                                                        }
+                                                            .into_any()
+                                                    } else {
+                                                        previous_high_score()
+                                                            .map(|high_score| {
+                                                                view! {
+                                                                    {t!(i18n, exercise.high_score)}
+                                                                    {high_score}
+                                                                }
+                                                            })
+                                                            .into_any()
+                                                    }
+                                                }}
+                                            </div>
+                                        }
+                                    })}
                                 <div class="flex justify-center">
                                     {t!(i18n, exercise.correct_answers)}
                                     <a class=result_color_class>
@@ -182,6 +319,40 @@ pub fn TestSession(
                                     {t!(i18n, exercise.correct_percentage)}
                                     <a class=result_color_class>{rounded_percentage}"%"</a>
                                 </div>
+                                {(params.exercise_type == ExerciseType::Mixed)
+                                    .then(|| {
+                                        [
+                                            (ExerciseType::ToneOnly, t_string!(i18n, form.exercise_type_tone_only)),
+                                            (
+                                                ExerciseType::NoTonePinyin,
+                                                t_string!(i18n, form.exercise_type_no_tone_pinyin),
+                                            ),
+                                            (ExerciseType::Pinyin, t_string!(i18n, form.exercise_type_pinyin)),
+                                        ]
+                                            .into_iter()
+                                            .filter_map(|(exercise_type, type_label)| {
+                                                exercise_summary
+                                                    .get_exercise_type_percentage(exercise_type)
+                                                    .map(|percentage| {
+                                                        view! {
+                                                            <div class="flex justify-center">
+                                                                {format!("{type_label}: {}%", percentage.round())}
+                                                            </div>
+                                                        }
+                                                    })
+                                            })
+                                            .collect_view()
+                                    })}
+                                {(exercise_summary.first_attempt_correct_answers
+                                    != exercise_summary.correct_answers)
+                                    .then(|| {
+                                        view! {
+                                            <div class="flex justify-center">
+                                                {t!(i18n, exercise.first_attempt_correct_answers)}
+                                                {exercise_summary.first_attempt_correct_answers}
+                                            </div>
+                                        }
+                                    })}
                                 <div class="text-center">
                                     {
                                         let count = exercise_summary
@@ -203,8 +374,8 @@ pub fn TestSession(
                                                         <li>
                                                             {format!(
                                                                 "({}, {}) => {mistake_count}",
-                                                                tone_pair.0.to_string(),
-                                                                tone_pair.1.to_string(),
+                                                                tone_pair.0,
+                                                                tone_pair.1,
                                                             )}
                                                         </li>
                                                     }
@@ -217,20 +388,48 @@ pub fn TestSession(
                                     }}
 
                                 </div>
+                                <DictionaryController
+                                    provider=dictionary_provider
+                                    set_provider=set_dictionary_provider
+                                />
                                 <div class="py-4">
                                     {
                                         let mut mistakes_views: Vec<AnyView> = vec![];
-                                        for elem in shuang_elements.read().iter() {
-                                            if !elem.is_correct {
-                                                let elem_ref = format!(
-                                                    "{}{}",
-                                                    t_string!(i18n, exercise.dictionnary_link),
-                                                    encode(&elem.hanzi_pair.characters),
-                                                );
+                                        for elem in exercise_summary.test_elements.iter() {
+                                            if elem.was_missed() {
+                                                let characters = elem.hanzi_pair.characters.clone();
+                                                let pinyin = elem.hanzi_pair.pinyin.clone();
+                                                let elem_ref = {
+                                                    let pinyin = pinyin.clone();
+                                                    move || {
+                                                        dictionary_provider
+                                                            .read()
+                                                            .hanzi_url(&characters, &pinyin)
+                                                    }
+                                                };
+                                                let pinyin_link = move || {
+                                                    dictionary_provider
+                                                        .read()
+                                                        .pinyin_url(&pinyin)
+                                                        .map(|pinyin_ref| {
+                                                            view! {
+                                                                " · "
+                                                                <a class="link link-info" href=pinyin_ref>
+                                                                    {t!(i18n, exercise.search_pinyin)}
+                                                                </a>
+                                                            }
+                                                        })
+                                                };
                                                 let audio_url = format_word_url(
                                                     &elem.hanzi_pair.characters,
-                                                    params().audio_quality,
+                                                    params.audio_quality,
                                                 );
+                                                let metadata = elem.hanzi_pair.metadata.clone();
+                                                let gloss = move || {
+                                                    metadata
+                                                        .gloss(i18n.get_locale().as_ref())
+                                                        .map(str::to_string)
+                                                };
                                                 mistakes_views
                                                     .push(
                                                         view! {
@@ -240,34 +439,92 @@ pub fn TestSession(
                                                                     <a class="link link-error">
                                                                         {elem.hanzi_pair.characters.clone()}
                                                                     </a>
+                                                                    <div class="text-sm font-normal italic">
+                                                                        {gloss}
+                                                                    </div>
                                                                 </div>
                                                                 <div class="collapse-content">
                                                                     <div class="flex flex-col justify-center">
                                                                         <div>
                                                                             <audio controls>
-                                                                                <source type="audio/mpeg" src=audio_url />
+                                                                                <source
+                                                                                    type="audio/mpeg"
+                                                                                    src=audio_url.clone()
+                                                                                />
                                                                             </audio>
                                                                         </div>
+                                                                        <SyllableReplay
+                                                                            audio_url=audio_url.clone()
+                                                                            pronounced_pinyin=elem.hanzi_pair.pronounced_pinyin.clone()
+                                                                        />
+                                                                        <PitchContour
+                                                                            audio_url
+                                                                            tone_pair=elem.hanzi_pair.pronounced_tone_pair
+                                                                        />
                                                                         <div>
                                                                             <a class="link link-info" href=elem_ref>
                                                                                 {t!(i18n, exercise.link_to_dictionnary)}
                                                                             </a>
+                                                                            {pinyin_link}
                                                                         </div>
+                                                                        {elem
+                                                                            .hanzi_pair
+                                                                            .pronunciation_override
+                                                                            .clone()
+                                                                            .map(|reason| {
+                                                                                view! {
+                                                                                    <div class="text-warning">
+                                                                                        {t!(i18n, exercise.recording_differs_from_dictionary)}
+                                                                                        {format!(
+                                                                                            "{} ({reason})",
+                                                                                            elem.hanzi_pair.pinyin,
+                                                                                        )}
+                                                                                    </div>
+                                                                                }
+                                                                            })}
                                                                         <div>
                                                                             {t!(i18n, exercise.expected_pinyin_answer)}
                                                                             {elem.hanzi_pair.pronounced_pinyin.clone()}
                                                                         </div>
+                                                                        {(!elem.hanzi_pair.alternative_readings.is_empty())
+                                                                            .then(|| {
+                                                                                view! {
+                                                                                    <div>
+                                                                                        {t!(i18n, exercise.other_readings)}
+                                                                                        {elem
+                                                                                            .hanzi_pair
+                                                                                            .alternative_readings
+                                                                                            .iter()
+                                                                                            .map(|reading| reading.pronounced_pinyin.clone())
+                                                                                            .collect::<Vec<String>>()
+                                                                                            .join(", ")}
+                                                                                    </div>
+                                                                                }
+                                                                            })}
                                                                         <div>
                                                                             {t!(i18n, exercise.expected_tone_answer)}
                                                                             {format!(
                                                                                 "{}{}",
-                                                                                elem.hanzi_pair.pronounced_tone_pair.0.clone().to_string(),
-                                                                                elem.hanzi_pair.pronounced_tone_pair.1.clone().to_string(),
+                                                                                elem.hanzi_pair.pronounced_tone_pair.0,
+                                                                                elem.hanzi_pair.pronounced_tone_pair.1,
                                                                             )}
                                                                         </div>
                                                                         <div>
                                                                             {t!(i18n, exercise.user_answer)}{elem.user_answer.clone()}
                                                                         </div>
+                                                                        {elem
+                                                                            .first_answer
+                                                                            .clone()
+                                                                            .map(|first_answer| {
+                                                                                view! {
+                                                                                    <div>
+                                                                                        {t!(i18n, exercise.first_answer)}{first_answer}
+                                                                                    </div>
+                                                                                    <div>
+                                                                                        {t!(i18n, exercise.attempts)}{elem.attempts}
+                                                                                    </div>
+                                                                                }
+                                                                            })}
                                                                     </div>
                                                                 </div>
                                                             </div>
@@ -280,7 +537,21 @@ pub fn TestSession(
                                     }
                                 </div>
                             </div>
-                            <div class="flex fit justify-center py-2">
+                            <div class="flex fit justify-center gap-2 py-2">
+                                {exercise_summary
+                                    .test_elements
+                                    .iter()
+                                    .any(|elem| !elem.is_correct)
+                                    .then(|| {
+                                        view! {
+                                            <button
+                                                class="btn rounded-md btn-primary text-white"
+                                                on:click=on_review_mistakes.clone()
+                                            >
+                                                {t!(i18n, exercise.review_mistakes)}
+                                            </button>
+                                        }
+                                    })}
                                 <button
                                     class="btn rounded-md btn-secondary text-white"
                                     on:click=move |_| { set_exercise_finished(true) }
@@ -302,21 +573,52 @@ pub fn TestSession(
                 view! {
                     <div class="flex h-full md:h-100 justify-center place-items-center">
                         <div class="flex flex-col justify-center">
-                            <div>
-                                <a class="badge badge-accent text-white font-semibold">
-                                    {random_idxs.read().len() - current_random_idx()}
-                                </a>
-                                {t!(i18n, exercise.remaining_pairs)}
-                            </div>
-                            {if params().timer_on {
+                            {match engine.read_untracked().game_mode() {
+                                GameMode::Standard => {
+                                    view! {
+                                        <div>
+                                            <a class="badge badge-accent text-white font-semibold">
+                                                {move || engine.read().remaining_pairs()}
+                                            </a>
+                                            {t!(i18n, exercise.remaining_pairs)}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                                GameMode::Survival { .. } => {
+                                    view! {
+                                        <div>
+                                            <a class="badge badge-error text-white font-semibold">
+                                                {move || {
+                                                    engine.read().remaining_mistakes().unwrap_or_default()
+                                                }}
+                                            </a>
+                                            {t!(i18n, exercise.remaining_mistakes)}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                                GameMode::TimeAttack { .. } => {
+                                    view! {
+                                        <div>
+                                            <a class="badge badge-warning text-white font-semibold">
+                                                {move || game_clock.get()}
+                                            </a>
+                                            {t!(i18n, exercise.remaining_seconds)}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                            }}
+                            {if params.timer_on {
                                 view! {
                                     <div>
                                         {t!(i18n, exercise.remaining_time)}
                                         <span class="countdown font-mono">
                                             <span
-                                                style=countdown_style()
+                                                style=countdown_style
                                                 aria-live="polite"
-                                                class=countdown_class()
+                                                class=countdown_class
                                             >
                                                 {move || timer.get()}
                                             </span>
@@ -328,35 +630,77 @@ pub fn TestSession(
                                 view! {}.into_any()
                             }}
 
-                            <div class="pt-2">
-                                <form on:submit={
-                                    let pause = pause.clone();
-                                    let resume = resume.clone();
-                                    let timer_on = params().timer_on;
-                                    move |ev: SubmitEvent| {
-                                        ev.prevent_default();
-                                        if timer_on {
-                                            pause();
-                                        }
-                                        on_submit_answer();
-                                        if timer_on {
-                                            resume();
-                                        }
+                            <Show when=is_answered>
+                                {move || {
+                                    engine
+                                        .read()
+                                        .last_answer()
+                                        .cloned()
+                                        .map(|shuang_element| {
+                                            let audio_url = format_word_url(
+                                                &shuang_element.hanzi_pair.characters,
+                                                params.audio_quality,
+                                            );
+                                            view! {
+                                                <AnswerFeedback
+                                                    shuang_element
+                                                    audio_url
+                                                    show_grading=params.immediate_feedback || is_sandhi
+                                                    show_meaning=params.show_meaning_after_answer
+                                                    on_replay=on_feedback_replay
+                                                    on_next=on_feedback_next
+                                                />
+                                            }
+                                        })
+                                }}
+                            </Show>
+                            {is_sandhi
+                                .then(|| {
+                                    view! {
+                                        <div class="text-center" class:hidden=is_answered>
+                                            <div class="text-3xl">
+                                                {move || {
+                                                    engine
+                                                        .read()
+                                                        .current_hanzi_pair()
+                                                        .map(|hanzi_pair| hanzi_pair.characters.clone())
+                                                }}
+                                            </div>
+                                            <div class="text-lg">
+                                                {move || {
+                                                    engine
+                                                        .read()
+                                                        .current_hanzi_pair()
+                                                        .map(|hanzi_pair| hanzi_pair.pinyin.clone())
+                                                }}
+                                            </div>
+                                        </div>
                                     }
+                                })}
+                            <div class="pt-2" class:hidden=is_answered>
+                                <form on:submit=move |ev: SubmitEvent| {
+                                    ev.prevent_default();
+                                    on_submit_answer();
                                 }>
-                                    {if let InputStyle::Keyboard = params().input_style {
+                                    {if let InputStyle::Keyboard = params.input_style {
+                                        let input_texts = move || {
                                         let input_placeholder: &str;
                                         let input_help: &str;
                                         let input_type: &str;
-                                        match params().exercise_type {
-                                            ExerciseType::Pinyin => {
+                                        match current_exercise_type() {
+                                            ExerciseType::Pinyin
+                                            | ExerciseType::ReverseAudio
+                                            | ExerciseType::Mixed => {
                                                 input_help = &t_string!(i18n, exercise.input_help_pinyin);
                                                 input_placeholder = &t_string!(
                                                     i18n, exercise.input_placeholder_pinyin
                                                 );
                                                 input_type = "text";
                                             }
-                                            ExerciseType::ToneOnly => {
+                                            ExerciseType::ToneOnly
+                                            | ExerciseType::Production
+                                            | ExerciseType::MinimalPair
+                                            | ExerciseType::Sandhi => {
                                                 input_help = &t_string!(
                                                     i18n, exercise.input_help_tone_only
                                                 );
@@ -365,7 +709,7 @@ pub fn TestSession(
                                                 );
                                                 input_type = "number";
                                             }
-                                            ExerciseType::NoTonePinyin => {
+                                            ExerciseType::NoTonePinyin | ExerciseType::PinyinDrill => {
                                                 input_help = &t_string!(
                                                     i18n, exercise.input_help_no_tone_pinyin
                                                 );
@@ -374,7 +718,20 @@ pub fn TestSession(
                                                 );
                                                 input_type = "text";
                                             }
+                                            ExerciseType::Hanzi => {
+                                                input_help = if params.exact_hanzi_match {
+                                                    t_string!(i18n, exercise.input_help_hanzi_exact)
+                                                } else {
+                                                    t_string!(i18n, exercise.input_help_hanzi)
+                                                };
+                                                input_placeholder = t_string!(
+                                                    i18n, exercise.input_placeholder_hanzi
+                                                );
+                                                input_type = "text";
+                                            }
                                         }
+                                        (input_help, input_placeholder, input_type)
+                                        };
                                         view! {
                                             <fieldset class="fieldset">
                                                 <legend class="w-full text-xs font-semibold">
@@ -385,10 +742,23 @@ pub fn TestSession(
                                                         class="input input-neutral rounded-md text-[16px]"
                                                         required
                                                         autocapitalize="none"
-                                                        type=input_type
+                                                        type=move || input_texts().2
                                                         node_ref=user_answer_element
                                                         value=user_answer
-                                                        placeholder=input_placeholder
+                                                        placeholder=move || input_texts().1
+                                                        on:compositionstart=move |_| set_is_composing(true)
+                                                        on:compositionend=move |_| set_is_composing(false)
+                                                        on:keydown=move |ev: KeyboardEvent| {
+                                                            // Safari ends the composition before the
+                                                            // keydown, which keeps the IME key code
+                                                            if ev.key() == "Enter"
+                                                                && (ev.is_composing()
+                                                                    || is_composing.get_untracked()
+                                                                    || ev.key_code() == IME_KEY_CODE)
+                                                            {
+                                                                ev.prevent_default();
+                                                            }
+                                                        }
                                                     />
                                                     <input
                                                         class="btn btn-primary text-white rounded-sm"
@@ -396,7 +766,63 @@ pub fn TestSession(
                                                         value=">"
                                                     />
                                                 </div>
-                                                <p class="label">{input_help}</p>
+                                                <p class="label">{move || input_texts().0}</p>
+                                            </fieldset>
+                                        }
+                                            .into_any()
+                                    } else if is_reverse_audio {
+                                        view! {
+                                            <div class="text-center">
+                                                <div class="text-3xl">
+                                                    {move || {
+                                                        engine
+                                                            .read()
+                                                            .current_hanzi_pair()
+                                                            .map(|hanzi_pair| hanzi_pair.characters.clone())
+                                                    }}
+                                                </div>
+                                                <div class="text-lg">{current_pronounced_pinyin}</div>
+                                            </div>
+                                            <fieldset class="fieldset">
+                                                <legend class="w-full text-xs font-semibold">
+                                                    {input_label}
+                                                </legend>
+                                                <AudioChoices
+                                                    choices=audio_choices.into()
+                                                    audio_quality=params.audio_quality
+                                                    set_chosen_answer
+                                                />
+                                            </fieldset>
+                                        }
+                                            .into_any()
+                                    } else if let InputStyle::MultipleChoice = params.input_style {
+                                        view! {
+                                            <fieldset class="fieldset">
+                                                <legend class="w-full text-xs font-semibold">
+                                                    {input_label}
+                                                </legend>
+                                                <div class="grid grid-cols-2 gap-2">
+                                                    {move || {
+                                                        answer_choices()
+                                                            .into_iter()
+                                                            .map(|choice| {
+                                                                let value = choice.clone();
+                                                                view! {
+                                                                    <button
+                                                                        type="submit"
+                                                                        class="btn btn-outline btn-primary rounded-md"
+                                                                        on:click=move |_| set_chosen_answer(value.clone())
+                                                                    >
+                                                                        {choice}
+                                                                    </button>
+                                                                }
+                                                            })
+                                                            .collect_view()
+                                                    }}
+                                                </div>
+                                                <p class="label">
+                                                    {t!(i18n, exercise.input_help_multiple_choice)}
+                                                </p>
                                             </fieldset>
                                         }
                                             .into_any()
@@ -405,8 +831,7 @@ pub fn TestSession(
                                             <div class="flex flex-row justify-center mb-6 mt-2">
                                                 <TouchToneButtons
                                                     pronounced_pinyin=current_pronounced_pinyin
-                                                    first_tone_value
-                                                    second_tone_value
+                                                    tone_values
                                                     set_audio_playing
                                                 />
                                                 <div class="flex justify-center place-items-center ml-4">
@@ -423,9 +848,15 @@ pub fn TestSession(
 
                                 </form>
                             </div>
-                            <div class="flex flex-wrap">
+                            // The reverse exercise has a player per choice instead, and the sandhi
+                            // quiz plays the recording once answered
+                            <div
+                                class="flex flex-wrap"
+                                class:hidden=move || is_reverse_audio || is_sandhi
+                            >
                                 <label class="label">
-                                    {t!(i18n, exercise.remaining_listenings)} {remaining_listenings}
+                                    {t!(i18n, exercise.remaining_listenings)}
+                                    {move || engine.read().remaining_listenings()}
                                     <audio
                                         autoplay
                                         node_ref=audio_element
@@ -436,7 +867,7 @@ pub fn TestSession(
                                 </label>
                                 {move || {
                                     let btn_class = "btn btn-neutral rounded-md mx-2";
-                                    if audio_playing() || *remaining_listenings.read() == 0 {
+                                    if audio_playing() || engine.read().remaining_listenings() == 0 {
 
                                         view! {
                                             <button class=btn_class disabled>
@@ -2,298 +2,145 @@ use leptos::{html, prelude::*};
 
 use crate::exercise::Tone;
 use crate::i18n::*;
-use crate::utils::format_toned_syllable_url;
+use crate::utils::{format_toned_syllable_url, get_syllables_from_pinyin};
 
-// A component to display all tones and play the syllable audio when clicked
+const TONES: [Tone; 4] = [Tone::Tone1, Tone::Tone2, Tone::Tone3, Tone::Tone4];
+
+/// Isolated recording of `syllable` said with `tone`, there are none for the neutral tone
+pub fn get_tone_preview_url(syllable: &str, tone: Tone) -> Option<String> {
+    match tone {
+        Tone::NeutralTone => None,
+        _ => Some(format_toned_syllable_url(syllable, &tone.to_string())),
+    }
+}
+
+// A component to display the tone choices of one syllable and play the syllable audio when
+// clicked, the neutral tone has no recording to play
 #[component]
-pub fn TouchToneButtons(
-    pronounced_pinyin: ReadSignal<String>,
-    first_tone_value: RwSignal<String>,
-    second_tone_value: RwSignal<String>,
+fn SyllableToneButtons(
+    syllable: String,
+    syllable_idx: usize,
+    tone_values: RwSignal<Vec<String>>,
     set_audio_playing: WriteSignal<bool>,
 ) -> impl IntoView {
     let i18n = use_i18n();
-
-    let (first_syllable, set_first_syllable) = signal("".to_string());
-    let (second_syllable, set_second_syllable) = signal("".to_string());
-
-    let audio_element_first_s_first_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_first_s_second_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_first_s_third_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_first_s_fourth_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_second_s_first_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_second_s_second_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_second_s_third_t: NodeRef<html::Audio> = NodeRef::new();
-    let audio_element_second_s_fourth_t: NodeRef<html::Audio> = NodeRef::new();
-
-    let play_first_s_first_t_audio = move |_| {
-        let audio = audio_element_first_s_first_t
-            .get()
-            .expect("<audio> first_s_first_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio first_s_first_t element.");
-        set_audio_playing(true);
-    };
-    let play_first_s_second_t_audio = move |_| {
-        let audio = audio_element_first_s_second_t
-            .get()
-            .expect("<audio> first_s_second_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio first_s_second_t element.");
-        set_audio_playing(true);
-    };
-    let play_first_s_third_t_audio = move |_| {
-        let audio = audio_element_first_s_third_t
-            .get()
-            .expect("<audio> first_s_third_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio first_s_third_t element.");
-        set_audio_playing(true);
-    };
-    let play_first_s_fourth_t_audio = move |_| {
-        let audio = audio_element_first_s_fourth_t
-            .get()
-            .expect("<audio> first_s_fourth_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio first_s_fourth_t element.");
-        set_audio_playing(true);
-    };
-    let play_second_s_first_t_audio = move |_| {
-        let audio = audio_element_second_s_first_t
-            .get()
-            .expect("<audio> second_s_firs_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio second_s_first_t element.");
-        set_audio_playing(true);
-    };
-    let play_second_s_second_t_audio = move |_| {
-        let audio = audio_element_second_s_second_t
-            .get()
-            .expect("<audio> second_s_second_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio second_s_second_t element.");
-        set_audio_playing(true);
-    };
-    let play_second_s_third_t_audio = move |_| {
-        let audio = audio_element_second_s_third_t
-            .get()
-            .expect("<audio> second_s_third_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio second_s_third_t element.");
-        set_audio_playing(true);
-    };
-    let play_second_s_fourth_t_audio = move |_| {
-        let audio = audio_element_second_s_fourth_t
-            .get()
-            .expect("<audio> second_s_fourth_t element should be mounted");
-        let _promise = audio
-            .play()
-            .expect("Failed to play audio second_s_fourth_t element.");
-        set_audio_playing(true);
+    let radio_class = "radio radio-sm radio-primary";
+    let radio_space_class = "px-1";
+    let group_name = format!("syllable_tone_{syllable_idx}");
+    // A word cannot start with a neutral tone
+    let tones: Vec<Tone> = if syllable_idx == 0 {
+        TONES.to_vec()
+    } else {
+        TONES.iter().copied().chain([Tone::NeutralTone]).collect()
     };
+    let legend_syllable = syllable.clone();
 
     view! {
-        {move || {
-            let radio_class = "radio radio-sm radio-primary";
-            let radio_space_class = "px-1";
-            let spaced_no_tone_pinyin = pronounced_pinyin().replace(char::is_numeric, " ");
-            let syllables: Vec<&str> = spaced_no_tone_pinyin.split(' ').collect();
-            set_first_syllable(syllables[0].to_string());
-            set_second_syllable(syllables[1].to_string());
-            let first_tone = Tone::Tone1.to_string();
-            let second_tone = Tone::Tone2.to_string();
-            let third_tone = Tone::Tone3.to_string();
-            let fourth_tone = Tone::Tone4.to_string();
-            view! {
-                <div>
-                    <fieldset>
-                        <legend class="fieldset-legend">
-                            {t!(i18n, exercise.select_first_tone_value)}
-                        </legend>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_first_s_first_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&first_syllable(), &first_tone)
-                                />
-                            </audio>
-                            {first_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=first_tone.clone()
-                                bind:group=first_tone_value
-                                on:click=play_first_s_first_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_first_s_second_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&first_syllable(), &second_tone)
-                                />
-                            </audio>
-                            {second_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=second_tone.clone()
-                                bind:group=first_tone_value
-                                on:click=play_first_s_second_t_audio
-                                required
-                            />
-                        </label>
+        <fieldset>
+            <legend class="fieldset-legend">
+                {t!(i18n, exercise.select_tone_value, syllable = move || legend_syllable.clone())}
+            </legend>
+            {tones
+                .into_iter()
+                .map(|tone| {
+                    let audio_element: NodeRef<html::Audio> = NodeRef::new();
+                    let tone_value = tone.to_string();
+                    let audio_url = get_tone_preview_url(&syllable, tone);
+                    let has_preview = audio_url.is_some();
+                    let play_audio = move |_| {
+                        if !has_preview {
+                            return;
+                        }
+                        let audio = audio_element
+                            .get()
+                            .expect("<audio> tone preview element should be mounted");
+                        let _promise = audio
+                            .play()
+                            .expect("Failed to play audio tone preview element.");
+                        set_audio_playing(true);
+                    };
+                    let on_change = {
+                        let tone_value = tone_value.clone();
+                        move |_| {
+                            tone_values
+                                .update(|values| {
+                                    if values.len() <= syllable_idx {
+                                        values.resize(syllable_idx + 1, "".to_string());
+                                    }
+                                    values[syllable_idx] = tone_value.clone();
+                                })
+                        }
+                    };
+                    let is_checked = {
+                        let tone_value = tone_value.clone();
+                        move || tone_values.read().get(syllable_idx) == Some(&tone_value)
+                    };
+                    view! {
                         <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_first_s_third_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&first_syllable(), &third_tone)
-                                />
-                            </audio>
-                            {third_tone.clone()}
+                            {audio_url
+                                .map(|audio_url| {
+                                    view! {
+                                        <audio
+                                            node_ref=audio_element
+                                            on:ended=move |_| { set_audio_playing(false) }
+                                        >
+                                            <source type="audio/mpeg" src=audio_url />
+                                        </audio>
+                                    }
+                                })}
+                            {tone_value.clone()}
+                            {(!has_preview)
+                                .then(|| {
+                                    view! {
+                                        <span class="text-xs opacity-70 px-1">
+                                            {t!(i18n, exercise.no_tone_preview)}
+                                        </span>
+                                    }
+                                })}
                             <input
                                 type="radio"
                                 class=radio_class
-                                value=third_tone.clone()
-                                bind:group=first_tone_value
-                                on:click=play_first_s_third_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_first_s_fourth_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&first_syllable(), &fourth_tone)
-                                />
-                            </audio>
-                            {fourth_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=fourth_tone.clone()
-                                bind:group=first_tone_value
-                                on:click=play_first_s_fourth_t_audio
+                                name=group_name.clone()
+                                value=tone_value
+                                prop:checked=is_checked
+                                on:change=on_change
+                                on:click=play_audio
                                 required
                             />
                         </label>
+                    }
+                })
+                .collect_view()}
+        </fieldset>
+    }
+}
 
-                    </fieldset>
-                    <fieldset>
-                        <legend class="fieldset-legend">
-                            {t!(i18n, exercise.select_second_tone_value)}
-                        </legend>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_second_s_first_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&second_syllable(), &first_tone)
-                                />
-                            </audio>
-                            {first_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=first_tone
-                                bind:group=second_tone_value
-                                on:click=play_second_s_first_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_second_s_second_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&second_syllable(), &second_tone)
-                                />
-                            </audio>
-                            {second_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=second_tone
-                                bind:group=second_tone_value
-                                on:click=play_second_s_second_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_second_s_third_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&second_syllable(), &third_tone)
-                                />
-                            </audio>
-                            {third_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=third_tone
-                                bind:group=second_tone_value
-                                on:click=play_second_s_third_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            <audio
-                                node_ref=audio_element_second_s_fourth_t
-                                on:ended=move |_| { set_audio_playing(false) }
-                            >
-                                <source
-                                    type="audio/mpeg"
-                                    src=format_toned_syllable_url(&second_syllable(), &fourth_tone)
+// A component to display all tones of every syllable, the chosen tones are stored in order in
+// `tone_values`
+#[component]
+pub fn TouchToneButtons(
+    pronounced_pinyin: Signal<String>,
+    tone_values: RwSignal<Vec<String>>,
+    set_audio_playing: WriteSignal<bool>,
+) -> impl IntoView {
+    view! {
+        {move || {
+            view! {
+                <div>
+                    {get_syllables_from_pinyin(&pronounced_pinyin())
+                        .into_iter()
+                        .enumerate()
+                        .map(|(syllable_idx, syllable)| {
+                            view! {
+                                <SyllableToneButtons
+                                    syllable
+                                    syllable_idx
+                                    tone_values
+                                    set_audio_playing
                                 />
-                            </audio>
-                            {fourth_tone.clone()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=fourth_tone
-                                bind:group=second_tone_value
-                                on:click=play_second_s_fourth_t_audio
-                                required
-                            />
-                        </label>
-                        <label class=radio_space_class>
-                            {Tone::NeutralTone.to_string()}
-                            <input
-                                type="radio"
-                                class=radio_class
-                                value=Tone::NeutralTone.to_string()
-                                bind:group=second_tone_value
-                                required
-                            />
-                        </label>
-                    </fieldset>
+                            }
+                        })
+                        .collect_view()}
                 </div>
             }
         }}
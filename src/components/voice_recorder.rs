@@ -0,0 +1,161 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobEvent, MediaRecorder, MediaStream, MediaStreamConstraints};
+
+use crate::api::decode_audio_samples;
+use crate::i18n::*;
+use crate::pitch::ANALYSIS_SAMPLE_RATE;
+
+async fn get_microphone_stream() -> Result<MediaStream, JsValue> {
+    let media_devices = window().navigator().media_devices()?;
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_audio(&JsValue::TRUE);
+    let promise = media_devices.get_user_media_with_constraints(&constraints)?;
+    JsFuture::from(promise).await?.dyn_into::<MediaStream>()
+}
+
+fn stop_stream(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        if let Ok(track) = track.dyn_into::<web_sys::MediaStreamTrack>() {
+            track.stop();
+        }
+    }
+}
+
+// A recording with its event handlers, which must live as long as the recorder can call them.
+// Dropping it releases the microphone, stopping the recording first if it is still running
+struct ActiveRecording {
+    media_recorder: MediaRecorder,
+    stream: MediaStream,
+    _on_data: Closure<dyn FnMut(BlobEvent)>,
+    _on_stop: Closure<dyn FnMut()>,
+}
+
+impl Drop for ActiveRecording {
+    fn drop(&mut self) {
+        // The handlers are about to be freed, a pending recording is discarded
+        self.media_recorder.set_ondataavailable(None);
+        self.media_recorder.set_onstop(None);
+        // Fails harmlessly when the recording already stopped
+        let _ = self.media_recorder.stop();
+        stop_stream(&self.stream);
+    }
+}
+
+// A component recording the microphone, the only part of production mode that needs it.
+// Recordings are handed over as mono samples at `ANALYSIS_SAMPLE_RATE`.
+#[component]
+pub fn VoiceRecorder(on_recorded: Callback<Vec<f32>>) -> impl IntoView {
+    let i18n = use_i18n();
+    // The last recording is kept until the next one or the unmount, so that its handlers
+    // outlive its stop event
+    let recorder = StoredValue::new(None::<SendWrapper<ActiveRecording>>);
+    let (recording, set_recording) = signal(false);
+    let (microphone_error, set_microphone_error) = signal(false);
+
+    let start_recording = move || {
+        spawn_local(async move {
+            let stream = match get_microphone_stream().await {
+                Ok(stream) => stream,
+                Err(_) => {
+                    set_microphone_error(true);
+                    return;
+                }
+            };
+            let media_recorder = MediaRecorder::new_with_media_stream(&stream)
+                .expect("Failed to create MediaRecorder.");
+            let chunks = js_sys::Array::new();
+            let on_data = Closure::<dyn FnMut(BlobEvent)>::new({
+                let chunks = chunks.clone();
+                move |ev: BlobEvent| {
+                    if let Some(data) = ev.data() {
+                        chunks.push(&data);
+                    }
+                }
+            });
+            media_recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+            let on_stop = Closure::<dyn FnMut()>::new({
+                let stream = stream.clone();
+                move || {
+                    stop_stream(&stream);
+                    let chunks = chunks.clone();
+                    spawn_local(async move {
+                        let blob = Blob::new_with_blob_sequence(&chunks)
+                            .expect("Failed to build Blob from recorded chunks.");
+                        let Ok(array_buffer) = JsFuture::from(blob.array_buffer()).await else {
+                            return;
+                        };
+                        let bytes = js_sys::Uint8Array::new(&array_buffer).to_vec();
+                        if let Some(samples) =
+                            decode_audio_samples(&bytes, ANALYSIS_SAMPLE_RATE).await
+                        {
+                            // The parent may be gone by the time the recording is decoded
+                            let _ = on_recorded.try_run(samples);
+                        }
+                    });
+                }
+            });
+            media_recorder.set_onstop(Some(on_stop.as_ref().unchecked_ref()));
+            media_recorder
+                .start()
+                .expect("Failed to start MediaRecorder.");
+            let active_recording = SendWrapper::new(ActiveRecording {
+                media_recorder,
+                stream,
+                _on_data: on_data,
+                _on_stop: on_stop,
+            });
+            // Unmounted while the microphone was requested, dropping it releases the microphone
+            if recorder.try_set_value(Some(active_recording)).is_some() {
+                return;
+            }
+            set_microphone_error(false);
+            set_recording(true);
+        });
+    };
+    let stop_recording = move || {
+        recorder.with_value(|active_recording| {
+            if let Some(active_recording) = active_recording {
+                let _ = active_recording.media_recorder.stop();
+            }
+        });
+        set_recording(false);
+    };
+    on_cleanup(move || {
+        recorder.try_update_value(|active_recording| active_recording.take());
+    });
+
+    view! {
+        <div class="flex flex-col items-center">
+            {move || {
+                if recording() {
+                    view! {
+                        <button
+                            class="btn btn-error rounded-md text-white"
+                            on:click=move |_| stop_recording()
+                        >
+                            {t!(i18n, exercise.stop_recording)}
+                        </button>
+                    }
+                        .into_any()
+                } else {
+                    view! {
+                        <button
+                            class="btn btn-primary rounded-md text-white"
+                            on:click=move |_| start_recording()
+                        >
+                            {t!(i18n, exercise.start_recording)}
+                        </button>
+                    }
+                        .into_any()
+                }
+            }}
+            <Show when=microphone_error>
+                <p class="label text-error">{t!(i18n, exercise.microphone_error)}</p>
+            </Show>
+        </div>
+    }
+}
@@ -6,10 +6,15 @@ use leptos_router::{components::*, path};
 
 // Modules
 mod api;
+mod audio;
 mod components;
-mod exercise;
 mod pages;
-mod utils;
+mod pitch;
+mod tone_analysis;
+use shuangshuang_core::{
+    binary_corpus, choices, corpus, dictionary, exercise, high_scores, minimal_pairs,
+    pinyin_drills, session, utils,
+};
 
 // Top-Level pages
 use crate::pages::home::Home;
@@ -0,0 +1,335 @@
+use crate::audio::get_rms;
+use crate::exercise::Tone;
+
+/// Rate recordings are resampled to before analysis, plenty for speech F0
+pub const ANALYSIS_SAMPLE_RATE: f32 = 16_000.0;
+const YIN_THRESHOLD: f32 = 0.15;
+const MIN_FREQUENCY: f32 = 60.0;
+const MAX_FREQUENCY: f32 = 500.0;
+const FRAME_DURATION: f32 = 0.04;
+const HOP_DURATION: f32 = 0.01;
+// Frames quieter than this ratio of the loudest frame are considered silent
+const SILENCE_RATIO: f32 = 0.1;
+// Narrower ranges are drawn over this span around the median, so that the jitter of a level
+// tone is not stretched over the whole Chao scale
+const MIN_CHAO_SPAN_SEMITONES: f32 = 6.0;
+
+/// One analysis frame of an F0 contour, `frequency` is None when the frame is unvoiced
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PitchFrame {
+    pub time: f32,
+    pub frequency: Option<f32>,
+    pub rms: f32,
+}
+
+/// A point of a contour on the Chao scale, `time` is normalized between 0 and 1
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ContourPoint {
+    pub time: f32,
+    pub level: f32,
+}
+
+/// Estimates the fundamental frequency of a frame with the YIN algorithm
+pub fn get_yin_frequency(frame: &[f32], sample_rate: f32) -> Option<f32> {
+    let window = frame.len() / 2;
+    let min_tau = (sample_rate / MAX_FREQUENCY).floor() as usize;
+    let max_tau = ((sample_rate / MIN_FREQUENCY).ceil() as usize).min(window);
+    if min_tau < 2 || min_tau >= max_tau {
+        return None;
+    }
+    // Difference function
+    let mut diffs = vec![0.0f32; max_tau + 1];
+    for (tau, diff) in diffs.iter_mut().enumerate().skip(1) {
+        *diff = (0..window)
+            .map(|j| {
+                let delta = frame[j] - frame[j + tau];
+                delta * delta
+            })
+            .sum();
+    }
+    // Cumulative mean normalized difference function
+    let mut cmnd = vec![1.0f32; max_tau + 1];
+    let mut running_sum = 0.0;
+    for tau in 1..=max_tau {
+        running_sum += diffs[tau];
+        cmnd[tau] = if running_sum > 0.0 {
+            diffs[tau] * tau as f32 / running_sum
+        } else {
+            1.0
+        };
+    }
+    let mut tau = min_tau;
+    while tau < max_tau {
+        if cmnd[tau] < YIN_THRESHOLD {
+            while tau + 1 < max_tau && cmnd[tau + 1] < cmnd[tau] {
+                tau += 1;
+            }
+            return Some(sample_rate / get_interpolated_tau(&cmnd, tau));
+        }
+        tau += 1;
+    }
+    None
+}
+
+// Parabolic interpolation around the local minimum for sub-sample precision
+fn get_interpolated_tau(cmnd: &[f32], tau: usize) -> f32 {
+    if tau < 1 || tau + 1 >= cmnd.len() {
+        return tau as f32;
+    }
+    let (prev, current, next) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+    let denominator = prev + next - 2.0 * current;
+    if denominator.abs() < f32::EPSILON {
+        tau as f32
+    } else {
+        tau as f32 + (prev - next) / (2.0 * denominator)
+    }
+}
+
+/// Computes the F0 contour of mono PCM samples, one frame every 10ms
+pub fn get_pitch_contour(samples: &[f32], sample_rate: f32) -> Vec<PitchFrame> {
+    let frame_size = (sample_rate * FRAME_DURATION) as usize;
+    let hop_size = ((sample_rate * HOP_DURATION) as usize).max(1);
+    if frame_size == 0 || samples.len() < frame_size {
+        return vec![];
+    }
+    let frames: Vec<&[f32]> = samples.windows(frame_size).step_by(hop_size).collect();
+    let max_rms = frames.iter().map(|f| get_rms(f)).fold(0.0, f32::max);
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let rms = get_rms(frame);
+            let frequency = if rms < max_rms * SILENCE_RATIO {
+                None
+            } else {
+                get_yin_frequency(frame, sample_rate)
+            };
+            PitchFrame {
+                time: (i * hop_size + frame_size / 2) as f32 / sample_rate,
+                frequency,
+                rms,
+            }
+        })
+        .collect()
+}
+
+pub fn get_semitones(frequency: f32, reference: f32) -> f32 {
+    12.0 * (frequency / reference).log2()
+}
+
+/// Maps the voiced frames of a contour on the 1-5 Chao scale, using the speaker's own range
+/// but at least `MIN_CHAO_SPAN_SEMITONES`. The contour is split in segments at unvoiced frames
+pub fn get_chao_contour(contour: &[PitchFrame]) -> Vec<Vec<ContourPoint>> {
+    let mut frequencies: Vec<f32> = contour.iter().filter_map(|frame| frame.frequency).collect();
+    if frequencies.len() < 2 {
+        return vec![];
+    }
+    frequencies.sort_by(f32::total_cmp);
+    let median_frequency = frequencies[frequencies.len() / 2];
+    let low = get_semitones(frequencies[0], median_frequency);
+    let high = get_semitones(frequencies[frequencies.len() - 1], median_frequency);
+    let (low, span) = if high - low < MIN_CHAO_SPAN_SEMITONES {
+        (-MIN_CHAO_SPAN_SEMITONES / 2.0, MIN_CHAO_SPAN_SEMITONES)
+    } else {
+        (low, high - low)
+    };
+    let voiced_times = || {
+        contour
+            .iter()
+            .filter(|frame| frame.frequency.is_some())
+            .map(|frame| frame.time)
+    };
+    let start = voiced_times().next().unwrap_or_default();
+    let duration = (voiced_times().next_back().unwrap_or_default() - start).max(f32::EPSILON);
+    contour
+        .split(|frame| frame.frequency.is_none())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .iter()
+                .filter_map(|frame| {
+                    frame.frequency.map(|frequency| ContourPoint {
+                        time: (frame.time - start) / duration,
+                        level: (1.0
+                            + 4.0 * (get_semitones(frequency, median_frequency) - low) / span)
+                            .clamp(1.0, 5.0),
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Idealized Chao tone letters of a tone, as evenly spaced levels
+pub fn get_ideal_tone_levels(tone: Tone) -> &'static [f32] {
+    match tone {
+        Tone::Tone1 => &[5.0, 5.0],
+        Tone::Tone2 => &[3.0, 5.0],
+        Tone::Tone3 => &[2.0, 1.0, 4.0],
+        Tone::Tone4 => &[5.0, 1.0],
+        Tone::NeutralTone => &[3.0, 2.5],
+    }
+}
+
+/// Idealized contour of a tone pair, the first tone taking the first half of the time
+pub fn get_ideal_tone_pair_contour(tone_pair: (Tone, Tone)) -> Vec<Vec<ContourPoint>> {
+    [tone_pair.0, tone_pair.1]
+        .iter()
+        .enumerate()
+        .map(|(syllable_idx, tone)| {
+            let levels = get_ideal_tone_levels(*tone);
+            levels
+                .iter()
+                .enumerate()
+                .map(|(i, level)| ContourPoint {
+                    time: (syllable_idx as f32 + i as f32 / (levels.len() - 1) as f32) / 2.0,
+                    level: *level,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: f32 = 16_000.0;
+
+    fn get_sine(frequency: f32, duration: f32) -> Vec<f32> {
+        (0..(SAMPLE_RATE * duration) as usize)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin())
+            .collect()
+    }
+
+    // A sine whose frequency moves linearly, with a few harmonics like a voice
+    fn get_glide(start_frequency: f32, end_frequency: f32, duration: f32) -> Vec<f32> {
+        let nb_samples = (SAMPLE_RATE * duration) as usize;
+        let mut phase = 0.0f32;
+        (0..nb_samples)
+            .map(|i| {
+                let progress = i as f32 / nb_samples as f32;
+                let frequency = start_frequency + (end_frequency - start_frequency) * progress;
+                phase += 2.0 * PI * frequency / SAMPLE_RATE;
+                phase.sin() + 0.5 * (2.0 * phase).sin() + 0.25 * (3.0 * phase).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn yin_finds_sine_frequency() {
+        for frequency in [110.0, 220.0, 330.0] {
+            let sine = get_sine(frequency, 0.04);
+            let estimate = get_yin_frequency(&sine, SAMPLE_RATE).expect("Sine should be voiced");
+            assert!(
+                (estimate - frequency).abs() / frequency < 0.01,
+                "expected {frequency}, got {estimate}"
+            );
+        }
+    }
+
+    #[test]
+    fn yin_finds_fundamental_of_harmonic_tone() {
+        let tone = get_glide(150.0, 150.0, 0.04);
+        let estimate = get_yin_frequency(&tone, SAMPLE_RATE).expect("Tone should be voiced");
+        assert!((estimate - 150.0).abs() < 2.0, "got {estimate}");
+    }
+
+    #[test]
+    fn noise_and_silence_are_unvoiced() {
+        let mut state = 12345u32;
+        let noise: Vec<f32> = (0..640)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as f32 / 32768.0 - 1.0
+            })
+            .collect();
+        assert_eq!(get_yin_frequency(&noise, SAMPLE_RATE), None);
+        let mut samples = vec![0.0; 1600];
+        samples.extend(get_sine(200.0, 0.2));
+        let contour = get_pitch_contour(&samples, SAMPLE_RATE);
+        assert_eq!(contour[0].frequency, None);
+        assert!(contour[contour.len() - 1].frequency.is_some());
+    }
+
+    #[test]
+    fn rising_glide_gives_rising_chao_contour() {
+        let contour = get_pitch_contour(&get_glide(150.0, 250.0, 0.5), SAMPLE_RATE);
+        let chao = get_chao_contour(&contour);
+        assert_eq!(chao.len(), 1);
+        let chao = &chao[0];
+        assert!(chao.len() > 10);
+        assert!(chao[0].level < 1.5);
+        assert!(chao[chao.len() - 1].level > 4.5);
+        assert_eq!(chao[0].time, 0.0);
+        assert_eq!(chao[chao.len() - 1].time, 1.0);
+    }
+
+    #[test]
+    fn level_tone_jitter_stays_level() {
+        let frames: Vec<PitchFrame> = [200.0, 203.0, 198.0, 201.0, 199.0]
+            .iter()
+            .enumerate()
+            .map(|(i, frequency)| PitchFrame {
+                time: i as f32 * 0.01,
+                frequency: Some(*frequency),
+                rms: 1.0,
+            })
+            .collect();
+        let chao = get_chao_contour(&frames);
+        assert!(chao[0].iter().all(|point| (point.level - 3.0).abs() < 0.5));
+    }
+
+    #[test]
+    fn chao_contour_is_split_at_unvoiced_frames() {
+        let frames: Vec<PitchFrame> = [Some(150.0), Some(160.0), None, None, Some(250.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, frequency)| PitchFrame {
+                time: i as f32 * 0.01,
+                frequency: *frequency,
+                rms: 1.0,
+            })
+            .collect();
+        let chao = get_chao_contour(&frames);
+        assert_eq!(chao.len(), 2);
+        assert_eq!(chao[0].len(), 2);
+        assert_eq!(chao[1][0].time, 1.0);
+        assert_eq!(chao[1][0].level, 5.0);
+    }
+
+    #[test]
+    fn ideal_tone_pair_contour_works() {
+        let contour = get_ideal_tone_pair_contour((Tone::Tone2, Tone::Tone3));
+        assert_eq!(contour.len(), 2);
+        assert_eq!(
+            contour[0][0],
+            ContourPoint {
+                time: 0.0,
+                level: 3.0
+            }
+        );
+        assert_eq!(
+            contour[0][1],
+            ContourPoint {
+                time: 0.5,
+                level: 5.0
+            }
+        );
+        assert_eq!(
+            contour[1][1],
+            ContourPoint {
+                time: 0.75,
+                level: 1.0
+            }
+        );
+        assert_eq!(
+            contour[1][2],
+            ContourPoint {
+                time: 1.0,
+                level: 4.0
+            }
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use crate::exercise::Tone;
+use crate::pitch::{get_pitch_contour, get_semitones, PitchFrame};
+
+// Voiced runs shorter than this many frames are treated as glitches
+const MIN_VOICED_FRAMES: usize = 5;
+// Ratio of the syllable trimmed at each edge before classification
+const EDGE_TRIM_RATIO: f32 = 0.1;
+const RISE_SEMITONES: f32 = 2.0;
+const FALL_SEMITONES: f32 = -2.5;
+// A dipping contour must fall a little then rise clearly, from below the speaker's median
+const DIP_FALL_SEMITONES: f32 = 0.5;
+const DIP_RISE_SEMITONES: f32 = 1.5;
+// Below this level relative to the speaker's median, a syllable is low pitched
+const LOW_SEMITONES: f32 = -1.5;
+// A second syllable this short compared to the first one is considered neutral
+const NEUTRAL_DURATION_RATIO: f32 = 0.6;
+// Below this duration ratio between two syllables, a boundary becomes less trustworthy
+const BALANCED_SYLLABLES_RATIO: f32 = 0.25;
+/// Below this confidence, a syllable boundary should not be used to slice a recording
+pub const MIN_BOUNDARY_CONFIDENCE: f32 = 0.5;
+
+/// Boundary between the two syllables of a recording, in seconds, with a confidence between 0
+/// and 1
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SyllableBoundary {
+    pub time: f32,
+    pub confidence: f32,
+}
+
+/// Frames `start..end` of a contour belonging to one syllable
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SyllableSegment {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SyllableSegment {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+fn get_voiced_runs(contour: &[PitchFrame]) -> Vec<SyllableSegment> {
+    let mut runs: Vec<SyllableSegment> = vec![];
+    let mut run_start: Option<usize> = None;
+    for (i, frame) in contour.iter().enumerate() {
+        match (frame.frequency, run_start) {
+            (Some(_), None) => run_start = Some(i),
+            (None, Some(start)) => {
+                runs.push(SyllableSegment { start, end: i });
+                run_start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(start) = run_start {
+        runs.push(SyllableSegment {
+            start,
+            end: contour.len(),
+        });
+    }
+    runs.retain(|run| run.len() >= MIN_VOICED_FRAMES);
+    runs
+}
+
+// Splits a segment at its quietest frame, looking only in its middle half
+fn split_at_energy_dip(contour: &[PitchFrame], segment: SyllableSegment) -> [SyllableSegment; 2] {
+    let quarter = segment.len() / 4;
+    let split = (segment.start + quarter..segment.end - quarter)
+        .min_by(|a, b| contour[*a].rms.total_cmp(&contour[*b].rms))
+        .unwrap_or(segment.start + segment.len() / 2);
+    [
+        SyllableSegment {
+            start: segment.start,
+            end: split,
+        },
+        SyllableSegment {
+            start: split,
+            end: segment.end,
+        },
+    ]
+}
+
+/// Splits the voiced part of a contour into `nb_syllables` segments, merging the closest
+/// voiced runs or splitting the longest ones at their energy dip
+pub fn segment_syllables(contour: &[PitchFrame], nb_syllables: usize) -> Vec<SyllableSegment> {
+    let mut segments = get_voiced_runs(contour);
+    while segments.len() > nb_syllables {
+        let closest = (0..segments.len() - 1)
+            .min_by_key(|i| segments[i + 1].start - segments[*i].end)
+            .unwrap();
+        segments[closest].end = segments[closest + 1].end;
+        segments.remove(closest + 1);
+    }
+    while !segments.is_empty() && segments.len() < nb_syllables {
+        let longest = (0..segments.len())
+            .max_by_key(|i| segments[*i].len())
+            .unwrap();
+        if segments[longest].len() < 2 * MIN_VOICED_FRAMES {
+            break;
+        }
+        let halves = split_at_energy_dip(contour, segments[longest]);
+        segments.splice(longest..longest + 1, halves);
+    }
+    segments
+}
+
+/// Finds where the second syllable of a two-syllable recording starts. A pause between the
+/// syllables is fully trusted, otherwise the confidence depends on how deep the energy dip
+/// between them is
+pub fn find_syllable_boundary(contour: &[PitchFrame]) -> Option<SyllableBoundary> {
+    let segments = segment_syllables(contour, 2);
+    let [first, second] = segments[..] else {
+        return None;
+    };
+    let time = (contour[first.end - 1].time + contour[second.start].time) / 2.0;
+    let dip_confidence = if second.start > first.end {
+        1.0
+    } else {
+        let get_peak = |segment: SyllableSegment| {
+            contour[segment.start..segment.end]
+                .iter()
+                .map(|f| f.rms)
+                .fold(0.0, f32::max)
+        };
+        let peak = get_peak(first).min(get_peak(second));
+        if peak <= 0.0 {
+            0.0
+        } else {
+            (1.0 - contour[second.start].rms / peak).clamp(0.0, 1.0)
+        }
+    };
+    let (shorter, longer) = if first.len() < second.len() {
+        (first.len(), second.len())
+    } else {
+        (second.len(), first.len())
+    };
+    let balance = (shorter as f32 / longer as f32 / BALANCED_SYLLABLES_RATIO).min(1.0);
+    Some(SyllableBoundary {
+        time,
+        confidence: dip_confidence * balance,
+    })
+}
+
+fn get_mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len().max(1) as f32
+}
+
+/// Classifies the pitch of one syllable, given in semitones relative to the speaker's median
+pub fn classify_tone(semitones: &[f32]) -> Tone {
+    let trim = (semitones.len() as f32 * EDGE_TRIM_RATIO) as usize;
+    let core = &semitones[trim..semitones.len() - trim];
+    if core.len() < 4 {
+        return Tone::NeutralTone;
+    }
+    let quarter = (core.len() / 4).max(1);
+    let start = get_mean(&core[..quarter]);
+    let end = get_mean(&core[core.len() - quarter..]);
+    let (min_idx, min) = core
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, v)| (i, *v))
+        .unwrap();
+    let min_position = min_idx as f32 / (core.len() - 1) as f32;
+    let delta = end - start;
+    let is_dipping = (0.2..0.8).contains(&min_position)
+        && start - min >= DIP_FALL_SEMITONES
+        && end - min >= DIP_RISE_SEMITONES
+        && min <= LOW_SEMITONES;
+    if is_dipping {
+        Tone::Tone3
+    } else if delta >= RISE_SEMITONES {
+        Tone::Tone2
+    } else if delta <= FALL_SEMITONES {
+        Tone::Tone4
+    } else if get_mean(core) <= LOW_SEMITONES {
+        Tone::Tone3
+    } else {
+        Tone::Tone1
+    }
+}
+
+/// Detects the tones of a recording of `nb_syllables` syllables
+#[allow(dead_code)]
+pub fn analyze_tones(samples: &[f32], sample_rate: f32, nb_syllables: usize) -> Vec<Tone> {
+    analyze_contour_tones(&get_pitch_contour(samples, sample_rate), nb_syllables)
+}
+
+/// Detects the tones of the F0 contour of a recording of `nb_syllables` syllables
+pub fn analyze_contour_tones(contour: &[PitchFrame], nb_syllables: usize) -> Vec<Tone> {
+    let segments = segment_syllables(contour, nb_syllables);
+    let mut voiced_frequencies: Vec<f32> = contour.iter().filter_map(|f| f.frequency).collect();
+    if voiced_frequencies.is_empty() {
+        return vec![];
+    }
+    voiced_frequencies.sort_by(f32::total_cmp);
+    let median = voiced_frequencies[voiced_frequencies.len() / 2];
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            if i > 0 && (segment.len() as f32) < segments[0].len() as f32 * NEUTRAL_DURATION_RATIO {
+                return Tone::NeutralTone;
+            }
+            let semitones: Vec<f32> = contour[segment.start..segment.end]
+                .iter()
+                .filter_map(|f| f.frequency)
+                .map(|f| get_semitones(f, median))
+                .collect();
+            classify_tone(&semitones)
+        })
+        .collect()
+}
+
+/// Tells for each expected syllable whether the learner produced its tone
+pub fn grade_produced_tones(produced: &[Tone], expected: (Tone, Tone)) -> Vec<bool> {
+    [expected.0, expected.1]
+        .iter()
+        .enumerate()
+        .map(|(i, tone)| produced.get(i) == Some(tone))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::decode_wav;
+
+    fn analyze_fixture(bytes: &[u8]) -> Vec<Tone> {
+        let (samples, sample_rate) = decode_wav(bytes).expect("Fixture should decode");
+        analyze_tones(&samples, sample_rate as f32, 2)
+    }
+
+    #[test]
+    fn classifies_level_tones() {
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/fei1ji1.wav"));
+        assert_eq!(tones, vec![Tone::Tone1, Tone::Tone1]);
+    }
+
+    #[test]
+    fn classifies_rising_and_falling_tones() {
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/xue2xiao4.wav"));
+        assert_eq!(tones, vec![Tone::Tone2, Tone::Tone4]);
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/wen4ti2.wav"));
+        assert_eq!(tones, vec![Tone::Tone4, Tone::Tone2]);
+    }
+
+    #[test]
+    fn classifies_half_and_full_third_tones() {
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/lao3shi1.wav"));
+        assert_eq!(tones, vec![Tone::Tone3, Tone::Tone1]);
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/ni2hao3.wav"));
+        assert_eq!(tones, vec![Tone::Tone2, Tone::Tone3]);
+    }
+
+    #[test]
+    fn classifies_neutral_tone() {
+        let tones = analyze_fixture(include_bytes!("../tests/fixtures/ma1ma.wav"));
+        assert_eq!(tones, vec![Tone::Tone1, Tone::NeutralTone]);
+    }
+
+    #[test]
+    fn segments_syllables_without_pause() {
+        let (samples, sample_rate) =
+            decode_wav(include_bytes!("../tests/fixtures/da4jia1.wav")).unwrap();
+        let contour = get_pitch_contour(&samples, sample_rate as f32);
+        let segments = segment_syllables(&contour, 2);
+        assert_eq!(segments.len(), 2);
+        // The boundary is 0.4s into the recording, frames are 10ms apart
+        let boundary = contour[segments[1].start].time;
+        assert!((boundary - 0.4).abs() < 0.05, "boundary at {boundary}");
+        assert_eq!(
+            analyze_tones(&samples, sample_rate as f32, 2),
+            vec![Tone::Tone4, Tone::Tone1]
+        );
+    }
+
+    #[test]
+    fn finds_syllable_boundary_with_confidence() {
+        let (samples, sample_rate) =
+            decode_wav(include_bytes!("../tests/fixtures/fei1ji1.wav")).unwrap();
+        let contour = get_pitch_contour(&samples, sample_rate as f32);
+        let boundary = find_syllable_boundary(&contour).expect("Pair should have a boundary");
+        assert_eq!(boundary.confidence, 1.0);
+
+        let (samples, sample_rate) =
+            decode_wav(include_bytes!("../tests/fixtures/da4jia1.wav")).unwrap();
+        let contour = get_pitch_contour(&samples, sample_rate as f32);
+        let boundary = find_syllable_boundary(&contour).expect("Pair should have a boundary");
+        assert!(
+            (boundary.time - 0.4).abs() < 0.05,
+            "boundary at {}",
+            boundary.time
+        );
+        assert!(boundary.confidence >= MIN_BOUNDARY_CONFIDENCE);
+    }
+
+    #[test]
+    fn steady_tone_has_low_boundary_confidence() {
+        let sample_rate = 16_000.0;
+        let samples: Vec<f32> = (0..(sample_rate * 0.6) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * 180.0 * i as f32 / sample_rate).sin())
+            .collect();
+        let contour = get_pitch_contour(&samples, sample_rate);
+        let boundary = find_syllable_boundary(&contour).expect("Tone should be split");
+        assert!(boundary.confidence < MIN_BOUNDARY_CONFIDENCE);
+        assert_eq!(find_syllable_boundary(&contour[..3]), None);
+    }
+
+    #[test]
+    fn grade_produced_tones_works() {
+        let expected = (Tone::Tone2, Tone::Tone3);
+        assert_eq!(
+            grade_produced_tones(&[Tone::Tone2, Tone::Tone3], expected),
+            vec![true, true]
+        );
+        assert_eq!(
+            grade_produced_tones(&[Tone::Tone3, Tone::Tone3], expected),
+            vec![false, true]
+        );
+        assert_eq!(
+            grade_produced_tones(&[Tone::Tone2], expected),
+            vec![true, false]
+        );
+    }
+}